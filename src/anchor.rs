@@ -0,0 +1,162 @@
+//! anchors links point to with `#anchor`
+//!
+//! every heading is an anchor named after its title, and so is every tag.
+//! vimwiki matches these rules:
+//!
+//! - `#Title` is the first anchor called `Title`
+//! - `#Chapter#Section` is the first `Section` after the first `Chapter`,
+//!   any number of `#` separated parts can be given
+//! - headings with the title of an earlier heading get a unique id with a
+//!   counter, `#Title-2` is the second heading called `Title`. tags are
+//!   counted the same way, but apart from headings
+//! - a heading and a tag of the same name are both `#name`, the heading wins
+//!
+//! html ids are the unique ids with whitespace replaced by `-`, the ids of
+//! tags start with `tag-` so they never collide with the ids of headings.
+
+use std::collections::HashMap;
+
+use crate::ast::{Heading, Tags};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::span::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnchorKind {
+    Heading,
+    Tag,
+}
+
+/// a place in a page links can point to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anchor<'a> {
+    pub kind: AnchorKind,
+    /// the heading title or tag name
+    pub name: String,
+    /// `name`, with a counter if an earlier anchor has the same name
+    pub id: String,
+    /// names of the headings the anchor is below, outermost first, and the
+    /// anchor's own name
+    pub path: Vec<String>,
+    /// heading level, 0 for tags
+    pub level: usize,
+    /// the `Heading` or `Tag` node
+    pub node: &'a Node,
+}
+
+impl Anchor<'_> {
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// the `id` attribute of the anchor in html
+    pub fn html_id(&self) -> String {
+        match self.kind {
+            AnchorKind::Heading => html_id(&self.id),
+            AnchorKind::Tag => format!("tag-{}", html_id(&self.id)),
+        }
+    }
+
+    /// the nested anchor through all parent headings, like `Chapter#Section`
+    pub fn full_path(&self) -> String {
+        self.path.join("#")
+    }
+}
+
+/// `anchor` as an html id
+pub fn html_id(anchor: &str) -> String {
+    anchor.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+/// all anchors of a page, in document order
+#[derive(Debug, Clone, Default)]
+pub struct Anchors<'a> {
+    anchors: Vec<Anchor<'a>>,
+}
+
+impl<'a> Anchors<'a> {
+    pub fn new(root: &'a Node) -> Self {
+        let mut anchors = Vec::new();
+        // headings and tags are counted apart, like vimwiki does
+        let mut seen: HashMap<(AnchorKind, String), usize> = HashMap::new();
+        // the open headings as (level, title)
+        let mut parents: Vec<(usize, String)> = Vec::new();
+        let mut push = |anchors: &mut Vec<Anchor<'a>>, kind, name: String, path, level, node| {
+            let count = seen.entry((kind, name.clone())).or_default();
+            *count += 1;
+            let id = match *count {
+                1 => name.clone(),
+                n => format!("{name}-{n}"),
+            };
+            anchors.push(Anchor {
+                kind,
+                name,
+                id,
+                path,
+                level,
+                node,
+            });
+        };
+        for block in root.children() {
+            if let Some(heading) = Heading::cast(block) {
+                let (level, title) = (heading.level(), heading.title());
+                parents.retain(|(l, _)| *l < level);
+                parents.push((level, title.clone()));
+                let path = parents.iter().map(|(_, t)| t.clone()).collect();
+                push(&mut anchors, AnchorKind::Heading, title, path, level, block);
+            }
+            for tags in crate::ast::find_all(block, SyntaxKind::Tags)
+                .into_iter()
+                .filter_map(Tags::cast)
+            {
+                for tag in tags.tags() {
+                    let mut path: Vec<_> = parents.iter().map(|(_, t)| t.clone()).collect();
+                    path.push(tag.text());
+                    push(&mut anchors, AnchorKind::Tag, tag.text(), path, 0, tag);
+                }
+            }
+        }
+        Self { anchors }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Anchor<'a>> {
+        self.anchors.iter()
+    }
+
+    /// the headings only
+    pub fn headings(&self) -> impl Iterator<Item = &Anchor<'a>> {
+        self.anchors
+            .iter()
+            .filter(|anchor| anchor.kind == AnchorKind::Heading)
+    }
+
+    /// the anchor a link with `#anchor` points to, the leading `#` is optional
+    pub fn resolve(&self, anchor: &str) -> Option<&Anchor<'a>> {
+        let anchor = anchor.trim().trim_start_matches('#');
+        if anchor.is_empty() {
+            return None;
+        }
+        if let Some(index) = first(&self.anchors, |a| a.id == anchor) {
+            return Some(&self.anchors[index]);
+        }
+        let mut rest = &self.anchors[..];
+        let mut found = None;
+        for part in anchor.split('#').map(str::trim) {
+            let index = first(rest, |a| a.name == part || a.id == part)?;
+            found = Some(&rest[index]);
+            rest = &rest[index + 1..];
+        }
+        found
+    }
+}
+
+/// the index of the first heading in `anchors` matching `matches`, or of the
+/// first tag if no heading does
+fn first(anchors: &[Anchor<'_>], matches: impl Fn(&Anchor<'_>) -> bool) -> Option<usize> {
+    let position = |kind| {
+        anchors
+            .iter()
+            .position(|anchor| anchor.kind == kind && matches(anchor))
+    };
+    position(AnchorKind::Heading).or_else(|| position(AnchorKind::Tag))
+}
@@ -1,17 +1,414 @@
 use crate::kind::SyntaxKind;
-use crate::lexer;
-use crate::parser::{self};
+use crate::parser::{self, Node};
+use crate::span::Span;
 
 pub fn print_ast(source: &str) {
-    let lexed = lexer::Lexer::new(source.into()).lex();
-    let mut nodes = parser::Parser::new(lexed.clone());
+    let root = parser::parse(source);
     println!("DOCUMENT:");
-    println!("  PARAGRAPH:");
-    nodes.parse().iter().for_each(|node| {
-        if node.kind() == SyntaxKind::NewLine {
-            println!("  PARAGRAPH:");
-        } else {
-            println!("    {}: {:?}  {}", node.kind(), node.text(), node.span())
+    root.children().iter().for_each(|node| print_node(node, 1));
+}
+
+fn print_node(node: &Node, depth: usize) {
+    let indent = "  ".repeat(depth);
+    if node.is_leaf() {
+        match node.errors() {
+            Some(error) => println!(
+                "{indent}{}: {:?}  {}  {}",
+                node.kind(),
+                node.text(),
+                node.span(),
+                error
+            ),
+            None => println!(
+                "{indent}{}: {:?}  {}",
+                node.kind(),
+                node.text(),
+                node.span()
+            ),
+        }
+    } else {
+        println!("{indent}{}:  {}", node.kind(), node.span());
+        node.children()
+            .iter()
+            .for_each(|child| print_node(child, depth + 1));
+    }
+}
+
+/// all nodes of `kind` in the subtree of `node`, in document order
+pub fn find_all(node: &Node, kind: SyntaxKind) -> Vec<&Node> {
+    let mut found = Vec::new();
+    if node.kind() == kind {
+        found.push(node);
+    }
+    for child in node.children() {
+        found.extend(find_all(child, kind));
+    }
+    found
+}
+
+fn inner_text<'a>(nodes: impl Iterator<Item = &'a Node>) -> String {
+    nodes.map(Node::text).collect::<String>().trim().to_owned()
+}
+
+/// span of `nodes` without leading and trailing whitespace
+pub fn trimmed_span<'a>(nodes: impl Iterator<Item = &'a Node>) -> Option<Span> {
+    let nodes: Vec<_> = nodes.collect();
+    let (first, last) = (nodes.first()?, nodes.last()?);
+    let text: String = nodes.iter().map(|n| n.text()).collect();
+    let leading = text.len() - text.trim_start().len();
+    let trailing = text.len() - text.trim_end().len();
+    let start = first.span().start + leading;
+    let end = last.span().end.saturating_sub(trailing).max(start);
+    Some(Span::new(start, end))
+}
+
+/// `= heading =`
+#[derive(Debug, Clone, Copy)]
+pub struct Heading<'a>(&'a Node);
+
+impl<'a> Heading<'a> {
+    pub fn cast(node: &'a Node) -> Option<Self> {
+        (node.kind() == SyntaxKind::Heading).then_some(Self(node))
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.0
+    }
+
+    /// number of `=` around the heading, 1 to 6
+    pub fn level(&self) -> usize {
+        self.0
+            .children()
+            .iter()
+            .skip_while(|c| c.kind().is_trivia())
+            .take_while(|c| c.kind() == SyntaxKind::Equal)
+            .count()
+    }
+
+    /// inline nodes between the `=` markers
+    pub fn content(&self) -> impl Iterator<Item = &'a Node> {
+        let level = self.level();
+        let children = self.0.children();
+        let start = children
+            .iter()
+            .position(|c| c.kind() == SyntaxKind::Equal)
+            .unwrap_or(0)
+            + level;
+        let end = children
+            .iter()
+            .rposition(|c| c.kind() == SyntaxKind::Equal)
+            .map(|i| i + 1 - level)
+            .unwrap_or(children.len());
+        children[start..end.max(start)].iter()
+    }
+
+    /// the text of the heading without markers and surrounding whitespace
+    pub fn title(&self) -> String {
+        inner_text(self.content())
+    }
+
+    /// span of `title`
+    pub fn title_span(&self) -> Option<Span> {
+        trimmed_span(self.content())
+    }
+
+    pub fn span(&self) -> Span {
+        self.0.span()
+    }
+}
+
+/// `[[target#anchor|description]]`
+#[derive(Debug, Clone, Copy)]
+pub struct Link<'a>(&'a Node);
+
+impl<'a> Link<'a> {
+    pub fn cast(node: &'a Node) -> Option<Self> {
+        (node.kind() == SyntaxKind::Link).then_some(Self(node))
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.0
+    }
+
+    pub fn target_node(&self) -> Option<&'a Node> {
+        self.0
+            .children()
+            .iter()
+            .find(|c| c.kind() == SyntaxKind::LinkTarget)
+    }
+
+    pub fn description_node(&self) -> Option<&'a Node> {
+        self.0
+            .children()
+            .iter()
+            .find(|c| c.kind() == SyntaxKind::LinkDescription)
+    }
+
+    /// everything before the `|`
+    pub fn target(&self) -> String {
+        self.target_node()
+            .map(|n| n.text().trim().to_owned())
+            .unwrap_or_default()
+    }
+
+    /// the page part of the target, empty for links into the current page
+    pub fn page(&self) -> String {
+        let target = self.target();
+        match target.split_once('#') {
+            Some((page, _)) => page.trim().to_owned(),
+            None => target,
+        }
+    }
+
+    /// everything after the first `#` of the target
+    pub fn anchor(&self) -> Option<String> {
+        self.target()
+            .split_once('#')
+            .map(|(_, anchor)| anchor.trim().to_owned())
+    }
+
+    pub fn description(&self) -> Option<String> {
+        self.description_node().map(|n| n.text().trim().to_owned())
+    }
+
+    /// `file`, `local`, `diary`, `https`, ... for targets like `scheme:rest`
+    pub fn scheme(&self) -> Option<String> {
+        let target = self.target();
+        let (scheme, _) = target.split_once(':')?;
+        let mut chars = scheme.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '-'));
+        valid.then(|| scheme.to_owned())
+    }
+
+    pub fn span(&self) -> Span {
+        self.0.span()
+    }
+}
+
+/// `:tag1:tag2:`
+#[derive(Debug, Clone, Copy)]
+pub struct Tags<'a>(&'a Node);
+
+impl<'a> Tags<'a> {
+    pub fn cast(node: &'a Node) -> Option<Self> {
+        (node.kind() == SyntaxKind::Tags).then_some(Self(node))
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.0
+    }
+
+    /// the `Tag` nodes
+    pub fn tags(&self) -> impl Iterator<Item = &'a Node> {
+        self.0
+            .children()
+            .iter()
+            .filter(|c| c.kind() == SyntaxKind::Tag)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.tags().map(Node::text).collect()
+    }
+
+    pub fn span(&self) -> Span {
+        self.0.span()
+    }
+}
+
+/// `%title My Page`
+#[derive(Debug, Clone, Copy)]
+pub struct Placeholder<'a>(&'a Node);
+
+impl<'a> Placeholder<'a> {
+    pub fn cast(node: &'a Node) -> Option<Self> {
+        (node.kind() == SyntaxKind::Placeholder).then_some(Self(node))
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.0
+    }
+
+    /// `title`, `date`, `template` or `nohtml`
+    pub fn name(&self) -> String {
+        let text = self.0.text();
+        text.trim_start_matches('%')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_owned()
+    }
+
+    /// everything after the name, trimmed
+    pub fn value(&self) -> String {
+        let text = self.0.text();
+        let text = text.trim().trim_start_matches('%');
+        match text.split_once([' ', '\t']) {
+            Some((_, value)) => value.trim().to_owned(),
+            None => String::new(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        self.0.span()
+    }
+}
+
+/// the `%title` of a document, if it has one
+pub fn title(root: &Node) -> Option<String> {
+    root.children()
+        .iter()
+        .filter_map(Placeholder::cast)
+        .find(|p| p.name() == "title")
+        .map(|p| p.value())
+}
+
+/// span of the top level section below the heading `title`
+///
+/// the section ends before the next heading of the same or a higher level,
+/// or before any heading unless `nested`. blank lines in front of it are not
+/// part of the section.
+pub fn section(root: &Node, title: &str, nested: bool) -> Option<Span> {
+    let blocks = root.children();
+    let start = blocks
+        .iter()
+        .position(|b| Heading::cast(b).is_some_and(|h| h.title() == title))?;
+    let level = Heading::cast(&blocks[start])?.level();
+    let end = blocks[start + 1..]
+        .iter()
+        .take_while(|b| Heading::cast(b).is_none_or(|h| nested && h.level() > level))
+        .filter(|b| !b.kind().is_trivia())
+        .last()
+        .unwrap_or(&blocks[start]);
+    Some(Span::new(blocks[start].span().start, end.span().end))
+}
+
+/// replaces the section below the heading `title` of `source` by `text`, or
+/// appends `text` after a blank line if there is no such section
+pub fn replace_section(source: &str, title: &str, text: &str) -> String {
+    match section(&parser::parse(source), title, true) {
+        Some(span) => format!("{}{text}{}", &source[..span.start], &source[span.end..]),
+        None if source.trim().is_empty() => text.to_owned(),
+        None => format!("{}\n\n{text}", source.trim_end()),
+    }
+}
+
+/// state of a `[ ]` checkbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CheckboxState {
+    /// `[ ]`
+    Open,
+    /// `[.]` up to a third of the children are done
+    Started,
+    /// `[o]` up to two thirds of the children are done
+    Half,
+    /// `[O]` most of the children are done
+    Mostly,
+    /// `[X]`
+    Done,
+    /// `[-]`
+    Rejected,
+}
+
+impl CheckboxState {
+    pub fn from_marker(marker: &str) -> Option<Self> {
+        match marker {
+            " " => Some(Self::Open),
+            "." => Some(Self::Started),
+            "o" => Some(Self::Half),
+            "O" => Some(Self::Mostly),
+            "X" => Some(Self::Done),
+            "-" => Some(Self::Rejected),
+            _ => None,
         }
-    });
+    }
+
+    /// the char between the brackets
+    pub fn marker(&self) -> &'static str {
+        match self {
+            Self::Open => " ",
+            Self::Started => ".",
+            Self::Half => "o",
+            Self::Mostly => "O",
+            Self::Done => "X",
+            Self::Rejected => "-",
+        }
+    }
+}
+
+/// `[X]`
+#[derive(Debug, Clone, Copy)]
+pub struct Checkbox<'a>(&'a Node);
+
+impl<'a> Checkbox<'a> {
+    pub fn cast(node: &'a Node) -> Option<Self> {
+        (node.kind() == SyntaxKind::Checkbox).then_some(Self(node))
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.0
+    }
+
+    pub fn state(&self) -> CheckboxState {
+        let text = self.0.text();
+        let marker = text.trim_start_matches('[').trim_end_matches(']');
+        CheckboxState::from_marker(marker).unwrap_or(CheckboxState::Open)
+    }
+
+    pub fn span(&self) -> Span {
+        self.0.span()
+    }
+}
+
+/// a single list item, with its nested lists
+#[derive(Debug, Clone, Copy)]
+pub struct ListItem<'a>(&'a Node);
+
+impl<'a> ListItem<'a> {
+    pub fn cast(node: &'a Node) -> Option<Self> {
+        (node.kind() == SyntaxKind::ListItem).then_some(Self(node))
+    }
+
+    pub fn node(&self) -> &'a Node {
+        self.0
+    }
+
+    pub fn marker(&self) -> Option<&'a Node> {
+        self.0
+            .children()
+            .iter()
+            .find(|c| c.kind() == SyntaxKind::ListMarker)
+    }
+
+    pub fn checkbox(&self) -> Option<Checkbox<'a>> {
+        self.0.children().iter().find_map(Checkbox::cast)
+    }
+
+    /// inline content after the marker and checkbox, without nested lists
+    pub fn content(&self) -> impl Iterator<Item = &'a Node> {
+        self.0
+            .children()
+            .iter()
+            .skip_while(|c| c.kind() != SyntaxKind::ListMarker)
+            .skip(1)
+            .filter(|c| !matches!(c.kind(), SyntaxKind::Checkbox | SyntaxKind::List))
+    }
+
+    /// the text of the item, continuation lines are joined with spaces
+    pub fn text(&self) -> String {
+        let text: String = self.content().map(Node::text).collect();
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    /// lists nested in this item
+    pub fn sublists(&self) -> impl Iterator<Item = &'a Node> {
+        self.0
+            .children()
+            .iter()
+            .filter(|c| c.kind() == SyntaxKind::List)
+    }
+
+    pub fn span(&self) -> Span {
+        self.0.span()
+    }
 }
@@ -0,0 +1,166 @@
+//! workspace wide checks of the links between pages
+//!
+//! every page of every wiki is parsed and each link is followed: to pages of
+//! the same or another wiki, to anchors in them and to files on disk. pages
+//! no other page links to are reported as orphans.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::anchor::Anchors;
+use crate::ast::{self, Link};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::span::Span;
+use crate::workspace::{Page, Workspace};
+
+/// what is wrong with a link or page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// `wikiN:` or `wn.name:` naming a wiki the workspace does not have
+    MissingWiki(String),
+    MissingPage(String),
+    MissingAnchor {
+        page: String,
+        anchor: String,
+    },
+    /// `file:` or `local:` link to a path which does not exist
+    MissingFile(PathBuf),
+    /// no other page links to this one
+    Orphan,
+}
+
+impl Problem {
+    /// whether the problem should fail a check, orphans are only warnings
+    pub fn is_error(&self) -> bool {
+        !matches!(self, Self::Orphan)
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingWiki(wiki) => write!(f, "wiki `{wiki}` does not exist"),
+            Self::MissingPage(page) => write!(f, "page `{page}` does not exist"),
+            Self::MissingAnchor { page, anchor } => {
+                write!(f, "page `{page}` has no anchor `#{anchor}`")
+            }
+            Self::MissingFile(path) => write!(f, "file `{}` does not exist", path.display()),
+            Self::Orphan => write!(f, "no page links here"),
+        }
+    }
+}
+
+/// a problem found in a page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub wiki: usize,
+    pub page: String,
+    /// the link target, empty for problems of the whole page
+    pub span: Span,
+    pub problem: Problem,
+}
+
+/// the file a `file:` or `local:` link in the page stored at `path` points to
+pub(crate) fn linked_file(path: &Path, target: &str) -> PathBuf {
+    let target = target.strip_prefix("//").unwrap_or(target);
+    let target = target.split_once('#').map_or(target, |(file, _)| file);
+    if let (Some(rest), Some(home)) = (target.strip_prefix("~/"), std::env::var_os("HOME")) {
+        return PathBuf::from(home).join(rest);
+    }
+    match path.parent() {
+        Some(dir) => dir.join(target),
+        None => PathBuf::from(target),
+    }
+}
+
+/// whether `scheme` names a wiki like `wiki1` or `wn.work`
+pub(crate) fn is_interwiki(scheme: &str) -> bool {
+    scheme.starts_with("wn.")
+        || scheme
+            .strip_prefix("wiki")
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// checks every link of `pages`, the pages of each wiki of `workspace`
+pub fn check_pages(workspace: &Workspace, pages: &[Vec<Page>]) -> Vec<Report> {
+    let mut reports = Vec::new();
+    let mut linked = HashSet::new();
+
+    for (wiki, wiki_pages) in pages.iter().enumerate() {
+        for page in wiki_pages {
+            for node in ast::find_all(&page.root, SyntaxKind::Link) {
+                let Some(link) = Link::cast(node) else {
+                    continue;
+                };
+                let span = link.target_node().map_or(link.span(), Node::span);
+                let report = |problem| Report {
+                    wiki,
+                    page: page.name.clone(),
+                    span,
+                    problem,
+                };
+                let target = link.target();
+                let scheme = link.scheme();
+                if let Some(scheme) = scheme.as_deref()
+                    && matches!(scheme, "file" | "local")
+                {
+                    let Some(path) = workspace.path(wiki, &page.name) else {
+                        continue;
+                    };
+                    let file = linked_file(&path, &target[scheme.len() + 1..]);
+                    if !file.exists() {
+                        reports.push(report(Problem::MissingFile(file)));
+                    }
+                    continue;
+                }
+                let Some(location) = workspace.resolve(wiki, &page.name, &target) else {
+                    if let Some(scheme) = scheme.filter(|s| is_interwiki(s)) {
+                        reports.push(report(Problem::MissingWiki(scheme)));
+                    }
+                    continue;
+                };
+                let Some(linked_page) = pages
+                    .get(location.wiki)
+                    .and_then(|p| p.iter().find(|p| p.name == location.page))
+                else {
+                    reports.push(report(Problem::MissingPage(location.page)));
+                    continue;
+                };
+                if (location.wiki, &location.page) != (wiki, &page.name) {
+                    linked.insert((location.wiki, location.page.clone()));
+                }
+                if let Some(anchor) = location.anchor.filter(|a| !a.is_empty())
+                    && Anchors::new(&linked_page.root).resolve(&anchor).is_none()
+                {
+                    reports.push(report(Problem::MissingAnchor {
+                        page: location.page,
+                        anchor,
+                    }));
+                }
+            }
+        }
+    }
+
+    for (wiki, wiki_pages) in pages.iter().enumerate() {
+        let index = workspace.wiki(wiki).map(|w| w.index.as_str());
+        for page in wiki_pages {
+            if Some(page.name.as_str()) != index && !linked.contains(&(wiki, page.name.clone())) {
+                reports.push(Report {
+                    wiki,
+                    page: page.name.clone(),
+                    span: Span::new(0, 0),
+                    problem: Problem::Orphan,
+                });
+            }
+        }
+    }
+
+    reports
+}
+
+/// reads every page of `workspace` from disk and checks its links
+pub fn check(workspace: &Workspace) -> Vec<Report> {
+    check_pages(workspace, &workspace.load_pages())
+}
@@ -0,0 +1,222 @@
+//! diary pages named after their date
+//!
+//! vimwiki keeps one page per day in the diary directory of a wiki, like
+//! `diary/2026-10-19.wiki`, and an index page listing all of them grouped
+//! by year and month, newest first.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ast::{self, Heading};
+use crate::workspace::{Page, WikiConfig, relative_page};
+
+/// heading of the diary index
+pub const HEADER: &str = "Diary";
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// a day of the gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// the date, `None` if the day does not exist
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Self> {
+        let date = Self { year, month, day };
+        ((1..=12).contains(&month) && (1..=date.days_in_month()).contains(&day)).then_some(date)
+    }
+
+    /// parses `YYYY-MM-DD`
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() || year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return None;
+        }
+        let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+        if !(digits(year) && digits(month) && digits(day)) {
+            return None;
+        }
+        Self::new(year.parse().ok()?, month.parse().ok()?, day.parse().ok()?)
+    }
+
+    /// today in UTC
+    pub fn today() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        Self::from_days((secs / 86_400) as i64)
+    }
+
+    /// the date `days` days after 1970-01-01
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+
+    pub fn is_leap_year(&self) -> bool {
+        (self.year % 4 == 0 && self.year % 100 != 0) || self.year % 400 == 0
+    }
+
+    pub fn days_in_month(&self) -> u32 {
+        match self.month {
+            2 if self.is_leap_year() => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    /// `January` to `December`
+    pub fn month_name(&self) -> &'static str {
+        MONTHS[(self.month as usize).clamp(1, 12) - 1]
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// a diary page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub date: Date,
+    /// the page name, like `diary/2026-10-19`
+    pub page: String,
+    /// title of the first heading of the page
+    pub caption: Option<String>,
+}
+
+/// the diary of a wiki
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diary {
+    /// the diary directory relative to the wiki root
+    dir: String,
+    /// the name of the index page
+    index: String,
+    /// sorted by date
+    entries: Vec<Entry>,
+}
+
+impl Diary {
+    /// the diary among `pages` of the wiki `config`
+    pub fn new(config: &WikiConfig, pages: &[Page]) -> Self {
+        let dir = config.diary.trim_matches('/').to_owned();
+        let mut entries: Vec<_> = pages
+            .iter()
+            .filter_map(|page| {
+                let name = page.name.strip_prefix(&dir)?.strip_prefix('/')?;
+                let caption = page.root.children().iter().find_map(Heading::cast);
+                Some(Entry {
+                    date: Date::parse(name)?,
+                    page: page.name.clone(),
+                    caption: caption.map(|h| h.title()).filter(|t| !t.is_empty()),
+                })
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.date);
+        Self {
+            index: format!("{dir}/{}", config.diary_index),
+            dir,
+            entries,
+        }
+    }
+
+    /// reads the diary pages of the wiki `config` from disk
+    pub fn load(config: &WikiConfig) -> Self {
+        let pages: Vec<_> = config
+            .pages()
+            .into_iter()
+            .filter(|name| config.is_diary(name))
+            .filter_map(|name| {
+                let text = std::fs::read_to_string(config.path(&name)).ok()?;
+                Some(Page::new(name, &text))
+            })
+            .collect();
+        Self::new(config, &pages)
+    }
+
+    /// every entry, oldest first
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// name of the diary index page, like `diary/diary`
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+
+    /// name of the page for `date`, whether it exists or not
+    pub fn page(&self, date: Date) -> String {
+        format!("{}/{date}", self.dir)
+    }
+
+    pub fn get(&self, date: Date) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.date == date)
+    }
+
+    /// the last entry before `date`
+    pub fn previous(&self, date: Date) -> Option<&Entry> {
+        self.entries.iter().rev().find(|entry| entry.date < date)
+    }
+
+    /// the first entry after `date`
+    pub fn next(&self, date: Date) -> Option<&Entry> {
+        self.entries.iter().find(|entry| entry.date > date)
+    }
+
+    /// the `Diary` section of the index page, entries grouped by year and
+    /// month, newest first, like `:VimwikiDiaryGenerateLinks`
+    pub fn links(&self) -> String {
+        let mut section = format!("= {HEADER} =\n");
+        let mut current = None;
+        for entry in self.entries.iter().rev() {
+            let (year, month) = (entry.date.year, entry.date.month);
+            if current.is_none_or(|(y, _)| y != year) {
+                section.push_str(&format!("\n== {year} ==\n"));
+            }
+            if current != Some((year, month)) {
+                section.push_str(&format!("\n=== {} ===\n", entry.date.month_name()));
+            }
+            current = Some((year, month));
+            let target = relative_page(&self.index, &entry.page);
+            match &entry.caption {
+                Some(caption) => section.push_str(&format!("- [[{target}|{caption}]]\n")),
+                None => section.push_str(&format!("- [[{target}]]\n")),
+            }
+        }
+        section
+    }
+
+    /// `source` of the index page with its `Diary` section regenerated
+    pub fn update_index(&self, source: &str) -> String {
+        ast::replace_section(source, HEADER, &self.links())
+    }
+}
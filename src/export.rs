@@ -0,0 +1,200 @@
+//! exporting vimwiki documents to other formats
+
+pub mod html;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod markdown;
+#[cfg(feature = "serde")]
+pub mod pandoc;
+pub mod plain;
+
+use std::str::FromStr;
+
+use crate::anchor::html_id;
+use crate::ast::Link;
+use crate::check::is_interwiki;
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+
+pub use self::html::Html;
+pub use self::plain::Plain;
+
+/// the formats a document can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Html,
+    Markdown,
+    #[cfg(feature = "serde")]
+    Json,
+    /// pandoc's json AST
+    #[cfg(feature = "serde")]
+    Pandoc,
+    Text,
+    /// text styled with ansi escape codes
+    Ansi,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "html" => Ok(Self::Html),
+            "md" | "markdown" => Ok(Self::Markdown),
+            #[cfg(feature = "serde")]
+            "json" => Ok(Self::Json),
+            #[cfg(feature = "serde")]
+            "pandoc" => Ok(Self::Pandoc),
+            #[cfg(not(feature = "serde"))]
+            "json" | "pandoc" => Err(format!("the {s} format needs the `serde` feature")),
+            "text" | "txt" => Ok(Self::Text),
+            "ansi" => Ok(Self::Ansi),
+            other => Err(format!(
+                "unknown format `{other}`, expected html, md, json, pandoc, text or ansi"
+            )),
+        }
+    }
+}
+
+/// `root` in `format`, with the default options of each exporter
+pub fn export(root: &Node, format: Format) -> String {
+    match format {
+        Format::Html => Html::new().render(root),
+        Format::Markdown => markdown::render(root),
+        #[cfg(feature = "serde")]
+        Format::Json => json::render(root),
+        #[cfg(feature = "serde")]
+        Format::Pandoc => pandoc::render(root),
+        Format::Text => Plain::new().render(root),
+        Format::Ansi => Plain::new().ansi(true).render(root),
+    }
+}
+
+/// the children of an inline node like `*bold*` without its markers
+pub(crate) fn inner(node: &Node) -> &[Node] {
+    let children = node.children();
+    let is_marker = |kind: SyntaxKind| {
+        matches!(
+            kind,
+            SyntaxKind::Astrisk
+                | SyntaxKind::Underscore
+                | SyntaxKind::Tilda
+                | SyntaxKind::CodeMarker
+                | SyntaxKind::SuperScriptMarker
+                | SyntaxKind::SubScriptMarker
+                | SyntaxKind::Dollar
+        )
+    };
+    let Some(marker) = children.first().map(Node::kind).filter(|k| is_marker(*k)) else {
+        return children;
+    };
+    let start = children.iter().take_while(|c| c.kind() == marker).count();
+    let end = children.len()
+        - children[start..]
+            .iter()
+            .rev()
+            .take_while(|c| c.kind() == marker)
+            .count();
+    &children[start..end]
+}
+
+/// the text of `nodes`, joined
+pub(crate) fn text(nodes: &[Node]) -> String {
+    nodes.iter().map(Node::text).collect()
+}
+
+/// the source and alternative text of a `{{source|alt}}` transclusion
+pub(crate) fn transclusion(node: &Node) -> (String, Option<String>) {
+    let text = node.text();
+    let text = text
+        .trim()
+        .trim_start_matches('{')
+        .trim_end_matches('}')
+        .trim();
+    match text.split_once('|') {
+        Some((source, alt)) => {
+            let alt = alt.split('|').next().unwrap_or_default().trim();
+            (source.trim().to_owned(), Some(alt.to_owned()))
+        }
+        None => (text.to_owned(), None),
+    }
+}
+
+/// the language and content of a `{{{lang` code block, or the content of a
+/// `{{$` math block
+pub(crate) fn verbatim(node: &Node) -> (String, String) {
+    let text = node.text();
+    let (first, rest) = text.split_once('\n').unwrap_or((&text, ""));
+    let info = first
+        .trim()
+        .trim_start_matches('{')
+        .trim_start_matches('$')
+        .trim()
+        .to_owned();
+    let rest = rest.trim_end();
+    let content = rest
+        .strip_suffix("}}}")
+        .or_else(|| rest.strip_suffix("}}$"))
+        .unwrap_or(rest);
+    (info, content.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// the cells of a table row, `None` for a `|---|` separator
+pub(crate) fn cells(row: &Node) -> Option<Vec<&Node>> {
+    let cells: Vec<_> = row
+        .children()
+        .iter()
+        .filter(|c| c.kind() == SyntaxKind::TableCell)
+        .collect();
+    let separator = !cells.is_empty()
+        && cells.iter().all(|cell| {
+            let text = cell.text();
+            let text = text.trim();
+            !text.is_empty() && text.chars().all(|c| c == '-')
+        });
+    (!separator).then_some(cells)
+}
+
+/// whether a list is numbered, `1.`, `a)`, `#` and the like
+pub(crate) fn is_ordered(list: &Node) -> bool {
+    list.children()
+        .iter()
+        .filter_map(|item| {
+            item.children()
+                .iter()
+                .find(|c| c.kind() == SyntaxKind::ListMarker)
+        })
+        .next()
+        .is_some_and(|marker| !matches!(marker.text().as_str(), "-" | "*"))
+}
+
+/// where a link points to in an export with files named `page.extension`
+pub(crate) fn href(link: &Link, extension: &str) -> String {
+    let target = link.target();
+    let page = link.page();
+    let fragment = link
+        .anchor()
+        .and_then(|anchor| anchor.rsplit('#').next().map(html_id))
+        .filter(|id| !id.is_empty())
+        .map(|id| format!("#{id}"))
+        .unwrap_or_default();
+    let page = match link.scheme().as_deref() {
+        Some(scheme) if is_interwiki(scheme) => {
+            page.get(scheme.len() + 1..).unwrap_or_default().to_owned()
+        }
+        Some("diary") => format!("diary/{}", page.get(6..).unwrap_or_default()),
+        Some("local" | "file") => {
+            let path = target.split_once(':').map_or("", |(_, path)| path);
+            return path.trim_start_matches("//").to_owned();
+        }
+        Some(_) => return target,
+        None => page,
+    };
+    if page.is_empty() {
+        return fragment;
+    }
+    if page.ends_with('/') {
+        return format!("{page}index.{extension}{fragment}");
+    }
+    format!("{page}.{extension}{fragment}")
+}
@@ -0,0 +1,391 @@
+//! html export, modelled after `:Vimwiki2HTML`
+//!
+//! headings and tags get the html ids of their anchors, checkbox items get
+//! the `done0` to `done4` and `rejected` classes of the vimwiki stylesheet.
+
+use std::collections::HashMap;
+
+use crate::anchor::{Anchors, html_id};
+use crate::ast::{self, CheckboxState, Heading, Link, ListItem};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::toc;
+
+use super::{cells, inner, is_ordered, text, transclusion, verbatim};
+
+/// resolves the href of a link, `None` falls back to [`href`]
+type LinkResolver<'a> = Box<dyn Fn(&Link) -> Option<String> + 'a>;
+
+/// the html exporter and its options
+#[derive(Default)]
+pub struct Html<'a> {
+    standalone: bool,
+    toc: Option<usize>,
+    css: Option<String>,
+    links: Option<LinkResolver<'a>>,
+}
+
+impl<'a> Html<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a whole document with `<head>` and `<body>` instead of a fragment
+    pub fn standalone(mut self, standalone: bool) -> Self {
+        self.standalone = standalone;
+        self
+    }
+
+    /// a table of contents down to heading level `depth` above the content
+    pub fn toc(mut self, depth: Option<usize>) -> Self {
+        self.toc = depth;
+        self
+    }
+
+    /// a stylesheet linked from standalone documents
+    pub fn css(mut self, href: impl Into<String>) -> Self {
+        self.css = Some(href.into());
+        self
+    }
+
+    /// overrides the href of links, the static site build points links to
+    /// other wikis and files through this
+    pub fn links(mut self, resolve: impl Fn(&Link) -> Option<String> + 'a) -> Self {
+        self.links = Some(Box::new(resolve));
+        self
+    }
+
+    pub fn render(&self, root: &Node) -> String {
+        let anchors = Anchors::new(root);
+        let mut writer = Writer {
+            options: self,
+            ids: anchors
+                .iter()
+                .map(|anchor| (anchor.span().start, anchor.html_id()))
+                .collect(),
+            anchors: &anchors,
+            out: String::new(),
+        };
+        if let Some(depth) = self.toc {
+            writer.toc(root, depth);
+        }
+        for block in root.children() {
+            writer.block(block);
+        }
+        if !self.standalone {
+            return writer.out;
+        }
+        let title = ast::title(root)
+            .or_else(|| {
+                root.children()
+                    .iter()
+                    .find_map(Heading::cast)
+                    .map(|h| h.title())
+            })
+            .unwrap_or_default();
+        let css = self
+            .css
+            .as_ref()
+            .map(|href| format!("<link rel=\"stylesheet\" href=\"{}\" />\n", escape(href)))
+            .unwrap_or_default();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\" />\n\
+             <title>{}</title>\n{css}</head>\n<body>\n{}</body>\n</html>\n",
+            escape(&title),
+            writer.out
+        )
+    }
+}
+
+/// the href of a link without a resolver
+///
+/// pages become `page.html`, `diary:` links point into `diary/`, interwiki
+/// prefixes are dropped and urls are kept as they are.
+pub fn href(link: &Link) -> String {
+    super::href(link, "html")
+}
+
+/// `text` with the html special characters escaped
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// class of a list item with a checkbox
+fn checkbox_class(state: CheckboxState) -> &'static str {
+    match state {
+        CheckboxState::Open => "done0",
+        CheckboxState::Started => "done1",
+        CheckboxState::Half => "done2",
+        CheckboxState::Mostly => "done3",
+        CheckboxState::Done => "done4",
+        CheckboxState::Rejected => "rejected",
+    }
+}
+
+struct Writer<'a, 'b> {
+    options: &'a Html<'b>,
+    anchors: &'a Anchors<'a>,
+    /// html ids of headings and tags by the start of their node
+    ids: HashMap<usize, String>,
+    out: String,
+}
+
+impl Writer<'_, '_> {
+    fn toc(&mut self, root: &Node, depth: usize) {
+        let entries = toc::entries(root, depth);
+        let Some(top) = entries.iter().map(|entry| entry.level).min() else {
+            return;
+        };
+        self.out.push_str(&format!(
+            "<div class=\"toc\">\n<h1 id=\"{0}\">{0}</h1>\n",
+            toc::HEADER
+        ));
+        let mut open = 0;
+        for entry in &entries {
+            let level = entry.level - top + 1;
+            if level > open {
+                for _ in open..level {
+                    self.out.push_str("<ul>\n<li>");
+                }
+            } else {
+                for _ in level..open {
+                    self.out.push_str("</li>\n</ul>\n");
+                }
+                self.out.push_str("</li>\n<li>");
+            }
+            open = level;
+            self.out.push_str(&format!(
+                "<a href=\"#{}\">{}</a>",
+                escape(&html_id(&entry.anchor)),
+                escape(&entry.title)
+            ));
+        }
+        for _ in 0..open {
+            self.out.push_str("</li>\n</ul>\n");
+        }
+        self.out.push_str("</div>\n");
+    }
+
+    fn block(&mut self, node: &Node) {
+        match node.kind() {
+            SyntaxKind::Heading => {
+                let Some(heading) = Heading::cast(node) else {
+                    return;
+                };
+                let level = heading.level().clamp(1, 6);
+                let id = self
+                    .ids
+                    .get(&node.span().start)
+                    .cloned()
+                    .unwrap_or_default();
+                let content = self.inlines(heading.content());
+                self.out.push_str(&format!(
+                    "<h{level} id=\"{}\">{}</h{level}>\n",
+                    escape(&id),
+                    content.trim()
+                ));
+            }
+            SyntaxKind::Paragraph => {
+                let content = self.inlines(node.children().iter());
+                self.out
+                    .push_str(&format!("<p>\n{}\n</p>\n", content.trim()));
+            }
+            SyntaxKind::List => self.list(node),
+            SyntaxKind::Table => self.table(node),
+            SyntaxKind::CodeBlock => {
+                let (lang, code) = verbatim(node);
+                let class = if lang.is_empty() {
+                    String::new()
+                } else {
+                    format!(" class=\"language-{}\"", escape(&lang))
+                };
+                self.out.push_str(&format!(
+                    "<pre><code{class}>{}\n</code></pre>\n",
+                    escape(&code)
+                ));
+            }
+            SyntaxKind::MathBlock => {
+                let (_, math) = verbatim(node);
+                self.out.push_str(&format!(
+                    "<div class=\"math\">\n\\[\n{}\n\\]\n</div>\n",
+                    escape(&math)
+                ));
+            }
+            SyntaxKind::HorizontalRule => self.out.push_str("<hr />\n"),
+            SyntaxKind::Comment | SyntaxKind::Placeholder => {}
+            kind if kind.is_trivia() => {}
+            _ => {
+                let text = node.text();
+                if !text.trim().is_empty() {
+                    self.out
+                        .push_str(&format!("<p>\n{}\n</p>\n", escape(text.trim())));
+                }
+            }
+        }
+    }
+
+    fn list(&mut self, list: &Node) {
+        let tag = if is_ordered(list) { "ol" } else { "ul" };
+        self.out.push_str(&format!("<{tag}>\n"));
+        for item in list.children().iter().filter_map(ListItem::cast) {
+            match item.checkbox() {
+                Some(checkbox) => self.out.push_str(&format!(
+                    "<li class=\"{}\">",
+                    checkbox_class(checkbox.state())
+                )),
+                None => self.out.push_str("<li>"),
+            }
+            let content = self.inlines(item.content());
+            self.out.push_str(content.trim());
+            let mut sublists = item.sublists().peekable();
+            if sublists.peek().is_some() {
+                self.out.push('\n');
+            }
+            for sublist in sublists {
+                self.list(sublist);
+            }
+            self.out.push_str("</li>\n");
+        }
+        self.out.push_str(&format!("</{tag}>\n"));
+    }
+
+    fn table(&mut self, table: &Node) {
+        let rows: Vec<_> = table
+            .children()
+            .iter()
+            .filter(|c| c.kind() == SyntaxKind::TableRow)
+            .collect();
+        // a separator as the second row turns the first into the header
+        let header = rows.len() > 1 && cells(rows[1]).is_none();
+        let mut rows = rows.into_iter().filter_map(cells);
+        self.out.push_str("<table>\n");
+        if header && let Some(row) = rows.next() {
+            self.out.push_str("<thead>\n");
+            self.row(&row, "th");
+            self.out.push_str("</thead>\n");
+        }
+        let body: Vec<_> = rows.collect();
+        if !body.is_empty() {
+            self.out.push_str("<tbody>\n");
+            for row in &body {
+                self.row(row, "td");
+            }
+            self.out.push_str("</tbody>\n");
+        }
+        self.out.push_str("</table>\n");
+    }
+
+    fn row(&mut self, cells: &[&Node], tag: &str) {
+        self.out.push_str("<tr>\n");
+        for cell in cells {
+            let content = self.inlines(cell.children().iter());
+            self.out
+                .push_str(&format!("<{tag}>{}</{tag}>\n", content.trim()));
+        }
+        self.out.push_str("</tr>\n");
+    }
+
+    fn inlines<'n>(&self, nodes: impl Iterator<Item = &'n Node>) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            self.inline(node, &mut out);
+        }
+        out
+    }
+
+    fn inline(&self, node: &Node, out: &mut String) {
+        let wrap = |tag: &str, out: &mut String| {
+            let content = self.inlines(inner(node).iter());
+            out.push_str(&format!("<{tag}>{content}</{tag}>"));
+        };
+        match node.kind() {
+            SyntaxKind::Bold => wrap("strong", out),
+            SyntaxKind::Italic => wrap("em", out),
+            SyntaxKind::Strikethrough => wrap("del", out),
+            SyntaxKind::Superscript => wrap("sup", out),
+            SyntaxKind::Subscript => wrap("sub", out),
+            SyntaxKind::Code => {
+                let code = text(inner(node));
+                out.push_str(&format!("<code>{}</code>", escape(&code)));
+            }
+            SyntaxKind::Math => {
+                let math = text(inner(node));
+                out.push_str(&format!("\\({}\\)", escape(&math)));
+            }
+            SyntaxKind::Link => {
+                let Some(link) = Link::cast(node) else {
+                    return;
+                };
+                let href = self.href(&link);
+                let content = match link.description_node() {
+                    Some(description) => self.inlines(description.children().iter()),
+                    None => escape(&link.target()),
+                };
+                out.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape(&href),
+                    content.trim()
+                ));
+            }
+            SyntaxKind::Transclusion => {
+                let (source, alt) = transclusion(node);
+                let alt = alt
+                    .map(|alt| format!(" alt=\"{}\"", escape(&alt)))
+                    .unwrap_or_default();
+                out.push_str(&format!("<img src=\"{}\"{alt} />", escape(&source)));
+            }
+            SyntaxKind::Tags => {
+                let tags: Vec<_> = node
+                    .children()
+                    .iter()
+                    .filter(|c| c.kind() == SyntaxKind::Tag)
+                    .map(|tag| {
+                        let id = self.ids.get(&tag.span().start).cloned().unwrap_or_default();
+                        format!(
+                            "<span class=\"tag\" id=\"{}\">{}</span>",
+                            escape(&id),
+                            escape(&tag.text())
+                        )
+                    })
+                    .collect();
+                out.push_str(&tags.join(" "));
+            }
+            _ if node.is_leaf() => out.push_str(&escape(&node.text())),
+            _ => {
+                for child in node.children() {
+                    self.inline(child, out);
+                }
+            }
+        }
+    }
+
+    fn href(&self, link: &Link) -> String {
+        if let Some(href) = self
+            .options
+            .links
+            .as_ref()
+            .and_then(|resolve| resolve(link))
+        {
+            return href;
+        }
+        // links into the page know the unique id of their anchor
+        if link.page().is_empty()
+            && link.scheme().is_none()
+            && let Some(anchor) = link.anchor().and_then(|a| self.anchors.resolve(&a))
+        {
+            return format!("#{}", anchor.html_id());
+        }
+        href(link)
+    }
+}
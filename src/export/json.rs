@@ -0,0 +1,37 @@
+//! json export of the syntax tree
+//!
+//! every node is an object with its `kind` and `span`, leaves have their
+//! `text` and inner nodes their `children`. error nodes also carry their
+//! `error`, `hint` and `code`.
+
+use serde_json::{Map, Value, json};
+
+use crate::parser::{Node, Repr};
+
+pub fn render(root: &Node) -> String {
+    serde_json::to_string_pretty(&value(root)).unwrap_or_default()
+}
+
+/// `node` as a json value
+pub fn value(node: &Node) -> Value {
+    let span = node.span();
+    let mut object = Map::new();
+    object.insert("kind".to_owned(), json!(node.kind().to_string()));
+    object.insert("span".to_owned(), json!([span.start, span.end]));
+    match node.repr() {
+        Repr::InnerNode(_) => {
+            let children = node.children().iter().map(value).collect();
+            object.insert("children".to_owned(), Value::Array(children));
+        }
+        Repr::SyntaxNode(_) => {
+            object.insert("text".to_owned(), json!(node.text()));
+        }
+        Repr::ErrorNode(error) => {
+            object.insert("text".to_owned(), json!(node.text()));
+            object.insert("error".to_owned(), json!(error.error()));
+            object.insert("hint".to_owned(), json!(error.hint()));
+            object.insert("code".to_owned(), json!(error.code().map(|code| code.id())));
+        }
+    }
+    Value::Object(object)
+}
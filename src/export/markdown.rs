@@ -0,0 +1,174 @@
+//! markdown export, in the GitHub flavour
+//!
+//! `%title` becomes YAML front matter, checkboxes become task list items and
+//! tables always get a header row since markdown has no tables without one.
+//! links to pages point to `page.md`.
+
+use crate::ast::{self, CheckboxState, Heading, Link, ListItem};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+
+use super::{cells, href, inner, is_ordered, text, transclusion, verbatim};
+
+pub fn render(root: &Node) -> String {
+    let mut blocks = Vec::new();
+    if let Some(title) = ast::title(root) {
+        blocks.push(format!("---\ntitle: {title}\n---\n"));
+    }
+    for block in root.children() {
+        if let Some(text) = self::block(block) {
+            blocks.push(text);
+        }
+    }
+    blocks.join("\n")
+}
+
+fn block(node: &Node) -> Option<String> {
+    let text = match node.kind() {
+        SyntaxKind::Heading => {
+            let heading = Heading::cast(node)?;
+            let marker = "#".repeat(heading.level().clamp(1, 6));
+            format!("{marker} {}\n", inlines(heading.content()).trim())
+        }
+        SyntaxKind::Paragraph => format!("{}\n", inlines(node.children().iter()).trim()),
+        SyntaxKind::List => {
+            let mut out = String::new();
+            list(node, 0, &mut out);
+            out
+        }
+        SyntaxKind::Table => table(node),
+        SyntaxKind::CodeBlock => {
+            let (lang, code) = verbatim(node);
+            format!("```{lang}\n{code}\n```\n")
+        }
+        SyntaxKind::MathBlock => {
+            let (_, math) = verbatim(node);
+            format!("$$\n{math}\n$$\n")
+        }
+        SyntaxKind::Comment => {
+            let text = node.text();
+            format!("<!-- {} -->\n", text.trim().trim_start_matches('%').trim())
+        }
+        SyntaxKind::HorizontalRule => "---\n".to_owned(),
+        SyntaxKind::Placeholder => return None,
+        kind if kind.is_trivia() => return None,
+        _ => {
+            let text = node.text();
+            if text.trim().is_empty() {
+                return None;
+            }
+            format!("{}\n", text.trim())
+        }
+    };
+    Some(text)
+}
+
+fn list(list: &Node, depth: usize, out: &mut String) {
+    let ordered = is_ordered(list);
+    for (i, item) in list
+        .children()
+        .iter()
+        .filter_map(ListItem::cast)
+        .enumerate()
+    {
+        let indent = "  ".repeat(depth);
+        let marker = if ordered {
+            format!("{}.", i + 1)
+        } else {
+            "-".to_owned()
+        };
+        let checkbox = match item.checkbox().map(|c| c.state()) {
+            Some(CheckboxState::Done) => "[x] ",
+            Some(_) => "[ ] ",
+            None => "",
+        };
+        let content = inlines(item.content());
+        let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+        out.push_str(&format!("{indent}{marker} {checkbox}{content}\n"));
+        for sublist in item.sublists() {
+            // nested items line up with the text of their parent
+            self::list(sublist, depth + marker.len().div_ceil(2), out);
+        }
+    }
+}
+
+fn table(table: &Node) -> String {
+    let rows: Vec<_> = table
+        .children()
+        .iter()
+        .filter(|c| c.kind() == SyntaxKind::TableRow)
+        .filter_map(cells)
+        .map(|cells| {
+            cells
+                .iter()
+                .map(|cell| inlines(cell.children().iter()).trim().replace('|', "\\|"))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let line = |cells: &[String]| {
+        let mut line = String::from("|");
+        for i in 0..columns {
+            line.push_str(&format!(" {} |", cells.get(i).map_or("", String::as_str)));
+        }
+        line.push('\n');
+        line
+    };
+    let mut out = String::new();
+    let mut rows = rows.iter();
+    out.push_str(&line(rows.next().map_or(&[], Vec::as_slice)));
+    out.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+    for row in rows {
+        out.push_str(&line(row));
+    }
+    out
+}
+
+fn inlines<'a>(nodes: impl Iterator<Item = &'a Node>) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        inline(node, &mut out);
+    }
+    out
+}
+
+fn inline(node: &Node, out: &mut String) {
+    let wrap = |open: &str, close: &str, out: &mut String| {
+        out.push_str(&format!("{open}{}{close}", inlines(inner(node).iter())));
+    };
+    match node.kind() {
+        SyntaxKind::Bold => wrap("**", "**", out),
+        SyntaxKind::Italic => wrap("_", "_", out),
+        SyntaxKind::Strikethrough => wrap("~~", "~~", out),
+        SyntaxKind::Superscript => wrap("<sup>", "</sup>", out),
+        SyntaxKind::Subscript => wrap("<sub>", "</sub>", out),
+        SyntaxKind::Code => {
+            let code = text(inner(node));
+            out.push_str(&format!("`{code}`"));
+        }
+        SyntaxKind::Math => {
+            let math = text(inner(node));
+            out.push_str(&format!("${math}$"));
+        }
+        SyntaxKind::Link => {
+            let Some(link) = Link::cast(node) else {
+                return;
+            };
+            let content = match link.description_node() {
+                Some(description) => inlines(description.children().iter()),
+                None => link.target(),
+            };
+            out.push_str(&format!("[{}]({})", content.trim(), href(&link, "md")));
+        }
+        SyntaxKind::Transclusion => {
+            let (source, alt) = transclusion(node);
+            out.push_str(&format!("![{}]({source})", alt.unwrap_or_default()));
+        }
+        _ if node.is_leaf() => out.push_str(&node.text()),
+        _ => {
+            for child in node.children() {
+                inline(child, out);
+            }
+        }
+    }
+}
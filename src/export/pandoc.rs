@@ -0,0 +1,376 @@
+//! pandoc json export, for `pandoc -f json`
+//!
+//! the document is written in the json form of pandoc's AST, so pandoc can
+//! turn it into anything it writes, like pdf or docx. `%title` becomes the
+//! title of the metadata, checkboxes become `☐` and `☒` like in pandoc's
+//! own task lists and ordered lists keep the numbering style of their
+//! first marker.
+
+use std::collections::HashMap;
+
+use serde_json::{Value, json};
+
+use crate::anchor::Anchors;
+use crate::ast::{self, CheckboxState, Heading, Link, ListItem};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+
+use super::{cells, href, inner, is_ordered, text, transclusion, verbatim};
+
+/// version of the pandoc types the output is written for
+pub const API_VERSION: [u32; 3] = [1, 23, 1];
+
+pub fn render(root: &Node) -> String {
+    serde_json::to_string(&value(root)).unwrap_or_default()
+}
+
+/// `root` as a pandoc document
+pub fn value(root: &Node) -> Value {
+    let anchors = Anchors::new(root);
+    let writer = Writer {
+        anchors: &anchors,
+        ids: anchors
+            .iter()
+            .map(|anchor| (anchor.span().start, anchor.html_id()))
+            .collect(),
+    };
+    let mut meta = serde_json::Map::new();
+    if let Some(title) = ast::title(root) {
+        let mut inlines = Inlines::default();
+        inlines.text(&title);
+        meta.insert(
+            "title".to_owned(),
+            json!({ "t": "MetaInlines", "c": inlines.finish() }),
+        );
+    }
+    let blocks: Vec<_> = root
+        .children()
+        .iter()
+        .filter_map(|block| writer.block(block))
+        .collect();
+    json!({
+        "pandoc-api-version": API_VERSION,
+        "meta": meta,
+        "blocks": blocks,
+    })
+}
+
+/// an attribute without id, classes or key-value pairs
+fn attr() -> Value {
+    json!(["", [], []])
+}
+
+/// the numbering style, delimiter and start of an ordered list marker
+fn list_attributes(marker: &str) -> (&'static str, &'static str, usize) {
+    let (number, delim) = match marker.strip_suffix(['.', ')']) {
+        Some(number) if marker.ends_with('.') => (number, "Period"),
+        Some(number) => (number, "OneParen"),
+        None => return ("DefaultStyle", "DefaultDelim", 1),
+    };
+    if let Ok(start) = number.parse() {
+        return ("Decimal", delim, start);
+    }
+    let lower = number.to_ascii_lowercase();
+    let upper = number.starts_with(|c: char| c.is_ascii_uppercase());
+    // a single letter other than `i` counts, like vimwiki does
+    if number.len() == 1 && lower != "i" {
+        let start = lower
+            .bytes()
+            .next()
+            .map_or(1, |c| usize::from(c - b'a') + 1);
+        let style = if upper { "UpperAlpha" } else { "LowerAlpha" };
+        return (style, delim, start);
+    }
+    let style = if upper { "UpperRoman" } else { "LowerRoman" };
+    (style, delim, roman(&lower).unwrap_or(1))
+}
+
+/// the value of a lowercase roman numeral
+fn roman(numeral: &str) -> Option<usize> {
+    let values = numeral
+        .chars()
+        .map(|c| match c {
+            'i' => Some(1),
+            'v' => Some(5),
+            'x' => Some(10),
+            'l' => Some(50),
+            'c' => Some(100),
+            'd' => Some(500),
+            'm' => Some(1000),
+            _ => None,
+        })
+        .collect::<Option<Vec<isize>>>()?;
+    let mut total = 0;
+    for (i, value) in values.iter().enumerate() {
+        if values.get(i + 1).is_some_and(|next| next > value) {
+            total -= value;
+        } else {
+            total += value;
+        }
+    }
+    usize::try_from(total).ok()
+}
+
+/// inlines with the text split into `Str`, `Space` and `SoftBreak`
+#[derive(Default)]
+struct Inlines(Vec<Value>);
+
+impl Inlines {
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            let last = self.0.last_mut();
+            if c.is_whitespace() {
+                let kind = if c == '\n' { "SoftBreak" } else { "Space" };
+                match last.map(|last| last["t"].clone()) {
+                    Some(t) if t == "Space" && kind == "SoftBreak" => {
+                        self.0.pop();
+                        self.0.push(json!({ "t": kind }));
+                    }
+                    Some(t) if t == "Space" || t == "SoftBreak" => {}
+                    _ => self.0.push(json!({ "t": kind })),
+                }
+            } else if let Some(Value::String(word)) = last
+                .filter(|last| last["t"] == "Str")
+                .and_then(|last| last.get_mut("c"))
+            {
+                word.push(c);
+            } else {
+                self.0.push(json!({ "t": "Str", "c": c.to_string() }));
+            }
+        }
+    }
+
+    fn push(&mut self, inline: Value) {
+        self.0.push(inline);
+    }
+
+    /// the inlines without leading and trailing spaces
+    fn finish(mut self) -> Vec<Value> {
+        let is_space = |inline: &Value| inline["t"] == "Space" || inline["t"] == "SoftBreak";
+        while self.0.last().is_some_and(is_space) {
+            self.0.pop();
+        }
+        let start = self.0.iter().take_while(|inline| is_space(inline)).count();
+        self.0.split_off(start)
+    }
+}
+
+struct Writer<'a> {
+    anchors: &'a Anchors<'a>,
+    /// html ids of headings and tags by the start of their node
+    ids: HashMap<usize, String>,
+}
+
+impl Writer<'_> {
+    fn id(&self, node: &Node) -> String {
+        self.ids
+            .get(&node.span().start)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn block(&self, node: &Node) -> Option<Value> {
+        let block = match node.kind() {
+            SyntaxKind::Heading => {
+                let heading = Heading::cast(node)?;
+                json!({
+                    "t": "Header",
+                    "c": [
+                        heading.level().clamp(1, 6),
+                        [self.id(node), [], []],
+                        self.inlines(heading.content()),
+                    ],
+                })
+            }
+            SyntaxKind::Paragraph => {
+                json!({ "t": "Para", "c": self.inlines(node.children().iter()) })
+            }
+            SyntaxKind::List => self.list(node),
+            SyntaxKind::Table => self.table(node),
+            SyntaxKind::CodeBlock => {
+                let (lang, code) = verbatim(node);
+                let classes: Vec<_> = lang.split_whitespace().take(1).collect();
+                json!({ "t": "CodeBlock", "c": [["", classes, []], code] })
+            }
+            SyntaxKind::MathBlock => {
+                let (_, math) = verbatim(node);
+                let math = json!({ "t": "Math", "c": [{ "t": "DisplayMath" }, math] });
+                json!({ "t": "Para", "c": [math] })
+            }
+            SyntaxKind::HorizontalRule => json!({ "t": "HorizontalRule" }),
+            SyntaxKind::Comment | SyntaxKind::Placeholder => return None,
+            kind if kind.is_trivia() => return None,
+            _ => {
+                let mut inlines = Inlines::default();
+                inlines.text(&node.text());
+                let inlines = inlines.finish();
+                if inlines.is_empty() {
+                    return None;
+                }
+                json!({ "t": "Para", "c": inlines })
+            }
+        };
+        Some(block)
+    }
+
+    fn list(&self, list: &Node) -> Value {
+        let items: Vec<_> = list.children().iter().filter_map(ListItem::cast).collect();
+        let content: Vec<_> = items
+            .iter()
+            .map(|item| {
+                let mut inlines = Inlines::default();
+                match item.checkbox().map(|c| c.state()) {
+                    Some(CheckboxState::Done) => inlines.text("☒ "),
+                    Some(_) => inlines.text("☐ "),
+                    None => {}
+                }
+                for node in item.content() {
+                    self.inline(node, &mut inlines);
+                }
+                let mut blocks = vec![json!({ "t": "Plain", "c": inlines.finish() })];
+                blocks.extend(item.sublists().map(|sublist| self.list(sublist)));
+                blocks
+            })
+            .collect();
+        if !is_ordered(list) {
+            return json!({ "t": "BulletList", "c": content });
+        }
+        let marker = items
+            .first()
+            .and_then(ListItem::marker)
+            .map(Node::text)
+            .unwrap_or_default();
+        let (style, delim, start) = list_attributes(&marker);
+        json!({
+            "t": "OrderedList",
+            "c": [[start, { "t": style }, { "t": delim }], content],
+        })
+    }
+
+    fn table(&self, table: &Node) -> Value {
+        let rows: Vec<_> = table
+            .children()
+            .iter()
+            .filter(|c| c.kind() == SyntaxKind::TableRow)
+            .collect();
+        // a separator as the second row turns the first into the header
+        let header = rows.len() > 1 && cells(rows[1]).is_none();
+        let rows: Vec<_> = rows.into_iter().filter_map(cells).collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let row = |cells: &[&Node]| {
+            let cells: Vec<_> = (0..columns)
+                .map(|i| {
+                    let content = cells
+                        .get(i)
+                        .map(|cell| self.inlines(cell.children().iter()))
+                        .unwrap_or_default();
+                    json!([attr(), { "t": "AlignDefault" }, 1, 1, [{ "t": "Plain", "c": content }]])
+                })
+                .collect();
+            json!([attr(), cells])
+        };
+        let (head, body) = match rows.split_first() {
+            Some((first, rest)) if header => (vec![row(first)], rest),
+            _ => (Vec::new(), rows.as_slice()),
+        };
+        let body: Vec<_> = body.iter().map(|cells| row(cells)).collect();
+        let colspecs = vec![json!([{ "t": "AlignDefault" }, { "t": "ColWidthDefault" }]); columns];
+        json!({
+            "t": "Table",
+            "c": [
+                attr(),
+                [null, []],
+                colspecs,
+                [attr(), head],
+                [[attr(), 0, [], body]],
+                [attr(), []],
+            ],
+        })
+    }
+
+    fn inlines<'n>(&self, nodes: impl Iterator<Item = &'n Node>) -> Vec<Value> {
+        let mut inlines = Inlines::default();
+        for node in nodes {
+            self.inline(node, &mut inlines);
+        }
+        inlines.finish()
+    }
+
+    fn inline(&self, node: &Node, out: &mut Inlines) {
+        let wrap = |t: &str, out: &mut Inlines| {
+            out.push(json!({ "t": t, "c": self.inlines(inner(node).iter()) }));
+        };
+        match node.kind() {
+            SyntaxKind::Bold => wrap("Strong", out),
+            SyntaxKind::Italic => wrap("Emph", out),
+            SyntaxKind::Strikethrough => wrap("Strikeout", out),
+            SyntaxKind::Superscript => wrap("Superscript", out),
+            SyntaxKind::Subscript => wrap("Subscript", out),
+            SyntaxKind::Code => {
+                out.push(json!({ "t": "Code", "c": [attr(), text(inner(node))] }));
+            }
+            SyntaxKind::Math => {
+                let math = text(inner(node));
+                out.push(json!({ "t": "Math", "c": [{ "t": "InlineMath" }, math] }));
+            }
+            SyntaxKind::Link => {
+                let Some(link) = Link::cast(node) else {
+                    return;
+                };
+                let content = match link.description_node() {
+                    Some(description) => self.inlines(description.children().iter()),
+                    None => {
+                        let mut inlines = Inlines::default();
+                        inlines.text(&link.target());
+                        inlines.finish()
+                    }
+                };
+                out.push(json!({
+                    "t": "Link",
+                    "c": [attr(), content, [self.href(&link), ""]],
+                }));
+            }
+            SyntaxKind::Transclusion => {
+                let (source, alt) = transclusion(node);
+                let mut inlines = Inlines::default();
+                inlines.text(&alt.unwrap_or_default());
+                out.push(json!({
+                    "t": "Image",
+                    "c": [attr(), inlines.finish(), [source, ""]],
+                }));
+            }
+            SyntaxKind::Tags => {
+                let tags = node
+                    .children()
+                    .iter()
+                    .filter(|c| c.kind() == SyntaxKind::Tag);
+                for (i, tag) in tags.enumerate() {
+                    if i > 0 {
+                        out.text(" ");
+                    }
+                    out.push(json!({
+                        "t": "Span",
+                        "c": [[self.id(tag), ["tag"], []], [{ "t": "Str", "c": tag.text() }]],
+                    }));
+                }
+            }
+            _ if node.is_leaf() => out.text(&node.text()),
+            _ => {
+                for child in node.children() {
+                    self.inline(child, out);
+                }
+            }
+        }
+    }
+
+    fn href(&self, link: &Link) -> String {
+        // links into the page know the unique id of their anchor
+        if link.page().is_empty()
+            && link.scheme().is_none()
+            && let Some(anchor) = link.anchor().and_then(|a| self.anchors.resolve(&a))
+        {
+            return format!("#{}", anchor.html_id());
+        }
+        href(link, "html")
+    }
+}
@@ -0,0 +1,319 @@
+//! plain text export, for reading pages in a terminal
+//!
+//! the markup is stripped and paragraphs and list items are wrapped. with
+//! ansi escapes, emphasis is rendered by the terminal, headings are colored,
+//! checkboxes become `☐` and `☑` and tables get box-drawing borders.
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::ast::{CheckboxState, Heading, Link, ListItem};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+
+use super::{cells, inner, text, transclusion, verbatim};
+
+/// the width paragraphs are wrapped at by default
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// colors of headings by level, magenta, blue, cyan, green, yellow and red
+const HEADING_COLORS: [u8; 6] = [35, 34, 36, 32, 33, 31];
+
+/// the plain text exporter and its options
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plain {
+    width: Option<usize>,
+    ansi: bool,
+}
+
+impl Default for Plain {
+    fn default() -> Self {
+        Self {
+            width: Some(DEFAULT_WIDTH),
+            ansi: false,
+        }
+    }
+}
+
+impl Plain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// wraps paragraphs and list items at `width` columns, `None` keeps
+    /// every paragraph on a single line
+    pub fn width(mut self, width: Option<usize>) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// styles the text with ansi escape codes
+    pub fn ansi(mut self, ansi: bool) -> Self {
+        self.ansi = ansi;
+        self
+    }
+
+    pub fn render(&self, root: &Node) -> String {
+        let blocks: Vec<_> = root
+            .children()
+            .iter()
+            .filter_map(|block| self.block(block))
+            .collect();
+        blocks.join("\n")
+    }
+
+    /// `text` between the escape codes `on` and `off`, inside of the styles
+    /// `outer`. those are turned on again after `off`, since it can end them
+    /// too: `22` ends both bold and dim text
+    fn style(&self, on: &str, text: &str, off: &str, outer: &[&str]) -> String {
+        if self.ansi {
+            let outer: String = outer.iter().map(|on| format!("\x1b[{on}m")).collect();
+            format!("\x1b[{on}m{text}\x1b[{off}m{outer}")
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn block(&self, node: &Node) -> Option<String> {
+        let text = match node.kind() {
+            SyntaxKind::Heading => {
+                let heading = Heading::cast(node)?;
+                let level = heading.level().clamp(1, 6);
+                let on = format!("1;{}", HEADING_COLORS[level - 1]);
+                let title = collapse(&self.inlines(heading.content(), &[&on]));
+                if self.ansi {
+                    format!("\x1b[{on}m{title}\x1b[0m\n")
+                } else {
+                    let underline = match level {
+                        1 => "=",
+                        2 => "-",
+                        _ => return Some(format!("{title}\n")),
+                    };
+                    format!("{title}\n{}\n", underline.repeat(width(&title)))
+                }
+            }
+            SyntaxKind::Paragraph => {
+                let content = collapse(&self.inlines(node.children().iter(), &[]));
+                self.wrap(&content, "", "")
+            }
+            SyntaxKind::List => {
+                let mut out = String::new();
+                self.list(node, "", &mut out);
+                out
+            }
+            SyntaxKind::Table => self.table(node),
+            SyntaxKind::CodeBlock | SyntaxKind::MathBlock => {
+                let (_, code) = verbatim(node);
+                code.lines()
+                    .map(|line| format!("    {}\n", self.style("2", line, "22", &[])))
+                    .collect()
+            }
+            SyntaxKind::HorizontalRule => {
+                let rule = if self.ansi { "─" } else { "-" };
+                format!("{}\n", rule.repeat(self.width.unwrap_or(DEFAULT_WIDTH)))
+            }
+            SyntaxKind::Comment | SyntaxKind::Placeholder => return None,
+            kind if kind.is_trivia() => return None,
+            _ => {
+                let text = collapse(&node.text());
+                if text.is_empty() {
+                    return None;
+                }
+                self.wrap(&text, "", "")
+            }
+        };
+        Some(text)
+    }
+
+    fn list(&self, list: &Node, indent: &str, out: &mut String) {
+        for item in list.children().iter().filter_map(ListItem::cast) {
+            let marker = match item.marker().map(Node::text).as_deref() {
+                Some("-" | "*") | None if self.ansi => "•".to_owned(),
+                Some(marker) => marker.to_owned(),
+                None => "-".to_owned(),
+            };
+            let checkbox = match item.checkbox().map(|c| c.state()) {
+                Some(CheckboxState::Done) if self.ansi => "☑ ",
+                Some(CheckboxState::Rejected) if self.ansi => "☒ ",
+                Some(_) if self.ansi => "☐ ",
+                Some(CheckboxState::Done) => "[x] ",
+                Some(_) => "[ ] ",
+                None => "",
+            };
+            let first = format!("{indent}{marker} ");
+            let rest = " ".repeat(width(&first));
+            let content = collapse(&self.inlines(item.content(), &[]));
+            out.push_str(&self.wrap(&format!("{checkbox}{content}"), &first, &rest));
+            for sublist in item.sublists() {
+                self.list(sublist, &rest, out);
+            }
+        }
+    }
+
+    fn table(&self, table: &Node) -> String {
+        let rows: Vec<_> = table
+            .children()
+            .iter()
+            .filter(|c| c.kind() == SyntaxKind::TableRow)
+            .collect();
+        // a separator as the second row turns the first into the header
+        let header = rows.len() > 1 && cells(rows[1]).is_none();
+        let rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .filter_map(cells)
+            .map(|cells| {
+                cells
+                    .iter()
+                    .map(|cell| collapse(&self.inlines(cell.children().iter(), &[])))
+                    .collect()
+            })
+            .collect();
+        let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+        let widths: Vec<_> = (0..columns)
+            .map(|i| {
+                rows.iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| width(cell))
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let (bar, join) = if self.ansi {
+            ("│", " │ ")
+        } else {
+            ("", "  ")
+        };
+        let line = |row: &[String]| {
+            let cells: Vec<_> = widths
+                .iter()
+                .enumerate()
+                .map(|(i, &w)| {
+                    let cell = row.get(i).map_or("", String::as_str);
+                    format!("{cell}{}", " ".repeat(w - width(cell)))
+                })
+                .collect();
+            let line = cells.join(join);
+            if self.ansi {
+                format!("{bar} {line} {bar}\n")
+            } else {
+                format!("{}\n", line.trim_end())
+            }
+        };
+        let border = |left: &str, middle: &str, right: &str| {
+            let parts: Vec<_> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+            format!("{left}{}{right}\n", parts.join(middle))
+        };
+        let mut out = String::new();
+        if self.ansi {
+            out.push_str(&border("┌", "┬", "┐"));
+        }
+        for (i, row) in rows.iter().enumerate() {
+            out.push_str(&line(row));
+            if header && i == 0 && rows.len() > 1 {
+                if self.ansi {
+                    out.push_str(&border("├", "┼", "┤"));
+                } else {
+                    let parts: Vec<_> = widths.iter().map(|w| "-".repeat(*w)).collect();
+                    out.push_str(&format!("{}\n", parts.join(join)));
+                }
+            }
+        }
+        if self.ansi {
+            out.push_str(&border("└", "┴", "┘"));
+        }
+        out
+    }
+
+    /// `text` wrapped at the width, the first line starts with `first` and
+    /// the following ones with `rest`
+    fn wrap(&self, text: &str, first: &str, rest: &str) -> String {
+        let mut out = String::new();
+        let mut line = first.to_owned();
+        let mut empty = true;
+        for word in text.split(' ').filter(|word| !word.is_empty()) {
+            let fits = self
+                .width
+                .is_none_or(|max| width(&line) + 1 + width(word) <= max);
+            if empty {
+                line.push_str(word);
+                empty = false;
+            } else if fits {
+                line.push(' ');
+                line.push_str(word);
+            } else {
+                out.push_str(&line);
+                out.push('\n');
+                line = format!("{rest}{word}");
+            }
+        }
+        out.push_str(&line);
+        out.push('\n');
+        out
+    }
+
+    /// the inline `nodes` inside of the styles `outer`
+    fn inlines<'n>(&self, nodes: impl Iterator<Item = &'n Node>, outer: &[&str]) -> String {
+        let mut out = String::new();
+        for node in nodes {
+            self.inline(node, outer, &mut out);
+        }
+        out
+    }
+
+    fn inline(&self, node: &Node, outer: &[&str], out: &mut String) {
+        let wrap = |on: &str, off: &str, out: &mut String| {
+            let styles: Vec<_> = outer.iter().copied().chain([on]).collect();
+            let content = self.inlines(inner(node).iter(), &styles);
+            out.push_str(&self.style(on, &content, off, outer));
+        };
+        match node.kind() {
+            SyntaxKind::Bold => wrap("1", "22", out),
+            SyntaxKind::Italic => wrap("3", "23", out),
+            SyntaxKind::Strikethrough => wrap("9", "29", out),
+            SyntaxKind::Code | SyntaxKind::Math => {
+                out.push_str(&self.style("2", &text(inner(node)), "22", outer));
+            }
+            SyntaxKind::Link => {
+                let Some(link) = Link::cast(node) else {
+                    return;
+                };
+                let styles: Vec<_> = outer.iter().copied().chain(["4"]).collect();
+                let content = match link.description_node() {
+                    Some(description) => self.inlines(description.children().iter(), &styles),
+                    None => link.target(),
+                };
+                out.push_str(&self.style("4", content.trim(), "24", outer));
+            }
+            SyntaxKind::Transclusion => {
+                let (source, alt) = transclusion(node);
+                out.push_str(&format!("[{}]", alt.unwrap_or(source)));
+            }
+            SyntaxKind::Tags => out.push_str(&self.style("2", &node.text(), "22", outer)),
+            _ if node.is_leaf() => out.push_str(&node.text()),
+            _ => {
+                for child in node.children() {
+                    self.inline(child, outer, out);
+                }
+            }
+        }
+    }
+}
+
+/// `text` with every run of whitespace turned into a single space
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// the number of columns `text` takes up in a terminal, without escape
+/// codes. wide characters like `漢` and most emoji take up two
+fn width(text: &str) -> usize {
+    let mut visible = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            visible.push(c);
+        }
+    }
+    visible.width()
+}
@@ -0,0 +1,289 @@
+//! machine-applicable fixes for syntax errors and lints
+//!
+//! a [`Fix`] is a set of [`TextEdit`]s on the source of a page which are
+//! applied together. fixes are applied in document order, a fix touching
+//! text another fix already changed is skipped, so running them again
+//! picks it up on the new text.
+
+use std::collections::HashSet;
+
+use thiserror::Error;
+
+use crate::lint::{self, LintConfig};
+use crate::parser::{self, ErrorCode, ErrorNode, Node};
+use crate::span::Span;
+
+/// a replacement of the text of a span
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub new_text: String,
+}
+
+impl TextEdit {
+    pub fn new(span: Span, new_text: impl Into<String>) -> Self {
+        Self {
+            span,
+            new_text: new_text.into(),
+        }
+    }
+
+    pub fn insert(offset: usize, new_text: impl Into<String>) -> Self {
+        Self::new(Span::new(offset, offset), new_text)
+    }
+
+    pub fn delete(span: Span) -> Self {
+        Self::new(span, "")
+    }
+
+    /// whether both edits change the same text or insert at the same offset,
+    /// which leaves their order open
+    fn conflicts(&self, other: &TextEdit) -> bool {
+        let (a, b) = (self.span, other.span);
+        (a.start < b.end && b.start < a.end) || a.start == b.start
+    }
+}
+
+/// edits which fix one problem
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fix {
+    /// what the fix does, like `Remove trailing whitespace`
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+impl Fix {
+    pub fn new(title: impl Into<String>, edits: Vec<TextEdit>) -> Self {
+        Self {
+            title: title.into(),
+            edits,
+        }
+    }
+
+    /// a fix with a single edit
+    pub fn edit(title: impl Into<String>, edit: TextEdit) -> Self {
+        Self::new(title, vec![edit])
+    }
+
+    fn start(&self) -> usize {
+        self.edits
+            .iter()
+            .map(|edit| edit.span.start)
+            .min()
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FixError {
+    #[error("the fixes would add {0} syntax errors")]
+    NewErrors(usize),
+}
+
+/// the text with fixes applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixed {
+    pub text: String,
+    /// the number of fixes applied
+    pub applied: usize,
+    /// the number of fixes skipped because they conflict with an applied one
+    pub skipped: usize,
+}
+
+/// byte offset of the end of the line containing `offset`, before trailing whitespace
+fn line_content_end(text: &str, offset: usize) -> usize {
+    let end = text[offset..].find('\n').map_or(text.len(), |i| offset + i);
+    offset + text[offset..end].trim_end().len()
+}
+
+/// the opening marker an error node of an inline element starts with, `*` or `~~`
+fn opening_marker(text: &str) -> &str {
+    let mut chars = text.char_indices();
+    match (chars.next(), chars.next()) {
+        (Some((_, first)), Some((i, second))) if first == second && matches!(first, '~' | ',') => {
+            &text[..i + second.len_utf8()]
+        }
+        (Some((_, first)), _) => &text[..first.len_utf8()],
+        _ => "",
+    }
+}
+
+/// the fix for an error the parser reported in `source`, `None` if there is none
+pub fn error_fix(source: &str, error: &ErrorNode) -> Option<Fix> {
+    let span = error.span();
+    if span.end > source.len() {
+        return None;
+    }
+    match error.code()? {
+        ErrorCode::TrailingWhitespace => {
+            let marker = opening_marker(&error.text);
+            let content = error.text.strip_prefix(marker)?.strip_suffix(marker)?;
+            let whitespace = content.len() - content.trim_end().len();
+            let close = span.end - marker.len();
+            let edit = TextEdit::delete(Span::new(close - whitespace, close));
+            Some(Fix::edit("Remove trailing whitespace", edit))
+        }
+        ErrorCode::UnclosedDelimiter => {
+            let marker = opening_marker(&error.text);
+            let end = line_content_end(source, span.end);
+            Some(Fix::edit(
+                format!("Close with `{marker}`"),
+                TextEdit::insert(end, marker),
+            ))
+        }
+        ErrorCode::UnclosedLink => {
+            let end = line_content_end(source, span.end);
+            Some(Fix::edit(
+                "Close link with `]]`",
+                TextEdit::insert(end, "]]"),
+            ))
+        }
+        code @ (ErrorCode::UnclosedCodeBlock | ErrorCode::UnclosedMathBlock) => {
+            let marker = if code == ErrorCode::UnclosedMathBlock {
+                "}}$"
+            } else {
+                "}}}"
+            };
+            let close = if source.ends_with('\n') || source.is_empty() {
+                format!("{marker}\n")
+            } else {
+                format!("\n{marker}\n")
+            };
+            Some(Fix::edit(
+                format!("Close code block with `{marker}`"),
+                TextEdit::new(span, close),
+            ))
+        }
+    }
+}
+
+/// the fixes for the syntax errors and lints of the page `root` parsed from
+/// `source`, in document order
+pub fn fixes(source: &str, root: &Node, config: &LintConfig) -> Vec<Fix> {
+    let mut fixes: Vec<_> = root
+        .error_nodes()
+        .into_iter()
+        .filter_map(|error| error_fix(source, error))
+        .chain(
+            lint::lint(root, config)
+                .into_iter()
+                .filter_map(|lint| lint.fix),
+        )
+        .collect();
+    fixes.sort_by_key(Fix::start);
+    fixes
+}
+
+/// `source` with the fixes applied which do not conflict with an earlier one
+///
+/// fixes with edits outside of `source` or in the middle of a character are
+/// skipped as well.
+pub fn apply(source: &str, fixes: &[Fix]) -> Fixed {
+    let (edits, applied, skipped) = select(source, fixes);
+    Fixed {
+        text: splice(source, &edits),
+        applied,
+        skipped,
+    }
+}
+
+/// `source` with `edits` in document order applied
+fn splice(source: &str, edits: &[&TextEdit]) -> String {
+    let mut text = String::with_capacity(source.len());
+    let mut offset = 0;
+    for edit in edits {
+        text.push_str(&source[offset..edit.span.start]);
+        text.push_str(&edit.new_text);
+        offset = edit.span.end;
+    }
+    text.push_str(&source[offset..]);
+    text
+}
+
+/// the edits of the fixes `apply` applies in document order, and the number
+/// of fixes applied and skipped
+fn select<'a>(source: &str, fixes: &'a [Fix]) -> (Vec<&'a TextEdit>, usize, usize) {
+    let mut fixes: Vec<_> = fixes.iter().collect();
+    fixes.sort_by_key(|fix| fix.start());
+    let valid = |edit: &TextEdit| {
+        edit.span.start <= edit.span.end
+            && source.is_char_boundary(edit.span.start)
+            && source.is_char_boundary(edit.span.end)
+    };
+    let mut edits: Vec<&TextEdit> = Vec::new();
+    let (mut applied, mut skipped) = (0, 0);
+    for fix in fixes {
+        let conflicts = fix.edits.iter().enumerate().any(|(i, edit)| {
+            !valid(edit)
+                || edits.iter().any(|other| edit.conflicts(other))
+                || fix.edits[..i].iter().any(|other| edit.conflicts(other))
+        });
+        if conflicts {
+            skipped += 1;
+        } else {
+            edits.extend(&fix.edits);
+            applied += 1;
+        }
+    }
+    edits.sort_by_key(|edit| edit.span.start);
+    (edits, applied, skipped)
+}
+
+/// where `span` is after `edits` are applied, `None` if an edit changes its
+/// text
+fn remap(span: Span, edits: &[&TextEdit]) -> Option<Span> {
+    let mut delta = 0;
+    for edit in edits {
+        let inside = if edit.span.start == edit.span.end {
+            span.start < edit.span.start && edit.span.start < span.end
+        } else {
+            edit.span.start < span.end && span.start < edit.span.end
+        };
+        if inside {
+            return None;
+        }
+        if edit.span.end <= span.start {
+            delta += edit.new_text.len() as isize - (edit.span.end - edit.span.start) as isize;
+        }
+    }
+    Some(Span::new(
+        span.start.saturating_add_signed(delta),
+        span.end.saturating_add_signed(delta),
+    ))
+}
+
+/// `source` with its syntax errors and lints fixed
+///
+/// the fixed text is parsed again and rejected if it has a syntax error
+/// which `source` does not have at the same place, the fixes would break the
+/// page there.
+pub fn fix(source: &str, config: &LintConfig) -> Result<Fixed, FixError> {
+    let root = parser::parse(source);
+    let fixes = fixes(source, &root, config);
+    let (edits, applied, skipped) = select(source, &fixes);
+    let before: HashSet<_> = root
+        .error_nodes()
+        .into_iter()
+        .filter_map(|error| {
+            let span = remap(error.span(), &edits)?;
+            Some((error.code(), error.error().map(str::to_owned), span))
+        })
+        .collect();
+    let text = splice(source, &edits);
+    let new = parser::parse(&text)
+        .error_nodes()
+        .into_iter()
+        .filter(|error| {
+            let key = (error.code(), error.error().map(str::to_owned), error.span());
+            !before.contains(&key)
+        })
+        .count();
+    if new > 0 {
+        return Err(FixError::NewErrors(new));
+    }
+    Ok(Fixed {
+        text,
+        applied,
+        skipped,
+    })
+}
@@ -0,0 +1,167 @@
+//! formatting of vimwiki documents
+//!
+//! the formatter only touches whitespace and never changes what a document
+//! means:
+//!
+//! - headings get a single space between their `=` and the title
+//! - the columns of tables are aligned
+//! - trailing whitespace is removed outside of code and math blocks
+//! - runs of blank lines become a single one, the document ends with a
+//!   single newline
+//!
+//! blocks with syntax errors are left as they are.
+
+use crate::ast::Heading;
+use crate::kind::SyntaxKind;
+use crate::parser::{self, Node};
+
+/// the formatted `source`
+pub fn format(source: &str) -> String {
+    let root = parser::parse(source);
+    let mut out = String::with_capacity(source.len());
+    // byte ranges of `out` which are copied verbatim
+    let mut verbatim = Vec::new();
+    for block in root.children() {
+        let start = out.len();
+        if !block.error_nodes().is_empty() {
+            out.push_str(&block.text());
+            verbatim.push(start..out.len());
+            continue;
+        }
+        match block.kind() {
+            SyntaxKind::Heading => out.push_str(&heading(block)),
+            SyntaxKind::Table => out.push_str(&table(block)),
+            SyntaxKind::CodeBlock | SyntaxKind::MathBlock => {
+                out.push_str(&block.text());
+                verbatim.push(start..out.len());
+            }
+            _ => out.push_str(&block.text()),
+        }
+    }
+    lines(&out, &verbatim)
+}
+
+/// whether `source` is formatted already
+pub fn is_formatted(source: &str) -> bool {
+    format(source) == source
+}
+
+/// strips trailing whitespace and extra blank lines outside of `verbatim`
+fn lines(text: &str, verbatim: &[std::ops::Range<usize>]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank = false;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let verbatim = verbatim.iter().any(|range| range.contains(&start));
+        let line = if verbatim { line } else { line.trim_end() };
+        if line.is_empty() {
+            blank = !out.is_empty();
+            continue;
+        }
+        if blank {
+            out.push('\n');
+            blank = false;
+        }
+        out.push_str(line);
+        if !verbatim {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// `= Title =` with the indent of centered headings kept
+fn heading(node: &Node) -> String {
+    let Some(heading) = Heading::cast(node) else {
+        return node.text();
+    };
+    let indent: String = node
+        .children()
+        .iter()
+        .take_while(|c| c.kind().is_trivia() && c.kind() != SyntaxKind::NewLine)
+        .map(Node::text)
+        .collect();
+    let level = heading.level();
+    let title = heading.title();
+    if level == 0 || title.is_empty() {
+        return node.text();
+    }
+    let marker = "=".repeat(level);
+    format!("{indent}{marker} {title} {marker}\n")
+}
+
+/// a table row split into its indent and trimmed cells, `None` for rows which
+/// do not start and end with `|`
+fn row(node: &Node) -> Option<(String, Vec<String>)> {
+    let children = node.children();
+    let indent: String = children
+        .iter()
+        .take_while(|c| {
+            c.kind() == SyntaxKind::IndentWhiteSpace || c.kind() == SyntaxKind::WhiteSpace
+        })
+        .map(Node::text)
+        .collect();
+    let meaningful: Vec<_> = children.iter().filter(|c| !c.kind().is_trivia()).collect();
+    if meaningful.first()?.kind() != SyntaxKind::Pipe
+        || meaningful.last()?.kind() != SyntaxKind::Pipe
+    {
+        return None;
+    }
+    let cells = children
+        .iter()
+        .filter(|c| c.kind() == SyntaxKind::TableCell)
+        .map(|c| c.text().trim().to_owned())
+        .collect();
+    Some((indent, cells))
+}
+
+/// whether a row is a `|---|---|` header separator
+fn is_separator(cells: &[String]) -> bool {
+    !cells.is_empty()
+        && cells
+            .iter()
+            .all(|cell| !cell.is_empty() && cell.chars().all(|c| c == '-'))
+}
+
+/// the table with its columns padded to the same width
+fn table(node: &Node) -> String {
+    let Some(rows) = node
+        .children()
+        .iter()
+        .filter(|c| c.kind() == SyntaxKind::TableRow)
+        .map(row)
+        .collect::<Option<Vec<_>>>()
+    else {
+        return node.text();
+    };
+    let mut widths: Vec<usize> = Vec::new();
+    for (_, cells) in rows.iter().filter(|(_, cells)| !is_separator(cells)) {
+        for (i, cell) in cells.iter().enumerate() {
+            let width = cell.chars().count();
+            match widths.get_mut(i) {
+                Some(w) => *w = (*w).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+    let mut out = String::new();
+    for (indent, cells) in &rows {
+        out.push_str(indent);
+        out.push('|');
+        let separator = is_separator(cells);
+        for (i, cell) in cells.iter().enumerate() {
+            let width = widths.get(i).copied().unwrap_or_default().max(1);
+            if separator {
+                out.push_str(&"-".repeat(width + 2));
+            } else {
+                let pad = width.saturating_sub(cell.chars().count());
+                out.push_str(&format!(" {cell}{} ", " ".repeat(pad)));
+            }
+            out.push('|');
+        }
+        out.push('\n');
+    }
+    out
+}
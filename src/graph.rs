@@ -0,0 +1,223 @@
+//! the graph of links between the pages of a workspace
+//!
+//! every existing page is a node, a link from one page to another is an
+//! edge. links to missing pages, anchors in the same page and urls are left
+//! out, `check` reports the broken ones.
+
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::ast::{self, Link};
+use crate::kind::SyntaxKind;
+use crate::workspace::{Page, Workspace};
+
+/// a page of a wiki of the workspace
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PageId {
+    pub wiki: usize,
+    pub page: String,
+}
+
+impl PageId {
+    pub fn new(wiki: usize, page: impl Into<String>) -> Self {
+        Self {
+            wiki,
+            page: page.into(),
+        }
+    }
+
+    /// the page as written in a link from the first wiki, like `todo` or
+    /// `wiki1:todo`
+    pub fn id(&self) -> String {
+        match self.wiki {
+            0 => self.page.clone(),
+            wiki => format!("wiki{wiki}:{}", self.page),
+        }
+    }
+}
+
+/// links between pages
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    /// every page, sorted
+    pages: Vec<PageId>,
+    /// `%title` of each page
+    titles: Vec<Option<String>>,
+    outgoing: Vec<BTreeSet<usize>>,
+    incoming: Vec<BTreeSet<usize>>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    id: String,
+    wiki: usize,
+    page: &'a str,
+    title: Option<&'a str>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct JsonEdge {
+    source: String,
+    target: String,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct JsonGraph<'a> {
+    nodes: Vec<JsonNode<'a>>,
+    edges: Vec<JsonEdge>,
+}
+
+/// quotes `text` as a DOT id
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Graph {
+    /// the graph of `pages`, the pages of each wiki of `workspace`
+    pub fn build(workspace: &Workspace, pages: &[Vec<Page>]) -> Self {
+        let mut all: Vec<(PageId, &Page)> = pages
+            .iter()
+            .enumerate()
+            .flat_map(|(wiki, pages)| pages.iter().map(move |p| (PageId::new(wiki, &p.name), p)))
+            .collect();
+        all.sort_by(|a, b| a.0.cmp(&b.0));
+        all.dedup_by(|a, b| a.0 == b.0);
+
+        let mut graph = Self {
+            pages: all.iter().map(|(id, _)| id.clone()).collect(),
+            titles: all.iter().map(|(_, page)| ast::title(&page.root)).collect(),
+            outgoing: vec![BTreeSet::new(); all.len()],
+            incoming: vec![BTreeSet::new(); all.len()],
+        };
+        for (from, (id, page)) in all.iter().enumerate() {
+            for node in ast::find_all(&page.root, SyntaxKind::Link) {
+                let Some(link) = Link::cast(node) else {
+                    continue;
+                };
+                let Some(location) = workspace.resolve(id.wiki, &id.page, &link.target()) else {
+                    continue;
+                };
+                let Some(to) = graph.index(&PageId::new(location.wiki, location.page)) else {
+                    continue;
+                };
+                if to != from {
+                    graph.outgoing[from].insert(to);
+                    graph.incoming[to].insert(from);
+                }
+            }
+        }
+        graph
+    }
+
+    fn index(&self, page: &PageId) -> Option<usize> {
+        self.pages.binary_search(page).ok()
+    }
+
+    /// every page, sorted by wiki and name
+    pub fn pages(&self) -> &[PageId] {
+        &self.pages
+    }
+
+    /// every link as `(from, to)`, sorted
+    pub fn edges(&self) -> impl Iterator<Item = (&PageId, &PageId)> {
+        self.outgoing
+            .iter()
+            .enumerate()
+            .flat_map(move |(from, to)| {
+                to.iter()
+                    .map(move |&to| (&self.pages[from], &self.pages[to]))
+            })
+    }
+
+    /// the pages linking to `page`
+    pub fn backlinks(&self, page: &PageId) -> Vec<&PageId> {
+        self.index(page)
+            .map(|i| self.incoming[i].iter().map(|&j| &self.pages[j]).collect())
+            .unwrap_or_default()
+    }
+
+    /// the pages `page` links to
+    pub fn outgoing(&self, page: &PageId) -> Vec<&PageId> {
+        self.index(page)
+            .map(|i| self.outgoing[i].iter().map(|&j| &self.pages[j]).collect())
+            .unwrap_or_default()
+    }
+
+    /// groups of pages connected by links in either direction, each sorted
+    /// and in the order of their first page
+    pub fn components(&self) -> Vec<Vec<&PageId>> {
+        let mut seen = vec![false; self.pages.len()];
+        let mut components = Vec::new();
+        for start in 0..self.pages.len() {
+            if seen[start] {
+                continue;
+            }
+            let mut members = vec![start];
+            seen[start] = true;
+            let mut next = 0;
+            while let Some(&i) = members.get(next) {
+                next += 1;
+                for &j in self.outgoing[i].iter().chain(&self.incoming[i]) {
+                    if !seen[j] {
+                        seen[j] = true;
+                        members.push(j);
+                    }
+                }
+            }
+            members.sort();
+            components.push(members.into_iter().map(|i| &self.pages[i]).collect());
+        }
+        components
+    }
+
+    /// the graph in Graphviz DOT, pages are labeled with their title
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph wiki {\n");
+        for (page, title) in self.pages.iter().zip(&self.titles) {
+            let id = page.id();
+            let _ = writeln!(
+                dot,
+                "    {} [label={}];",
+                quote(&id),
+                quote(title.as_deref().unwrap_or(&id))
+            );
+        }
+        for (from, to) in self.edges() {
+            let _ = writeln!(dot, "    {} -> {};", quote(&from.id()), quote(&to.id()));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// the graph as a JSON object of `nodes` and `edges`
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let graph = JsonGraph {
+            nodes: self
+                .pages
+                .iter()
+                .zip(&self.titles)
+                .map(|(page, title)| JsonNode {
+                    id: page.id(),
+                    wiki: page.wiki,
+                    page: &page.page,
+                    title: title.as_deref(),
+                })
+                .collect(),
+            edges: self
+                .edges()
+                .map(|(from, to)| JsonEdge {
+                    source: from.id(),
+                    target: to.id(),
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&graph).unwrap_or_default()
+    }
+}
@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 #[repr(u8)]
@@ -55,15 +56,140 @@ pub enum SyntaxKind {
     SuperScriptMarker,
     /// `,`
     SubScriptMarker,
+    /// `|` link description and table cell separator
+    Pipe,
+    /// `$` inline math
+    Dollar,
+
+    /// `= heading =`, level is the number of `=`
+    Heading,
+    /// consecutive lines of inline content
+    Paragraph,
+    /// `*bold*`
+    Bold,
+    /// `_italic_`
+    Italic,
+    /// `~~strikethrough~~`
+    Strikethrough,
+    /// `` `code` ``
+    Code,
+    /// `^superscript^`
+    Superscript,
+    /// `,,subscript,,`
+    Subscript,
+    /// `$math$`
+    Math,
+    /// `[[target|description]]`
+    Link,
+    /// the part of a link before `|`
+    LinkTarget,
+    /// the part of a link after `|`
+    LinkDescription,
+    /// `{{image.png}}`
+    Transclusion,
+    /// `:tag1:tag2:`
+    Tags,
+    /// a single tag name inside of `Tags`
+    Tag,
+    /// consecutive list items sharing an indent
+    List,
+    /// a single list item including its nested lists
+    ListItem,
+    /// `-`, `*`, `#`, `1.`, `a)` ...
+    ListMarker,
+    /// `[ ]`, `[.]`, `[o]`, `[O]`, `[X]`, `[-]`
+    Checkbox,
+    /// consecutive `| cell |` rows
+    Table,
+    /// a single table line
+    TableRow,
+    /// a single table cell
+    TableCell,
+    /// `{{{` preformatted text `}}}`
+    CodeBlock,
+    /// `{{$` math `}}$`
+    MathBlock,
+    /// `%% comment` and `%%+ comment +%%`
+    Comment,
+    /// `%title`, `%date`, `%template`, `%nohtml`
+    Placeholder,
+    /// `----`
+    HorizontalRule,
 }
 
 impl SyntaxKind {
+    /// every kind, in declaration order
+    pub const ALL: [SyntaxKind; 55] = [
+        Self::LeftSqBrackets,
+        Self::RightSqBrackets,
+        Self::LeftParen,
+        Self::RightParen,
+        Self::LeftCurlyBraces,
+        Self::RightCurlyBraces,
+        Self::Slash,
+        Self::Astrisk,
+        Self::Tilda,
+        Self::Hyphen,
+        Self::SemiColon,
+        Self::HashTag,
+        Self::Equal,
+        Self::Percentage,
+        Self::WhiteSpace,
+        Self::NewLine,
+        Self::Text,
+        Self::Eof,
+        Self::Underscore,
+        Self::At,
+        Self::Error,
+        Self::Root,
+        Self::IndentWhiteSpace,
+        Self::CodeMarker,
+        Self::SuperScriptMarker,
+        Self::SubScriptMarker,
+        Self::Pipe,
+        Self::Dollar,
+        Self::Heading,
+        Self::Paragraph,
+        Self::Bold,
+        Self::Italic,
+        Self::Strikethrough,
+        Self::Code,
+        Self::Superscript,
+        Self::Subscript,
+        Self::Math,
+        Self::Link,
+        Self::LinkTarget,
+        Self::LinkDescription,
+        Self::Transclusion,
+        Self::Tags,
+        Self::Tag,
+        Self::List,
+        Self::ListItem,
+        Self::ListMarker,
+        Self::Checkbox,
+        Self::Table,
+        Self::TableRow,
+        Self::TableCell,
+        Self::CodeBlock,
+        Self::MathBlock,
+        Self::Comment,
+        Self::Placeholder,
+        Self::HorizontalRule,
+    ];
+
     pub fn is_grouping(&self) -> bool {
         matches!(self, Self::LeftCurlyBraces)
     }
     pub fn is_error(&self) -> bool {
         matches!(self, Self::Error)
     }
+    /// whitespace and newlines, which carry no meaning of their own
+    pub fn is_trivia(&self) -> bool {
+        matches!(
+            self,
+            Self::WhiteSpace | Self::IndentWhiteSpace | Self::NewLine
+        )
+    }
 }
 
 impl Display for SyntaxKind {
@@ -85,20 +211,61 @@ impl Display for SyntaxKind {
                 SyntaxKind::Error => "ERROR",
                 SyntaxKind::Root => "ROOT",
                 SyntaxKind::IndentWhiteSpace => "INDENTWHITESPACE",
-                SyntaxKind::LeftSqBrackets => "TODO",
-                SyntaxKind::RightSqBrackets => "TODO",
-                SyntaxKind::LeftParen => "TODO",
-                SyntaxKind::RightParen => "TODO",
-                SyntaxKind::LeftCurlyBraces => "TODO",
-                SyntaxKind::RightCurlyBraces => "TODO",
-                SyntaxKind::SemiColon => "TODO",
-                SyntaxKind::HashTag => "TODO",
-                SyntaxKind::Equal => "TODO",
-                SyntaxKind::Percentage => "TODO",
-                SyntaxKind::CodeMarker => "TODO",
-                SyntaxKind::SuperScriptMarker => "TODO",
-                SyntaxKind::SubScriptMarker => "TODO",
+                SyntaxKind::LeftSqBrackets => "LEFTSQBRACKETS",
+                SyntaxKind::RightSqBrackets => "RIGHTSQBRACKETS",
+                SyntaxKind::LeftParen => "LEFTPAREN",
+                SyntaxKind::RightParen => "RIGHTPAREN",
+                SyntaxKind::LeftCurlyBraces => "LEFTCURLYBRACES",
+                SyntaxKind::RightCurlyBraces => "RIGHTCURLYBRACES",
+                SyntaxKind::SemiColon => "SEMICOLON",
+                SyntaxKind::HashTag => "HASHTAG",
+                SyntaxKind::Equal => "EQUAL",
+                SyntaxKind::Percentage => "PERCENTAGE",
+                SyntaxKind::CodeMarker => "CODEMARKER",
+                SyntaxKind::SuperScriptMarker => "SUPERSCRIPTMARKER",
+                SyntaxKind::SubScriptMarker => "SUBSCRIPTMARKER",
+                SyntaxKind::Pipe => "PIPE",
+                SyntaxKind::Dollar => "DOLLAR",
+                SyntaxKind::Heading => "HEADING",
+                SyntaxKind::Paragraph => "PARAGRAPH",
+                SyntaxKind::Bold => "BOLD",
+                SyntaxKind::Italic => "ITALIC",
+                SyntaxKind::Strikethrough => "STRIKETHROUGH",
+                SyntaxKind::Code => "CODE",
+                SyntaxKind::Superscript => "SUPERSCRIPT",
+                SyntaxKind::Subscript => "SUBSCRIPT",
+                SyntaxKind::Math => "MATH",
+                SyntaxKind::Link => "LINK",
+                SyntaxKind::LinkTarget => "LINKTARGET",
+                SyntaxKind::LinkDescription => "LINKDESCRIPTION",
+                SyntaxKind::Transclusion => "TRANSCLUSION",
+                SyntaxKind::Tags => "TAGS",
+                SyntaxKind::Tag => "TAG",
+                SyntaxKind::List => "LIST",
+                SyntaxKind::ListItem => "LISTITEM",
+                SyntaxKind::ListMarker => "LISTMARKER",
+                SyntaxKind::Checkbox => "CHECKBOX",
+                SyntaxKind::Table => "TABLE",
+                SyntaxKind::TableRow => "TABLEROW",
+                SyntaxKind::TableCell => "TABLECELL",
+                SyntaxKind::CodeBlock => "CODEBLOCK",
+                SyntaxKind::MathBlock => "MATHBLOCK",
+                SyntaxKind::Comment => "COMMENT",
+                SyntaxKind::Placeholder => "PLACEHOLDER",
+                SyntaxKind::HorizontalRule => "HORIZONTALRULE",
             }
         )
     }
 }
+
+impl FromStr for SyntaxKind {
+    type Err = String;
+
+    /// the kind with the `Display` name `s`, like `HEADING`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| format!("unknown syntax kind `{s}`"))
+    }
+}
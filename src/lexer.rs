@@ -18,16 +18,16 @@ impl std::fmt::Display for Token {
 }
 
 pub struct Lexer {
-    source: Rope,
-    start: usize,   // start index (char offset) of current lexeme
-    current: usize, // current index (char offset)
+    source: String,
+    start: usize,   // start index (byte offset) of current lexeme
+    current: usize, // current index (byte offset)
     tokens: Vec<Token>,
 }
 
 impl Lexer {
     pub fn new(source: Rope) -> Self {
         Self {
-            source,
+            source: source.to_string(),
             start: 0,
             current: 0,
             tokens: Vec::new(),
@@ -36,51 +36,28 @@ impl Lexer {
 
     #[inline]
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len_chars()
+        self.current >= self.source.len()
     }
 
     // peek at the current charecter without consuming it.
     #[inline]
     fn peek(&self) -> Option<char> {
-        if self.is_at_end() {
-            None
-        } else {
-            self.source
-                .get_slice(self.current..)
-                .unwrap()
-                .chars()
-                .next()
-        }
+        self.source[self.current..].chars().next()
     }
 
     // eat current char and return next char, updating current
     #[inline]
     fn advance(&mut self) -> Option<char> {
-        if self.is_at_end() {
-            None
-        } else {
-            self.source
-                .get_slice(self.current..)
-                .unwrap()
-                .chars()
-                .next()
-                .map(|c| {
-                    let char = c;
-                    self.current += char.len_utf8();
-                    char
-                })
-        }
+        let char = self.peek()?;
+        self.current += char.len_utf8();
+        Some(char)
     }
 
     #[inline]
     fn make_token(&self, token_kind: SyntaxKind) -> Token {
         Token {
             kind: token_kind,
-            text: self
-                .source
-                .get_slice(self.start..self.current)
-                .unwrap()
-                .to_string(),
+            text: self.source[self.start..self.current].to_string(),
             span: Span::new(self.start, self.current),
         }
     }
@@ -119,6 +96,19 @@ impl Lexer {
                 '~' => self.make_token(SyntaxKind::Tilda),
                 '/' => self.make_token(SyntaxKind::Slash),
                 '_' => self.make_token(SyntaxKind::Underscore),
+                '`' => self.make_token(SyntaxKind::CodeMarker),
+                '^' => self.make_token(SyntaxKind::SuperScriptMarker),
+                ',' => self.make_token(SyntaxKind::SubScriptMarker),
+                '$' => self.make_token(SyntaxKind::Dollar),
+                '[' => self.make_token(SyntaxKind::LeftSqBrackets),
+                ']' => self.make_token(SyntaxKind::RightSqBrackets),
+                '{' => self.make_token(SyntaxKind::LeftCurlyBraces),
+                '}' => self.make_token(SyntaxKind::RightCurlyBraces),
+                '|' => self.make_token(SyntaxKind::Pipe),
+                ':' => self.make_token(SyntaxKind::SemiColon),
+                '#' => self.make_token(SyntaxKind::HashTag),
+                '=' => self.make_token(SyntaxKind::Equal),
+                '%' => self.make_token(SyntaxKind::Percentage),
                 '\n' => self.make_token(SyntaxKind::NewLine),
                 ' ' | '\t' => {
                     let prev_token = self
@@ -1,21 +1,64 @@
 use self::lexer::Token;
 
+pub mod anchor;
 pub mod ast;
+pub mod check;
+pub mod diary;
+pub mod export;
+pub mod fix;
+pub mod fmt;
+pub mod graph;
 pub mod kind;
 pub mod lexer;
+pub mod line_index;
+pub mod lint;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 pub mod parser;
+pub mod query;
+pub mod reparser;
+#[cfg(feature = "serde")]
+pub mod schema;
+pub mod serve;
+pub mod site;
 pub mod span;
+pub mod tags;
+pub mod tasks;
+pub mod toc;
+pub mod visit;
+pub mod workspace;
 
 pub(crate) mod error;
 
 pub trait NeoChar {
-    /// returns true if the char matches with any syntax char of neorg
+    /// returns true if the char matches with any syntax char of vimwiki
     fn is_special_char(&self) -> bool;
 }
 
 impl NeoChar for char {
     fn is_special_char(&self) -> bool {
-        matches!(self, '*' | '/' | '_' | '\n' | '\t' | '~' | '-')
+        matches!(
+            self,
+            '*' | '/'
+                | '_'
+                | '\n'
+                | '\t'
+                | '~'
+                | '-'
+                | '`'
+                | '^'
+                | ','
+                | '$'
+                | '['
+                | ']'
+                | '{'
+                | '}'
+                | '|'
+                | ':'
+                | '#'
+                | '='
+                | '%'
+        )
     }
 }
 
@@ -0,0 +1,264 @@
+//! conversion between byte offsets and line/column positions
+//!
+//! spans are byte offsets, editors count columns in UTF-8 bytes, UTF-16 code
+//! units or chars. a `LineIndex` is built once for a document, updated with
+//! every edit and converts between all of them without looking at the text
+//! again.
+
+#[cfg(feature = "lsp")]
+use tower_lsp::lsp_types::PositionEncodingKind;
+#[cfg(feature = "lsp")]
+pub use tower_lsp::lsp_types::{Position, Range};
+
+use crate::span::Span;
+
+/// a zero based line and column, like the lsp type of the same name
+#[cfg(not(feature = "lsp"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[cfg(not(feature = "lsp"))]
+impl Position {
+    pub fn new(line: u32, character: u32) -> Self {
+        Self { line, character }
+    }
+}
+
+/// a range between two positions, like the lsp type of the same name
+#[cfg(not(feature = "lsp"))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[cfg(not(feature = "lsp"))]
+impl Range {
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
+    }
+}
+
+/// the unit columns are counted in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// bytes
+    Utf8,
+    /// UTF-16 code units, the default of the language server protocol
+    #[default]
+    Utf16,
+    /// chars
+    Utf32,
+}
+
+impl Encoding {
+    /// the encoding to use with a client supporting `supported`
+    ///
+    /// UTF-8 needs no conversion at all and is preferred, without a list
+    /// UTF-16 is the only choice.
+    #[cfg(feature = "lsp")]
+    pub fn negotiate(supported: Option<&[PositionEncodingKind]>) -> Self {
+        let supported = supported.unwrap_or_default();
+        [Self::Utf8, Self::Utf32]
+            .into_iter()
+            .find(|encoding| supported.contains(&encoding.kind()))
+            .unwrap_or(Self::Utf16)
+    }
+
+    #[cfg(feature = "lsp")]
+    pub fn kind(&self) -> PositionEncodingKind {
+        match self {
+            Self::Utf8 => PositionEncodingKind::UTF8,
+            Self::Utf16 => PositionEncodingKind::UTF16,
+            Self::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    /// length of `char` in this encoding
+    fn len(&self, char: char) -> usize {
+        match self {
+            Self::Utf8 => char.len_utf8(),
+            Self::Utf16 => char.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+}
+
+/// a char taking more than one byte
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct WideChar {
+    /// byte offset in the text
+    start: usize,
+    char: char,
+}
+
+/// line starts and wide chars of a text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineIndex {
+    len: usize,
+    /// byte offset of every line start, a text ending in a newline has an
+    /// empty last line
+    lines: Vec<usize>,
+    /// every char longer than one byte, in text order
+    wide: Vec<WideChar>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut lines = vec![0];
+        let mut wide = Vec::new();
+        for (start, char) in text.char_indices() {
+            if char == '\n' {
+                lines.push(start + 1);
+            } else if !char.is_ascii() {
+                wide.push(WideChar { start, char });
+            }
+        }
+        Self {
+            len: text.len(),
+            lines,
+            wide,
+        }
+    }
+
+    /// updates the index for the text of the byte range `span` being
+    /// replaced with `text`
+    ///
+    /// only the replaced lines are scanned, the line starts and wide chars
+    /// after them are moved.
+    pub fn edit(&mut self, span: Span, text: &str) {
+        let span = Span::new(span.start.min(self.len), span.end.min(self.len));
+        let delta = text.len() as isize - (span.end - span.start) as isize;
+        let moved = |offset: usize| offset.saturating_add_signed(delta);
+
+        let mut lines = Vec::new();
+        let mut wide = Vec::new();
+        for (start, char) in text.char_indices() {
+            if char == '\n' {
+                lines.push(span.start + start + 1);
+            } else if !char.is_ascii() {
+                wide.push(WideChar {
+                    start: span.start + start,
+                    char,
+                });
+            }
+        }
+
+        // line starts after a removed line break, wide chars inside the span
+        let from = self.lines.partition_point(|&start| start <= span.start);
+        let to = self.lines.partition_point(|&start| start <= span.end);
+        for start in &mut self.lines[to..] {
+            *start = moved(*start);
+        }
+        self.lines.splice(from..to, lines);
+
+        let from = self.wide.partition_point(|w| w.start < span.start);
+        let to = self.wide.partition_point(|w| w.start < span.end);
+        for w in &mut self.wide[to..] {
+            w.start = moved(w.start);
+        }
+        self.wide.splice(from..to, wide);
+
+        self.len = moved(self.len);
+    }
+
+    /// length of the text in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// zero based line of a byte offset, offsets past the end are on the last line
+    pub fn line(&self, offset: usize) -> usize {
+        self.lines.partition_point(|&start| start <= offset) - 1
+    }
+
+    /// byte range of `line` without its line break
+    pub fn line_span(&self, line: usize) -> Option<Span> {
+        let start = *self.lines.get(line)?;
+        let end = self.lines.get(line + 1).map_or(self.len, |next| next - 1);
+        Some(Span::new(start, end))
+    }
+
+    /// wide chars starting in the byte range `span`
+    fn wide_in(&self, span: Span) -> &[WideChar] {
+        let from = self.wide.partition_point(|w| w.start < span.start);
+        let to = self.wide.partition_point(|w| w.start < span.end);
+        &self.wide[from..to]
+    }
+
+    /// the start of the char containing `offset`, clamped to the text
+    fn floor(&self, offset: usize) -> usize {
+        let offset = offset.min(self.len);
+        let index = self.wide.partition_point(|w| w.start <= offset);
+        match index.checked_sub(1).map(|i| self.wide[i]) {
+            Some(w) if offset < w.start + w.char.len_utf8() => w.start,
+            _ => offset,
+        }
+    }
+
+    /// line and column of a byte offset
+    ///
+    /// offsets inside of a char count as its start, offsets past the end as
+    /// the end of the text.
+    pub fn position(&self, offset: usize, encoding: Encoding) -> Position {
+        let offset = self.floor(offset);
+        let line = self.line(offset);
+        let start = self.lines[line];
+        let column: usize = (offset - start)
+            - self
+                .wide_in(Span::new(start, offset))
+                .iter()
+                .map(|w| w.char.len_utf8() - encoding.len(w.char))
+                .sum::<usize>();
+        Position::new(line as u32, column as u32)
+    }
+
+    /// byte offset of a position, `None` if its line does not exist
+    ///
+    /// columns past the end of the line mean the end of the line, columns
+    /// inside of a char its start.
+    pub fn offset(&self, position: Position, encoding: Encoding) -> Option<usize> {
+        let line = self.line_span(position.line as usize)?;
+        let column = position.character as usize;
+        let (mut offset, mut units) = (line.start, 0);
+        for w in self.wide_in(line) {
+            let ascii = w.start - offset;
+            if units + ascii >= column {
+                return Some(offset + column - units);
+            }
+            units += ascii;
+            offset = w.start;
+            let len = encoding.len(w.char);
+            if units + len > column {
+                return Some(offset);
+            }
+            units += len;
+            offset += w.char.len_utf8();
+        }
+        Some((offset + column - units).min(line.end))
+    }
+
+    pub fn range(&self, span: Span, encoding: Encoding) -> Range {
+        Range::new(
+            self.position(span.start, encoding),
+            self.position(span.end, encoding),
+        )
+    }
+
+    pub fn span(&self, range: Range, encoding: Encoding) -> Option<Span> {
+        let start = self.offset(range.start, encoding)?;
+        let end = self.offset(range.end, encoding)?;
+        Some(Span::new(start, end.max(start)))
+    }
+}
@@ -0,0 +1,568 @@
+//! style and correctness lints for a page
+//!
+//! every [`Rule`] has an id and a default [`Severity`], which can be changed
+//! in the `[lint]` table of the workspace config:
+//!
+//! ```toml
+//! [lint]
+//! trailing-whitespace = "off"
+//! heading-skip = "error"
+//! ```
+//!
+//! `%% lint-disable rule...` turns rules off from the comment on, for the
+//! rest of the page or up to a `%% lint-enable rule...`. without rules it
+//! turns off every rule. names which are not rules are reported and do not
+//! turn off anything.
+//!
+//! lints which can be fixed carry a [`Fix`], see [`crate::fix`].
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use thiserror::Error;
+use toml::de::{DeTable, DeValue};
+
+use crate::ast::{self, Heading, Link, ListItem};
+use crate::fix::{Fix, TextEdit};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::span::Span;
+use crate::visit::{Visitor, Walk, walk};
+use crate::workspace::CONFIG_FILE;
+
+#[derive(Debug, Error)]
+pub enum LintError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid lint config: {0}")]
+    Config(#[from] toml::de::Error),
+    #[error("unknown lint rule `{0}`")]
+    UnknownRule(String),
+    #[error("unknown lint severity `{0}`")]
+    UnknownSeverity(String),
+    #[error("invalid lint config: {0}")]
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// the rule is not checked
+    Off,
+    Info,
+    Warning,
+    /// fails `check`
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        })
+    }
+}
+
+impl FromStr for Severity {
+    type Err = LintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "info" => Ok(Self::Info),
+            "warning" => Ok(Self::Warning),
+            "error" => Ok(Self::Error),
+            _ => Err(LintError::UnknownSeverity(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rule {
+    /// a heading more than one level below the one before
+    HeadingSkip,
+    EmptyHeading,
+    /// a heading with the title of an earlier one, links reach the first
+    DuplicateHeading,
+    /// `-` and `*` items following each other
+    MixedBullets,
+    /// numbered items not counting up from the first
+    ListNumbering,
+    /// table rows with more or fewer cells than the first
+    RaggedTable,
+    TrailingWhitespace,
+    /// `[[page|page]]`
+    RedundantDescription,
+    /// a name in a `%% lint-disable` or `%% lint-enable` comment which is
+    /// not a rule
+    UnknownRule,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 9] = [
+        Self::HeadingSkip,
+        Self::EmptyHeading,
+        Self::DuplicateHeading,
+        Self::MixedBullets,
+        Self::ListNumbering,
+        Self::RaggedTable,
+        Self::TrailingWhitespace,
+        Self::RedundantDescription,
+        Self::UnknownRule,
+    ];
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::HeadingSkip => "heading-skip",
+            Self::EmptyHeading => "empty-heading",
+            Self::DuplicateHeading => "duplicate-heading",
+            Self::MixedBullets => "mixed-bullets",
+            Self::ListNumbering => "list-numbering",
+            Self::RaggedTable => "ragged-table",
+            Self::TrailingWhitespace => "trailing-whitespace",
+            Self::RedundantDescription => "redundant-description",
+            Self::UnknownRule => "unknown-rule",
+        }
+    }
+
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Self::TrailingWhitespace | Self::RedundantDescription => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for Rule {
+    type Err = LintError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.id() == s)
+            .ok_or_else(|| LintError::UnknownRule(s.to_owned()))
+    }
+}
+
+/// the severities of the rules
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LintConfig {
+    /// rules which do not have their default severity
+    severities: BTreeMap<Rule, Severity>,
+}
+
+impl LintConfig {
+    /// reads the `[lint]` table of a TOML config
+    pub fn from_toml(source: &str) -> Result<Self, LintError> {
+        let file = DeTable::parse(source).map_err(|mut err| {
+            err.set_input(Some(source));
+            err
+        })?;
+        let mut config = Self::default();
+        let lint = match file.get_ref().get("lint").map(|lint| lint.get_ref()) {
+            None => return Ok(config),
+            Some(DeValue::Table(lint)) => lint,
+            Some(_) => return Err(LintError::Invalid("`lint` must be a table".to_owned())),
+        };
+        for (rule, severity) in lint {
+            let DeValue::String(severity) = severity.get_ref() else {
+                return Err(LintError::Invalid(format!(
+                    "the severity of `{}` must be a string",
+                    rule.get_ref()
+                )));
+            };
+            config.set(rule.get_ref().parse()?, severity.parse()?);
+        }
+        Ok(config)
+    }
+
+    /// loads the TOML config at `path`
+    pub fn load(path: &Path) -> Result<Self, LintError> {
+        let source = std::fs::read_to_string(path).map_err(|source| LintError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::from_toml(&source)
+    }
+
+    /// the config of directory `dir`, from its workspace config file if it
+    /// has one
+    pub fn discover(dir: &Path) -> Result<Self, LintError> {
+        let config = dir.join(CONFIG_FILE);
+        if config.is_file() {
+            Self::load(&config)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn set(&mut self, rule: Rule, severity: Severity) {
+        self.severities.insert(rule, severity);
+    }
+
+    pub fn severity(&self, rule: Rule) -> Severity {
+        self.severities
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+}
+
+/// a problem found by a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub rule: Rule,
+    pub severity: Severity,
+    pub span: Span,
+    pub message: String,
+    /// the edits which fix the problem, if it can be fixed
+    pub fix: Option<Fix>,
+}
+
+/// the lints of the page below `root`, in document order
+pub fn lint(root: &Node, config: &LintConfig) -> Vec<Lint> {
+    let mut linter = Linter {
+        config,
+        found: Vec::new(),
+        previous: None,
+        titles: HashSet::new(),
+        runs: Vec::new(),
+        directives: Directives::default(),
+    };
+    walk(root, &mut linter);
+    let Linter {
+        mut found,
+        directives,
+        ..
+    } = linter;
+    found.retain(|lint| lint.severity != Severity::Off && !directives.is_disabled(lint));
+    found.sort_by_key(|lint| (lint.span.start, lint.rule));
+    found
+}
+
+/// the rules, checked on a walk over a page
+struct Linter<'a> {
+    config: &'a LintConfig,
+    found: Vec<Lint>,
+    /// level of the heading before
+    previous: Option<usize>,
+    titles: HashSet<String>,
+    /// for the root and each list item the walk is in, the bullet of the
+    /// run of lists among its children so far and where the run ends
+    runs: Vec<Option<(String, usize)>>,
+    directives: Directives,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: Rule, span: Span, message: String, fix: Option<Fix>) {
+        self.found.push(Lint {
+            rule,
+            severity: self.config.severity(rule),
+            span,
+            message,
+            fix,
+        });
+    }
+}
+
+impl Visitor for Linter<'_> {
+    fn enter_root(&mut self, node: &Node) -> Walk {
+        self.runs.push(None);
+        trailing_whitespace(&node.text(), &mut |rule, span, message, fix| {
+            self.report(rule, span, message, fix)
+        });
+        Walk::Continue
+    }
+
+    fn leave_root(&mut self, _node: &Node) {
+        self.runs.pop();
+    }
+
+    /// the parser reads a heading without a title, like `== ==`, as a
+    /// paragraph
+    fn enter_paragraph(&mut self, node: &Node) -> Walk {
+        let mut start = node.span().start;
+        for line in node.text().split_inclusive('\n') {
+            if is_empty_heading(line) {
+                let indent = line.len() - line.trim_start().len();
+                let span = Span::new(start + indent, start + line.trim_end().len());
+                self.report(
+                    Rule::EmptyHeading,
+                    span,
+                    "the heading is empty".to_owned(),
+                    None,
+                );
+            }
+            start += line.len();
+        }
+        Walk::Continue
+    }
+
+    fn enter_heading(&mut self, node: &Node) -> Walk {
+        let Some(heading) = Heading::cast(node) else {
+            return Walk::Continue;
+        };
+        let span = ast::trimmed_span(node.children().iter()).unwrap_or(heading.span());
+        let level = heading.level();
+        if let Some(previous) = self.previous
+            && level > previous + 1
+        {
+            let message = format!("heading level {level} skips level {}", previous + 1);
+            self.report(Rule::HeadingSkip, span, message, None);
+        }
+        self.previous = Some(level);
+        let title = heading.title();
+        if title.is_empty() {
+            self.report(
+                Rule::EmptyHeading,
+                span,
+                "the heading is empty".to_owned(),
+                None,
+            );
+        } else if !self.titles.insert(title.clone()) {
+            let message = format!("duplicate heading `{title}`, links to it reach the first one");
+            self.report(Rule::DuplicateHeading, span, message, None);
+        }
+        Walk::Continue
+    }
+
+    /// the parser starts a new list where the bullet changes, so mixed
+    /// bullets are lists following each other without a blank line
+    fn enter_list(&mut self, node: &Node) -> Walk {
+        let markers: Vec<_> = node
+            .children()
+            .iter()
+            .filter_map(ListItem::cast)
+            .filter_map(|item| item.marker())
+            .collect();
+        let run = self.runs.last_mut().and_then(Option::take);
+        let run = match markers.first().map(|marker| marker.text()) {
+            Some(bullet) if bullet == "-" || bullet == "*" => match run {
+                Some((first, end)) if end == node.span().start => {
+                    if first != bullet {
+                        for marker in &markers {
+                            let message = format!("bullet `{bullet}` differs from `{first}` above");
+                            let fix = Fix::edit(
+                                format!("Replace `{bullet}` with `{first}`"),
+                                TextEdit::new(marker.span(), first.as_str()),
+                            );
+                            self.report(Rule::MixedBullets, marker.span(), message, Some(fix));
+                        }
+                    }
+                    Some((first, node.span().end))
+                }
+                _ => Some((bullet, node.span().end)),
+            },
+            _ => {
+                numbering(&markers, &mut |rule, span, message, fix| {
+                    self.report(rule, span, message, fix)
+                });
+                None
+            }
+        };
+        if let Some(last) = self.runs.last_mut() {
+            *last = run;
+        }
+        Walk::Continue
+    }
+
+    fn enter_list_item(&mut self, _node: &Node) -> Walk {
+        self.runs.push(None);
+        Walk::Continue
+    }
+
+    fn leave_list_item(&mut self, _node: &Node) {
+        self.runs.pop();
+    }
+
+    fn enter_table(&mut self, node: &Node) -> Walk {
+        ragged_table(node, &mut |rule, span, message, fix| {
+            self.report(rule, span, message, fix)
+        });
+        Walk::Continue
+    }
+
+    fn enter_link(&mut self, node: &Node) -> Walk {
+        let Some(link) = Link::cast(node) else {
+            return Walk::Continue;
+        };
+        if link
+            .description()
+            .is_some_and(|description| description.trim() == link.target().trim())
+        {
+            let message = "the description repeats the link target".to_owned();
+            let fix =
+                link.target_node()
+                    .zip(link.description_node())
+                    .map(|(target, description)| {
+                        let span = Span::new(target.span().end, description.span().end);
+                        Fix::edit("Remove the description", TextEdit::delete(span))
+                    });
+            self.report(Rule::RedundantDescription, link.span(), message, fix);
+        }
+        Walk::Continue
+    }
+
+    fn enter_comment(&mut self, node: &Node) -> Walk {
+        for (span, name) in self.directives.push(node) {
+            let message = format!("unknown lint rule `{name}`, it is ignored");
+            self.report(Rule::UnknownRule, span, message, None);
+        }
+        Walk::Skip
+    }
+}
+
+/// whether `line` is a heading without a title, like `== ==`, which the
+/// parser reads as a paragraph
+fn is_empty_heading(line: &str) -> bool {
+    let line = line.trim();
+    let open = line.chars().take_while(|c| *c == '=').count();
+    let close = line.chars().rev().take_while(|c| *c == '=').count();
+    (1..=6).contains(&open)
+        && open == close
+        && line.len() > open
+        && line[open..line.len() - close].trim().is_empty()
+}
+
+/// reports numbered markers like `1.` which do not count up from the first
+fn numbering(markers: &[&Node], report: &mut impl FnMut(Rule, Span, String, Option<Fix>)) {
+    let number = |marker: &Node| {
+        let text = marker.text();
+        let digits: String = text.chars().take_while(char::is_ascii_digit).collect();
+        digits.parse::<usize>().ok()
+    };
+    let Some(first) = markers.first().and_then(|marker| number(marker)) else {
+        return;
+    };
+    for (i, marker) in markers.iter().enumerate().skip(1) {
+        let expected = first + i;
+        if let Some(found) = number(marker)
+            && found != expected
+        {
+            let message = format!("item numbered {found}, expected {expected}");
+            let text = marker.text();
+            let suffix = text.trim_start_matches(|c: char| c.is_ascii_digit());
+            let fix = Fix::edit(
+                format!("Renumber to `{expected}{suffix}`"),
+                TextEdit::new(marker.span(), format!("{expected}{suffix}")),
+            );
+            report(Rule::ListNumbering, marker.span(), message, Some(fix));
+        }
+    }
+}
+
+fn ragged_table(table: &Node, report: &mut impl FnMut(Rule, Span, String, Option<Fix>)) {
+    let mut rows = table
+        .children()
+        .iter()
+        .filter(|row| row.kind() == SyntaxKind::TableRow)
+        .map(|row| {
+            let cells = row
+                .children()
+                .iter()
+                .filter(|cell| cell.kind() == SyntaxKind::TableCell)
+                .count();
+            (row, cells)
+        });
+    let Some((_, columns)) = rows.next() else {
+        return;
+    };
+    for (row, cells) in rows.filter(|(_, cells)| *cells != columns) {
+        let span = ast::trimmed_span(row.children().iter()).unwrap_or(row.span());
+        let message = format!("row has {cells} cells, the first has {columns}");
+        report(Rule::RaggedTable, span, message, None);
+    }
+}
+
+fn trailing_whitespace(text: &str, report: &mut impl FnMut(Rule, Span, String, Option<Fix>)) {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_end_matches([' ', '\t']);
+        if trimmed.len() < content.len() {
+            let span = Span::new(start + trimmed.len(), start + content.len());
+            report(
+                Rule::TrailingWhitespace,
+                span,
+                "trailing whitespace".to_owned(),
+                Some(Fix::edit(
+                    "Remove trailing whitespace",
+                    TextEdit::delete(span),
+                )),
+            );
+        }
+        start += line.len();
+    }
+}
+
+/// the `%% lint-disable` and `%% lint-enable` comments of a page
+#[derive(Default)]
+struct Directives {
+    /// offset, whether rules are disabled, the rules or `None` for all
+    directives: Vec<(usize, bool, Option<Vec<Rule>>)>,
+}
+
+impl Directives {
+    /// records `comment` if it is a directive, comments come in document
+    /// order
+    ///
+    /// returns the names which are not rules with their spans. they are left
+    /// out, so a directive naming only unknown rules affects none.
+    fn push(&mut self, comment: &Node) -> Vec<(Span, String)> {
+        let text = comment.text();
+        let body = text.trim_start().trim_start_matches('%').trim_start();
+        let base = comment.span().start + text.len() - body.len();
+        let mut cursor = 0;
+        let mut words = body.split_whitespace().map(|word| {
+            let at = cursor + body[cursor..].find(word).unwrap_or_default();
+            cursor = at + word.len();
+            (word, base + at)
+        });
+        let disable = match words.next() {
+            Some(("lint-disable", _)) => true,
+            Some(("lint-enable", _)) => false,
+            _ => return Vec::new(),
+        };
+        let (mut rules, mut unknown) = (Vec::new(), Vec::new());
+        let mut named = false;
+        for (word, start) in words {
+            named = true;
+            match word.parse() {
+                Ok(rule) => rules.push(rule),
+                Err(_) => unknown.push((Span::new(start, start + word.len()), word.to_owned())),
+            }
+        }
+        self.directives
+            .push((comment.span().start, disable, named.then_some(rules)));
+        unknown
+    }
+
+    fn is_disabled(&self, lint: &Lint) -> bool {
+        let mut disabled = false;
+        for (offset, disable, rules) in &self.directives {
+            if *offset > lint.span.start {
+                break;
+            }
+            if rules
+                .as_ref()
+                .is_none_or(|rules| rules.contains(&lint.rule))
+            {
+                disabled = *disable;
+            }
+        }
+        disabled
+    }
+}
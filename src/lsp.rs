@@ -0,0 +1,775 @@
+//! language server for vimwiki files
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+use ropey::Rope;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer, LspService, Server};
+
+use crate::line_index::{Encoding, LineIndex};
+use crate::lint::{self, LintConfig, Severity};
+use crate::parser::{self, Node};
+use crate::reparser::{self, Edit};
+use crate::span::Span;
+use crate::toc;
+use crate::workspace::Workspace;
+
+pub mod code_action;
+pub mod completion;
+pub mod folding;
+pub mod hover;
+pub mod rename;
+pub mod semantic;
+
+/// an open document
+#[derive(Debug, Clone)]
+pub struct Document {
+    pub text: Rope,
+    pub root: Node,
+    pub index: LineIndex,
+    /// the encoding columns are exchanged with the client in
+    pub encoding: Encoding,
+}
+
+impl Document {
+    pub fn new(text: &str) -> Self {
+        Self::with_encoding(text, Encoding::default())
+    }
+
+    pub fn with_encoding(text: &str, encoding: Encoding) -> Self {
+        Self {
+            text: text.into(),
+            root: parser::parse(text),
+            index: LineIndex::new(text),
+            encoding,
+        }
+    }
+
+    /// applies `edit` to the text and reparses the blocks it touched
+    pub fn edit(&mut self, edit: &Edit) {
+        edit.apply(&mut self.text);
+        self.root = reparser::reparse(&self.root, &self.text, edit);
+        self.index.edit(edit.span, &edit.text);
+    }
+
+    /// byte offset of an lsp position, `None` if it is outside of the document
+    pub fn offset_at(&self, position: Position) -> Option<usize> {
+        self.index.offset(position, self.encoding)
+    }
+
+    /// the lsp range of a span of the document
+    pub fn range(&self, span: Span) -> Range {
+        self.index.range(span, self.encoding)
+    }
+}
+
+pub use crate::workspace::{Page, relative_page, resolve_page};
+
+/// source reported with every diagnostic of the server
+pub const SOURCE: &str = "vimwiki";
+
+/// the errors of a parsed document as lsp diagnostics, with the id of their
+/// [`ErrorCode`](crate::parser::ErrorCode) as code
+pub fn diagnostics(document: &Document) -> Vec<Diagnostic> {
+    document
+        .root
+        .error_nodes()
+        .into_iter()
+        .filter_map(|error| {
+            let range = document.range(error.span());
+            let message = match (error.error(), error.hint()) {
+                (Some(error), Some(hint)) => format!("{error}, {hint}"),
+                (Some(error), None) => error.to_owned(),
+                (None, _) => return None,
+            };
+            Some(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                code: error
+                    .code()
+                    .map(|code| NumberOrString::String(code.id().to_owned())),
+                source: Some(SOURCE.to_owned()),
+                message,
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// the lints of a document as lsp diagnostics, with the rule as code
+pub fn lints(document: &Document, config: &LintConfig) -> Vec<Diagnostic> {
+    lint::lint(&document.root, config)
+        .into_iter()
+        .map(|lint| Diagnostic {
+            range: document.range(lint.span),
+            severity: Some(match lint.severity {
+                Severity::Error => DiagnosticSeverity::ERROR,
+                Severity::Warning => DiagnosticSeverity::WARNING,
+                Severity::Info | Severity::Off => DiagnosticSeverity::INFORMATION,
+            }),
+            code: Some(NumberOrString::String(lint.rule.id().to_owned())),
+            source: Some(SOURCE.to_owned()),
+            message: lint.message,
+            ..Default::default()
+        })
+        .collect()
+}
+
+pub struct Backend {
+    client: Client,
+    documents: RwLock<HashMap<Url, Arc<Document>>>,
+    /// the parsed pages of each wiki, read on first use and kept up to date
+    /// by `refresh`
+    pages: RwLock<HashMap<usize, Arc<Vec<Page>>>>,
+    workspace: RwLock<Workspace>,
+    lint: RwLock<LintConfig>,
+    encoding: RwLock<Encoding>,
+}
+
+impl Backend {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            documents: RwLock::new(HashMap::new()),
+            pages: RwLock::new(HashMap::new()),
+            workspace: RwLock::new(Workspace::default()),
+            lint: RwLock::new(LintConfig::default()),
+            encoding: RwLock::new(Encoding::default()),
+        }
+    }
+
+    fn document(&self, uri: &Url) -> Option<Arc<Document>> {
+        self.documents.read().ok()?.get(uri).cloned()
+    }
+
+    fn encoding(&self) -> Encoding {
+        self.encoding.read().map(|e| *e).unwrap_or_default()
+    }
+
+    fn workspace(&self) -> Workspace {
+        self.workspace.read().map(|w| w.clone()).unwrap_or_default()
+    }
+
+    /// the wiki and page name of the document behind `uri`
+    fn locate(&self, uri: &Url) -> Option<(usize, String)> {
+        self.workspace().locate(&uri.to_file_path().ok()?)
+    }
+
+    /// every page of the wiki `uri` belongs to sorted by name, open documents
+    /// take precedence over the disk
+    fn pages(&self, uri: &Url) -> Arc<Vec<Page>> {
+        let wiki = self.locate(uri).map_or(0, |(wiki, _)| wiki);
+        if let Some(pages) = self.pages.read().ok().and_then(|c| c.get(&wiki).cloned()) {
+            return pages;
+        }
+        let workspace = self.workspace();
+        let Some(config) = workspace.wiki(wiki) else {
+            return Arc::default();
+        };
+        let pages: Vec<Page> = config
+            .pages()
+            .into_iter()
+            .filter_map(|name| {
+                let root = self.read(&config.path(&name))?;
+                Some(Page { name, root })
+            })
+            .collect();
+        let pages = Arc::new(pages);
+        if let Ok(mut cache) = self.pages.write() {
+            cache.insert(wiki, Arc::clone(&pages));
+        }
+        pages
+    }
+
+    /// the tree of the page stored at `path`, from its open document if there
+    /// is one
+    fn read(&self, path: &Path) -> Option<Node> {
+        let open = Url::from_file_path(path)
+            .ok()
+            .and_then(|uri| self.document(&uri));
+        match open {
+            Some(document) => Some(document.root.clone()),
+            None => Some(parser::parse(&std::fs::read_to_string(path).ok()?)),
+        }
+    }
+
+    /// brings the cached page behind `uri` up to date, it is dropped when the
+    /// file is gone
+    fn refresh(&self, uri: &Url) {
+        let Some((wiki, name)) = self.locate(uri) else {
+            return;
+        };
+        let root = uri.to_file_path().ok().and_then(|path| self.read(&path));
+        let Ok(mut cache) = self.pages.write() else {
+            return;
+        };
+        // wikis nobody asked for yet are read in full when they are
+        let Some(pages) = cache.get_mut(&wiki) else {
+            return;
+        };
+        let pages = Arc::make_mut(pages);
+        match (pages.binary_search_by(|page| page.name.cmp(&name)), root) {
+            (Ok(index), Some(root)) => pages[index].root = root,
+            (Err(index), Some(root)) => pages.insert(index, Page { name, root }),
+            (Ok(index), None) => {
+                pages.remove(index);
+            }
+            (Err(_), None) => {}
+        }
+    }
+
+    /// name of the page behind `uri`, relative to the root of its wiki
+    fn page_of(&self, uri: &Url) -> Option<String> {
+        self.locate(uri).map(|(_, page)| page)
+    }
+
+    /// the uri of `page` in the same wiki as `uri`
+    fn page_uri(&self, uri: &Url, page: &str) -> Option<Url> {
+        let wiki = self.locate(uri).map_or(0, |(wiki, _)| wiki);
+        Url::from_file_path(self.workspace().path(wiki, page)?).ok()
+    }
+
+    /// the parse errors and lints of `document`
+    fn diagnose(&self, document: &Document) -> Vec<Diagnostic> {
+        let mut diagnostics = diagnostics(document);
+        if let Ok(config) = self.lint.read() {
+            diagnostics.extend(lints(document, &config));
+        }
+        diagnostics
+    }
+
+    async fn update(&self, uri: Url, document: Document) {
+        let diagnostics = self.diagnose(&document);
+        if let Ok(mut documents) = self.documents.write() {
+            documents.insert(uri.clone(), Arc::new(document));
+        }
+        self.refresh(&uri);
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+
+    fn workspace_edit(&self, edits: Vec<(Url, Vec<TextEdit>)>) -> WorkspaceEdit {
+        let operations = edits
+            .into_iter()
+            .map(|(uri, edits)| {
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+            })
+            .collect();
+        WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            ..Default::default()
+        }
+    }
+}
+
+#[tower_lsp::async_trait]
+impl LanguageServer for Backend {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let root = params
+            .workspace_folders
+            .and_then(|folders| folders.into_iter().next())
+            .map(|folder| folder.uri)
+            .or(params.root_uri)
+            .and_then(|uri| uri.to_file_path().ok());
+        let lint = match root.as_deref().map(LintConfig::discover) {
+            Some(Ok(config)) => config,
+            Some(Err(err)) => {
+                self.client
+                    .log_message(MessageType::ERROR, err.to_string())
+                    .await;
+                LintConfig::default()
+            }
+            None => LintConfig::default(),
+        };
+        if let Ok(mut current) = self.lint.write() {
+            *current = lint;
+        }
+        let workspace = match root {
+            Some(root) => match Workspace::discover(&root) {
+                Ok(workspace) => workspace,
+                Err(err) => {
+                    self.client
+                        .log_message(MessageType::ERROR, err.to_string())
+                        .await;
+                    Workspace::single(root)
+                }
+            },
+            None => Workspace::default(),
+        };
+        if let Ok(mut current) = self.workspace.write() {
+            *current = workspace;
+        }
+        let encoding = Encoding::negotiate(
+            params
+                .capabilities
+                .general
+                .as_ref()
+                .and_then(|general| general.position_encodings.as_deref()),
+        );
+        if let Ok(mut current) = self.encoding.write() {
+            *current = encoding;
+        }
+
+        Ok(InitializeResult {
+            capabilities: ServerCapabilities {
+                position_encoding: Some(encoding.kind()),
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
+                        save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                        ..Default::default()
+                    },
+                )),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec!["[".to_owned(), "#".to_owned(), ":".to_owned()]),
+                    ..Default::default()
+                }),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: semantic::legend(),
+                            range: Some(true),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::REFACTOR_REWRITE,
+                            CodeActionKind::SOURCE,
+                        ]),
+                        ..Default::default()
+                    },
+                )),
+                ..Default::default()
+            },
+            server_info: Some(ServerInfo {
+                name: env!("CARGO_PKG_NAME").to_owned(),
+                version: Some(env!("CARGO_PKG_VERSION").to_owned()),
+            }),
+        })
+    }
+
+    async fn initialized(&self, _: InitializedParams) {
+        // pages changed outside of the editor refresh the page cache
+        let watchers = self
+            .workspace()
+            .wikis
+            .iter()
+            .map(|wiki| FileSystemWatcher {
+                glob_pattern: GlobPattern::String(format!("**/*.{}", wiki.extension)),
+                kind: None,
+            })
+            .collect();
+        let options = DidChangeWatchedFilesRegistrationOptions { watchers };
+        let registration = Registration {
+            id: "watched-pages".to_owned(),
+            method: "workspace/didChangeWatchedFiles".to_owned(),
+            register_options: serde_json::to_value(options).ok(),
+        };
+        if let Err(err) = self.client.register_capability(vec![registration]).await {
+            self.client
+                .log_message(MessageType::WARNING, err.to_string())
+                .await;
+        }
+        self.client
+            .log_message(MessageType::INFO, "vimwiki language server initialized")
+            .await;
+    }
+
+    async fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn did_open(&self, params: DidOpenTextDocumentParams) {
+        let document = Document::with_encoding(&params.text_document.text, self.encoding());
+        self.update(params.text_document.uri, document).await;
+    }
+
+    async fn did_change(&self, params: DidChangeTextDocumentParams) {
+        let uri = params.text_document.uri;
+        let encoding = self.encoding();
+        // the stored document is edited in place, it is only copied while a
+        // request still holds on to it
+        let diagnostics = {
+            let Ok(mut documents) = self.documents.write() else {
+                return;
+            };
+            let document = documents
+                .entry(uri.clone())
+                .or_insert_with(|| Arc::new(Document::with_encoding("", encoding)));
+            let document = Arc::make_mut(document);
+            for change in params.content_changes {
+                let span = change.range.and_then(|range| {
+                    let start = document.offset_at(range.start)?;
+                    let end = document.offset_at(range.end)?;
+                    Some(Span::new(start, end.max(start)))
+                });
+                match span {
+                    Some(span) => document.edit(&Edit::new(span, change.text)),
+                    None => *document = Document::with_encoding(&change.text, encoding),
+                }
+            }
+            self.diagnose(document)
+        };
+        self.refresh(&uri);
+        self.client
+            .publish_diagnostics(uri, diagnostics, None)
+            .await;
+    }
+
+    async fn did_save(&self, params: DidSaveTextDocumentParams) {
+        self.refresh(&params.text_document.uri);
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        if let Ok(mut documents) = self.documents.write() {
+            documents.remove(&params.text_document.uri);
+        }
+        // unsaved changes are gone, the page is what is on disk again
+        self.refresh(&params.text_document.uri);
+    }
+
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        for change in params.changes {
+            self.refresh(&change.uri);
+        }
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let Some(document) = self.document(&uri) else {
+            return Ok(None);
+        };
+        let current = self.page_of(&uri).unwrap_or_default();
+        let items = completion::complete(&document, position, &current, &self.pages(&uri));
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = params.text_document_position_params.text_document.uri;
+        let Some(document) = self.document(&uri) else {
+            return Ok(None);
+        };
+        let Some(offset) = document.offset_at(params.text_document_position_params.position) else {
+            return Ok(None);
+        };
+        let current = self.page_of(&uri).unwrap_or_default();
+        let Some((value, span)) = hover::hover(&document.root, &current, offset, &self.pages(&uri))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(document.range(span)),
+        }))
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        let Some(document) = self.document(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        Ok(Some(folding::folding_ranges(
+            &document.root,
+            &document.index,
+        )))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        let Some(document) = self.document(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let current = self.page_of(&params.text_document.uri).unwrap_or_default();
+        let target = document
+            .offset_at(params.position)
+            .and_then(|offset| rename::target_at(&document.root, &current, offset));
+        let Some(target) = target else {
+            return Ok(None);
+        };
+        let span = target.span();
+        Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: document.range(span),
+            placeholder: document.text.byte_slice(span.start..span.end).to_string(),
+        }))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = params.text_document_position.text_document.uri;
+        let Some(document) = self.document(&uri) else {
+            return Ok(None);
+        };
+        let current = self.page_of(&uri).unwrap_or_default();
+        let target = document
+            .offset_at(params.text_document_position.position)
+            .and_then(|offset| rename::target_at(&document.root, &current, offset));
+        let Some(target) = target else {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                "only links and headings can be renamed",
+            ));
+        };
+
+        let renamed = rename::rename(
+            &target,
+            &current,
+            &params.new_name,
+            &self.pages(&uri),
+            self.encoding(),
+        )
+        .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        let mut operations = Vec::new();
+        for (page, edits) in renamed.edits {
+            let Some(page_uri) = self.page_uri(&uri, &page) else {
+                continue;
+            };
+            operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier {
+                    uri: page_uri,
+                    version: None,
+                },
+                edits: edits.into_iter().map(OneOf::Left).collect(),
+            }));
+        }
+        if let Some((old, new)) = renamed.moved
+            && let (Some(old_uri), Some(new_uri)) =
+                (self.page_uri(&uri, &old), self.page_uri(&uri, &new))
+        {
+            operations.push(DocumentChangeOperation::Op(ResourceOp::Rename(
+                RenameFile {
+                    old_uri,
+                    new_uri,
+                    options: None,
+                    annotation_id: None,
+                },
+            )));
+        }
+        Ok(Some(WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            ..Default::default()
+        }))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let Some(document) = self.document(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let data = semantic::tokens(&document, None);
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        })))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let Some(document) = self.document(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let (Some(start), Some(end)) = (
+            document.offset_at(params.range.start),
+            document.offset_at(params.range.end),
+        ) else {
+            return Ok(None);
+        };
+        let data = semantic::tokens(&document, Some(Span::new(start, end)));
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        })))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let Some(document) = self.document(&uri) else {
+            return Ok(None);
+        };
+        let Some(offset) = document.offset_at(params.range.start) else {
+            return Ok(None);
+        };
+        let root = &document.root;
+        let mut actions = Vec::new();
+        let mut push = |title: String,
+                        kind: CodeActionKind,
+                        edit: WorkspaceEdit,
+                        diagnostics: Option<Vec<Diagnostic>>| {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(kind),
+                diagnostics,
+                edit: Some(edit),
+                ..Default::default()
+            }));
+        };
+
+        // fixes for the errors under the cursor and the diagnostics the client sent
+        for error in root.error_nodes() {
+            let range = document.range(error.span());
+            let reported: Vec<_> = params
+                .context
+                .diagnostics
+                .iter()
+                .filter(|d| d.range == range && d.source.as_deref() == Some(SOURCE))
+                .cloned()
+                .collect();
+            let under_cursor = range.start <= params.range.end && params.range.start <= range.end;
+            if !under_cursor && reported.is_empty() {
+                continue;
+            }
+            if let Some((title, edit)) = code_action::fix(&document, error) {
+                push(
+                    title,
+                    CodeActionKind::QUICKFIX,
+                    self.workspace_edit(vec![(uri.clone(), vec![edit])]),
+                    (!reported.is_empty()).then_some(reported),
+                );
+            }
+        }
+
+        // and for the lints which can be fixed
+        let found = match self.lint.read() {
+            Ok(config) => lint::lint(root, &config),
+            Err(_) => Vec::new(),
+        };
+        for lint in found {
+            let Some(fix) = &lint.fix else {
+                continue;
+            };
+            let range = document.range(lint.span);
+            let code = NumberOrString::String(lint.rule.id().to_owned());
+            let reported: Vec<_> = params
+                .context
+                .diagnostics
+                .iter()
+                .filter(|d| d.range == range && d.code.as_ref() == Some(&code))
+                .cloned()
+                .collect();
+            let under_cursor = range.start <= params.range.end && params.range.start <= range.end;
+            if !under_cursor && reported.is_empty() {
+                continue;
+            }
+            if let Some(edits) = code_action::edits(&document, fix) {
+                push(
+                    fix.title.clone(),
+                    CodeActionKind::QUICKFIX,
+                    self.workspace_edit(vec![(uri.clone(), edits)]),
+                    (!reported.is_empty()).then_some(reported),
+                );
+            }
+        }
+
+        if let Some(edits) = code_action::toggle_checkbox(&document, offset) {
+            push(
+                "Toggle checkbox".to_owned(),
+                CodeActionKind::REFACTOR_REWRITE,
+                self.workspace_edit(vec![(uri.clone(), edits)]),
+                None,
+            );
+        }
+        if let Some(edits) = code_action::renumber_list(&document, offset) {
+            push(
+                "Renumber list".to_owned(),
+                CodeActionKind::REFACTOR_REWRITE,
+                self.workspace_edit(vec![(uri.clone(), edits)]),
+                None,
+            );
+        }
+        for style in code_action::LIST_STYLES {
+            let Some(edits) = code_action::convert_list(&document, offset, style) else {
+                continue;
+            };
+            if !edits.is_empty() {
+                push(
+                    format!("Convert list to `{style}`"),
+                    CodeActionKind::REFACTOR_REWRITE,
+                    self.workspace_edit(vec![(uri.clone(), edits)]),
+                    None,
+                );
+            }
+        }
+
+        if let Some((span, new_text)) = toc::edit(root, 6) {
+            let edit = TextEdit {
+                range: document.range(span),
+                new_text,
+            };
+            push(
+                "Update table of contents".to_owned(),
+                CodeActionKind::SOURCE,
+                self.workspace_edit(vec![(uri.clone(), vec![edit])]),
+                None,
+            );
+        }
+
+        let current = self.page_of(&uri).unwrap_or_default();
+        if let Some(page) = code_action::missing_page(root, &current, offset, &self.pages(&uri))
+            && let Some(new_uri) = self.page_uri(&uri, &page)
+        {
+            let title = page.rsplit('/').next().unwrap_or(&page).to_owned();
+            let mut edit = self.workspace_edit(vec![(
+                new_uri.clone(),
+                vec![TextEdit {
+                    range: Range::default(),
+                    new_text: format!("= {title} =\n"),
+                }],
+            )]);
+            if let Some(DocumentChanges::Operations(operations)) = &mut edit.document_changes {
+                operations.insert(
+                    0,
+                    DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                        uri: new_uri,
+                        options: Some(CreateFileOptions {
+                            overwrite: Some(false),
+                            ignore_if_exists: Some(true),
+                        }),
+                        annotation_id: None,
+                    })),
+                );
+            }
+            push(
+                format!("Create page `{page}`"),
+                CodeActionKind::QUICKFIX,
+                edit,
+                None,
+            );
+        }
+
+        Ok(Some(actions))
+    }
+}
+
+/// runs the language server over stdin and stdout
+pub async fn serve_stdio() {
+    let stdin = tokio::io::stdin();
+    let stdout = tokio::io::stdout();
+    let (service, socket) = LspService::new(Backend::new);
+    Server::new(stdin, stdout, socket).serve(service).await;
+}
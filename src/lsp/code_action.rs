@@ -0,0 +1,272 @@
+//! quick fixes for parser diagnostics and list refactorings
+
+use std::collections::HashMap;
+
+use tower_lsp::lsp_types::TextEdit;
+
+use super::{Document, Page, resolve_page};
+use crate::ast::{CheckboxState, ListItem};
+use crate::kind::SyntaxKind;
+use crate::parser::{ErrorNode, Node};
+use crate::span::Span;
+
+fn edit(document: &Document, span: Span, new_text: impl Into<String>) -> Option<TextEdit> {
+    (span.end <= document.index.len()).then(|| TextEdit {
+        range: document.range(span),
+        new_text: new_text.into(),
+    })
+}
+
+/// a quick fix for an error reported by the parser, `None` if there is none
+pub fn fix(document: &Document, error: &ErrorNode) -> Option<(String, TextEdit)> {
+    let fix = crate::fix::error_fix(&document.text.to_string(), error)?;
+    let [edit] = <[_; 1]>::try_from(fix.edits).ok()?;
+    Some((fix.title, self::edit(document, edit.span, edit.new_text)?))
+}
+
+/// the edits of a fix of a syntax error or lint
+pub fn edits(document: &Document, fix: &crate::fix::Fix) -> Option<Vec<TextEdit>> {
+    fix.edits
+        .iter()
+        .map(|edit| self::edit(document, edit.span, edit.new_text.as_str()))
+        .collect()
+}
+
+/// the list items containing `offset`, outermost first
+fn list_items(root: &Node, offset: usize) -> Vec<&Node> {
+    root.covering(offset)
+        .into_iter()
+        .filter(|n| n.kind() == SyntaxKind::ListItem)
+        .collect()
+}
+
+/// direct child items of a list item
+fn child_items<'a>(item: &ListItem<'a>) -> impl Iterator<Item = ListItem<'a>> {
+    item.sublists()
+        .flat_map(|list| list.children())
+        .filter_map(ListItem::cast)
+}
+
+fn rate(state: CheckboxState) -> Option<f64> {
+    match state {
+        CheckboxState::Open => Some(0.0),
+        CheckboxState::Started => Some(25.0),
+        CheckboxState::Half => Some(50.0),
+        CheckboxState::Mostly => Some(75.0),
+        CheckboxState::Done => Some(100.0),
+        CheckboxState::Rejected => None,
+    }
+}
+
+/// state of a parent from the completion of its children, vimwiki style
+fn aggregate(states: &[CheckboxState]) -> Option<CheckboxState> {
+    let rates: Vec<_> = states.iter().filter_map(|s| rate(*s)).collect();
+    if rates.is_empty() {
+        return None;
+    }
+    let average = rates.iter().sum::<f64>() / rates.len() as f64;
+    Some(if average >= 100.0 {
+        CheckboxState::Done
+    } else if average <= 0.0 {
+        CheckboxState::Open
+    } else if average <= 100.0 / 3.0 {
+        CheckboxState::Started
+    } else if average <= 200.0 / 3.0 {
+        CheckboxState::Half
+    } else {
+        CheckboxState::Mostly
+    })
+}
+
+/// toggles the checkbox of the list item at `offset`
+///
+/// children follow the new state and the checkboxes of all parents are
+/// updated to reflect how many of their children are done. items without a
+/// checkbox get an empty one.
+pub fn toggle_checkbox(document: &Document, offset: usize) -> Option<Vec<TextEdit>> {
+    let root = &document.root;
+    let chain = list_items(root, offset);
+    let item = ListItem::cast(chain.last()?)?;
+
+    let Some(checkbox) = item.checkbox() else {
+        let marker = item.marker()?;
+        let at = item
+            .content()
+            .find(|n| !n.kind().is_trivia())
+            .map_or(marker.span().end + 1, |n| n.span().start);
+        let at = at.min(document.index.len());
+        return Some(vec![edit(document, Span::new(at, at), "[ ] ")?]);
+    };
+
+    let new_state = match checkbox.state() {
+        CheckboxState::Done => CheckboxState::Open,
+        _ => CheckboxState::Done,
+    };
+
+    // new states keyed by the start of the checkbox
+    let mut states: HashMap<usize, CheckboxState> = HashMap::new();
+    fn force(item: ListItem, state: CheckboxState, states: &mut HashMap<usize, CheckboxState>) {
+        if let Some(checkbox) = item.checkbox() {
+            states.insert(checkbox.span().start, state);
+        }
+        for child in child_items(&item) {
+            force(child, state, states);
+        }
+    }
+    force(item, new_state, &mut states);
+
+    for parent in chain.iter().rev().skip(1).filter_map(|n| ListItem::cast(n)) {
+        let Some(checkbox) = parent.checkbox() else {
+            continue;
+        };
+        let children: Vec<_> = child_items(&parent)
+            .filter_map(|child| child.checkbox())
+            .map(|c| {
+                states
+                    .get(&c.span().start)
+                    .copied()
+                    .unwrap_or_else(|| c.state())
+            })
+            .collect();
+        if let Some(state) = aggregate(&children) {
+            states.insert(checkbox.span().start, state);
+        }
+    }
+
+    let mut checkboxes: Vec<_> = crate::ast::find_all(root, SyntaxKind::Checkbox)
+        .into_iter()
+        .filter_map(crate::ast::Checkbox::cast)
+        .filter_map(|c| {
+            let state = states.get(&c.span().start)?;
+            (*state != c.state()).then_some((c.span().start, *state))
+        })
+        .collect();
+    checkboxes.sort_by_key(|(start, _)| *start);
+    checkboxes
+        .into_iter()
+        .map(|(start, state)| edit(document, Span::new(start + 1, start + 2), state.marker()))
+        .collect()
+}
+
+/// markers offered when converting a list
+pub const LIST_STYLES: [&str; 4] = ["-", "*", "#", "1."];
+
+/// the innermost list containing `offset`
+pub fn list_at(root: &Node, offset: usize) -> Option<&Node> {
+    root.covering(offset)
+        .into_iter()
+        .rev()
+        .find(|n| n.kind() == SyntaxKind::List)
+}
+
+fn markers(list: &Node) -> Vec<&Node> {
+    list.children()
+        .iter()
+        .filter_map(ListItem::cast)
+        .filter_map(|item| item.marker())
+        .collect()
+}
+
+fn to_roman(mut n: usize) -> String {
+    const NUMERALS: [(usize, &str); 13] = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut roman = String::new();
+    for (value, numeral) in NUMERALS {
+        while n >= value {
+            roman.push_str(numeral);
+            n -= value;
+        }
+    }
+    roman
+}
+
+fn to_letters(mut n: usize) -> String {
+    let mut letters = Vec::new();
+    while n > 0 {
+        n -= 1;
+        letters.push((b'a' + (n % 26) as u8) as char);
+        n /= 26;
+    }
+    letters.iter().rev().collect()
+}
+
+/// the `n`th marker, starting at 1, of a list whose first marker is `first`
+///
+/// bullets stay the same, numbers, letters and roman numerals count up.
+pub fn nth_marker(first: &str, n: usize) -> String {
+    let Some(punctuation) = first.chars().last() else {
+        return first.to_owned();
+    };
+    let body = &first[..first.len() - punctuation.len_utf8()];
+    let upper = body.chars().all(|c| c.is_ascii_uppercase());
+    let number = if body.is_empty() {
+        return first.to_owned();
+    } else if body.chars().all(|c| c.is_ascii_digit()) {
+        n.to_string()
+    } else if body.eq_ignore_ascii_case("i")
+        || (body.len() > 1 && body.chars().all(|c| "ivxlcdmIVXLCDM".contains(c)))
+    {
+        to_roman(n)
+    } else {
+        to_letters(n)
+    };
+    let number = if upper {
+        number.to_ascii_uppercase()
+    } else {
+        number
+    };
+    format!("{number}{punctuation}")
+}
+
+/// replaces the markers of the list at `offset` with `style`, numbering ordered styles
+pub fn convert_list(document: &Document, offset: usize, style: &str) -> Option<Vec<TextEdit>> {
+    let list = list_at(&document.root, offset)?;
+    markers(list)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, marker)| {
+            let new = nth_marker(style, i + 1);
+            (new != marker.text()).then(|| edit(document, marker.span(), new))
+        })
+        .collect()
+}
+
+/// renumbers the ordered list at `offset`, `None` if it is not ordered or in order
+pub fn renumber_list(document: &Document, offset: usize) -> Option<Vec<TextEdit>> {
+    let list = list_at(&document.root, offset)?;
+    let first = markers(list).first()?.text();
+    if !first.starts_with(char::is_alphanumeric) {
+        return None;
+    }
+    // numbering always starts over at 1, `a)` or `i)`
+    let style = nth_marker(&first, 1);
+    let edits = convert_list(document, offset, &style)?;
+    (!edits.is_empty()).then_some(edits)
+}
+
+/// the page a link at `offset` points to, if it does not exist yet
+pub fn missing_page(root: &Node, current: &str, offset: usize, pages: &[Page]) -> Option<String> {
+    let link = root
+        .covering(offset)
+        .into_iter()
+        .rev()
+        .find_map(crate::ast::Link::cast)?;
+    if link.scheme().is_some() || link.page().is_empty() {
+        return None;
+    }
+    let page = resolve_page(current, &link.page());
+    (!pages.iter().any(|p| p.name == page)).then_some(page)
+}
@@ -0,0 +1,173 @@
+//! completion of link targets, heading anchors and tags
+
+use std::collections::BTreeSet;
+
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Position, Range, TextEdit,
+};
+
+use super::{Document, Page};
+use crate::anchor::{AnchorKind, Anchors};
+use crate::ast::{self, Tags};
+use crate::kind::SyntaxKind;
+use crate::span::Span;
+use crate::workspace::{relative_page, resolve_page};
+
+/// what is being completed, the span covers the already typed text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Context {
+    /// `[[pa`
+    Page { span: Span },
+    /// `[[page#hea`, the page is empty for anchors into the current page
+    Anchor { page: String, span: Span },
+    /// `:ta`
+    Tag { span: Span },
+}
+
+impl Context {
+    fn shifted(self, by: usize) -> Self {
+        let shift = |span: Span| Span::new(span.start + by, span.end + by);
+        match self {
+            Self::Page { span } => Self::Page { span: shift(span) },
+            Self::Anchor { page, span } => Self::Anchor {
+                page,
+                span: shift(span),
+            },
+            Self::Tag { span } => Self::Tag { span: shift(span) },
+        }
+    }
+}
+
+/// finds the completion context of the cursor at byte `offset`
+pub fn context(text: &str, offset: usize) -> Option<Context> {
+    let before = text.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = &before[line_start..];
+
+    if let Some(open) = line.rfind("[[") {
+        let typed = &line[open + 2..];
+        if !typed.contains("]]") && !typed.contains('|') {
+            let start = line_start + open + 2;
+            return Some(match typed.find('#') {
+                Some(hash) => Context::Anchor {
+                    page: typed[..hash].trim().to_owned(),
+                    span: Span::new(start + hash + 1, offset),
+                },
+                None => Context::Page {
+                    span: Span::new(start, offset),
+                },
+            });
+        }
+    }
+
+    let word_start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+    let word = &line[word_start..];
+    if word.starts_with(':') {
+        let last = word.rfind(':')?;
+        return Some(Context::Tag {
+            span: Span::new(line_start + word_start + last + 1, offset),
+        });
+    }
+    None
+}
+
+/// completion items for the cursor at `position` in `document`
+///
+/// `current` is the page name of `document`, `pages` are all pages of the wiki.
+pub fn complete(
+    document: &Document,
+    position: Position,
+    current: &str,
+    pages: &[Page],
+) -> Vec<CompletionItem> {
+    // the context only depends on the line up to the cursor
+    let Some(context) = document.offset_at(position).and_then(|offset| {
+        let line = document.index.line_span(position.line as usize)?;
+        let before = document
+            .text
+            .get_byte_slice(line.start..offset)?
+            .to_string();
+        Some(context(&before, before.len())?.shifted(line.start))
+    }) else {
+        return Vec::new();
+    };
+
+    match context {
+        Context::Page { span } => {
+            let range = document.range(span);
+            pages
+                .iter()
+                .map(|page| {
+                    item(
+                        &relative_page(current, &page.name),
+                        CompletionItemKind::FILE,
+                        ast::title(&page.root),
+                        range,
+                    )
+                })
+                .collect()
+        }
+        Context::Anchor { page, span } => {
+            let range = document.range(span);
+            let page = resolve_page(current, &page);
+            let root = if page == current {
+                Some(&document.root)
+            } else {
+                pages.iter().find(|p| p.name == page).map(|p| &p.root)
+            };
+            let Some(root) = root else {
+                return Vec::new();
+            };
+            // the unique ids, so duplicate headings each have their own item
+            Anchors::new(root)
+                .iter()
+                .map(|anchor| match anchor.kind {
+                    AnchorKind::Heading => item(
+                        &anchor.id,
+                        CompletionItemKind::REFERENCE,
+                        Some(format!("{} {}", "=".repeat(anchor.level), anchor.name)),
+                        range,
+                    ),
+                    AnchorKind::Tag => item(
+                        &anchor.id,
+                        CompletionItemKind::KEYWORD,
+                        Some(format!(":{}:", anchor.name)),
+                        range,
+                    ),
+                })
+                .collect()
+        }
+        Context::Tag { span } => {
+            let range = document.range(span);
+            let tags: BTreeSet<String> = pages
+                .iter()
+                .map(|p| &p.root)
+                .chain(std::iter::once(&document.root))
+                .flat_map(|root| ast::find_all(root, SyntaxKind::Tags))
+                .filter_map(Tags::cast)
+                .flat_map(|tags| tags.names())
+                .collect();
+            tags.iter()
+                .map(|tag| item(tag, CompletionItemKind::KEYWORD, None, range))
+                .collect()
+        }
+    }
+}
+
+fn item(
+    label: &str,
+    kind: CompletionItemKind,
+    detail: Option<String>,
+    range: Range,
+) -> CompletionItem {
+    CompletionItem {
+        label: label.to_owned(),
+        kind: Some(kind),
+        detail,
+        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+            range,
+            new_text: label.to_owned(),
+        })),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,78 @@
+//! folding ranges for heading sections and multiline blocks
+
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
+
+use crate::ast::Heading;
+use crate::kind::SyntaxKind;
+use crate::line_index::LineIndex;
+use crate::parser::Node;
+
+/// line of the last character before `end`
+fn last_line(index: &LineIndex, start: usize, end: usize) -> u32 {
+    index.line(end.saturating_sub(1).max(start)) as u32
+}
+
+fn range(start: u32, end: u32, kind: FoldingRangeKind) -> Option<FoldingRange> {
+    (end > start).then(|| FoldingRange {
+        start_line: start,
+        end_line: end,
+        kind: Some(kind),
+        ..Default::default()
+    })
+}
+
+fn blocks(node: &Node, index: &LineIndex, out: &mut Vec<FoldingRange>) {
+    for child in node.children() {
+        let kind = match child.kind() {
+            SyntaxKind::List
+            | SyntaxKind::Table
+            | SyntaxKind::CodeBlock
+            | SyntaxKind::MathBlock => Some(FoldingRangeKind::Region),
+            SyntaxKind::Comment => Some(FoldingRangeKind::Comment),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let span = child.span();
+            let start = index.line(span.start) as u32;
+            let end = last_line(index, span.start, span.end);
+            out.extend(range(start, end, kind));
+        }
+        if matches!(child.kind(), SyntaxKind::List | SyntaxKind::ListItem) {
+            blocks(child, index, out);
+        }
+    }
+}
+
+/// folding ranges of a parsed document, sorted by their first line
+///
+/// a heading folds everything up to the next heading of the same or a
+/// higher level, blank lines at the end of the section stay visible.
+pub fn folding_ranges(root: &Node, index: &LineIndex) -> Vec<FoldingRange> {
+    let mut out = Vec::new();
+
+    let children = root.children();
+    for (i, child) in children.iter().enumerate() {
+        let Some(heading) = Heading::cast(child) else {
+            continue;
+        };
+        let level = heading.level();
+        let next = children[i + 1..]
+            .iter()
+            .position(|next| Heading::cast(next).is_some_and(|h| h.level() <= level))
+            .map_or(children.len(), |n| i + 1 + n);
+        let end = children[i..next]
+            .iter()
+            .rfind(|block| !block.kind().is_trivia())
+            .map_or(child.span().end, |block| block.span().end);
+        let span = child.span();
+        out.extend(range(
+            index.line(span.start) as u32,
+            last_line(index, span.start, end),
+            FoldingRangeKind::Region,
+        ));
+    }
+
+    blocks(root, index, &mut out);
+    out.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    out
+}
@@ -0,0 +1,87 @@
+//! hover previews of linked pages and tag usage
+
+use super::{Page, resolve_page};
+use crate::anchor::Anchors;
+use crate::ast::{self, Heading, Link, Tags};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::span::Span;
+
+/// the text of a paragraph with its line breaks joined
+fn paragraph_text(node: &Node) -> String {
+    node.text().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// the first paragraph of a page, or of the section below `anchor`
+pub fn first_paragraph(root: &Node, anchor: Option<&str>) -> Option<String> {
+    let blocks = root.children();
+    let start = match anchor {
+        Some(anchor) => {
+            let at = Anchors::new(root).resolve(anchor)?.span().start;
+            let block = blocks.iter().position(|block| block.span().end > at)?;
+            block + usize::from(Heading::cast(&blocks[block]).is_some())
+        }
+        None => 0,
+    };
+    blocks[start..]
+        .iter()
+        .take_while(|block| anchor.is_none() || block.kind() != SyntaxKind::Heading)
+        .find(|block| block.kind() == SyntaxKind::Paragraph)
+        .map(paragraph_text)
+}
+
+/// markdown previewing the page a link points to
+fn preview(link: &Link, current: &str, pages: &[Page]) -> String {
+    let name = resolve_page(current, &link.page());
+    let Some(page) = pages.iter().find(|page| page.name == name) else {
+        return format!("page `{name}` does not exist yet");
+    };
+    let anchor = link.anchor();
+    let mut parts = Vec::new();
+    match (ast::title(&page.root), &anchor) {
+        (Some(title), _) => parts.push(format!("**{title}**")),
+        (None, None) => parts.push(format!("**{name}**")),
+        (None, Some(_)) => {}
+    }
+    if let Some(anchor) = &anchor {
+        parts.push(format!("`#{anchor}`"));
+    }
+    if let Some(paragraph) = first_paragraph(&page.root, anchor.as_deref()) {
+        parts.push(paragraph);
+    }
+    parts.join("\n\n")
+}
+
+/// how many pages use `tag`
+pub fn tag_usage(tag: &str, pages: &[Page]) -> usize {
+    pages
+        .iter()
+        .filter(|page| {
+            ast::find_all(&page.root, SyntaxKind::Tags)
+                .into_iter()
+                .filter_map(Tags::cast)
+                .any(|tags| tags.names().iter().any(|name| name == tag))
+        })
+        .count()
+}
+
+/// hover contents as markdown and the span they describe, for byte `offset`
+pub fn hover(root: &Node, current: &str, offset: usize, pages: &[Page]) -> Option<(String, Span)> {
+    let chain = root.covering(offset);
+
+    if let Some(link) = chain.iter().rev().find_map(|n| Link::cast(n)) {
+        if link.scheme().is_some() {
+            return None;
+        }
+        return Some((preview(&link, current, pages), link.span()));
+    }
+
+    let tag = chain.iter().rev().find(|n| n.kind() == SyntaxKind::Tag)?;
+    let name = tag.text();
+    let count = tag_usage(&name, pages);
+    let pages = if count == 1 { "page" } else { "pages" };
+    Some((
+        format!("tag `:{name}:` is used on {count} {pages}"),
+        tag.span(),
+    ))
+}
@@ -0,0 +1,329 @@
+//! renaming of pages and headings, rewriting every link pointing at them
+
+use std::collections::BTreeMap;
+
+use tower_lsp::lsp_types::TextEdit;
+
+use super::{Page, relative_page, resolve_page};
+use crate::anchor::{Anchor, AnchorKind, Anchors};
+use crate::ast::{self, Heading, Link};
+use crate::kind::SyntaxKind;
+use crate::line_index::{Encoding, LineIndex};
+use crate::parser::{self, Node};
+use crate::span::Span;
+
+/// the thing under the cursor which is going to be renamed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// the page part of a link target, `page` is resolved against the wiki root
+    Page { page: String, span: Span },
+    /// a heading, or a `#anchor` of a link pointing at one
+    Heading {
+        page: String,
+        title: String,
+        span: Span,
+    },
+}
+
+impl Target {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Page { span, .. } | Self::Heading { span, .. } => *span,
+        }
+    }
+}
+
+/// finds the renameable target at byte `offset` of page `current`
+///
+/// only link targets and headings can be renamed.
+pub fn target_at(root: &Node, current: &str, offset: usize) -> Option<Target> {
+    let chain = root.covering(offset);
+
+    if let Some(link) = chain.iter().rev().find_map(|n| Link::cast(n)) {
+        if link.scheme().is_some() {
+            return None;
+        }
+        let target = link.target_node()?;
+        let span = target.span();
+        if offset < span.start || offset >= span.end {
+            return None;
+        }
+        let text = target.text();
+        let relative = offset - span.start;
+        let (page_text, anchors) = match text.find('#') {
+            Some(hash) => (&text[..hash], Some(hash)),
+            None => (text.as_str(), None),
+        };
+
+        return match anchors {
+            Some(hash) if relative > hash => {
+                // the `#` separated segment under the cursor
+                let mut start = hash + 1;
+                for segment in text[hash + 1..].split('#') {
+                    let end = start + segment.len();
+                    if relative <= end {
+                        let title = segment.trim();
+                        let leading = segment.len() - segment.trim_start().len();
+                        let begin = span.start + start + leading;
+                        return Some(Target::Heading {
+                            page: resolve_page(current, page_text),
+                            title: title.to_owned(),
+                            span: Span::new(begin, begin + title.len()),
+                        });
+                    }
+                    start = end + 1;
+                }
+                None
+            }
+            _ => {
+                let page = page_text.trim();
+                if page.is_empty() {
+                    return None;
+                }
+                let leading = page_text.len() - page_text.trim_start().len();
+                let begin = span.start + leading;
+                Some(Target::Page {
+                    page: resolve_page(current, page),
+                    span: Span::new(begin, begin + page.len()),
+                })
+            }
+        };
+    }
+
+    let heading = chain.iter().rev().find_map(|n| Heading::cast(n))?;
+    Some(Target::Heading {
+        page: current.to_owned(),
+        title: heading.title(),
+        span: heading.title_span()?,
+    })
+}
+
+/// edits produced by a rename
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Rename {
+    /// text edits keyed by page name, ranges refer to the page before the rename
+    pub edits: BTreeMap<String, Vec<TextEdit>>,
+    /// old and new page name when a page file has to be moved
+    pub moved: Option<(String, String)>,
+    /// the encoding of the columns of `edits`
+    pub encoding: Encoding,
+}
+
+impl Rename {
+    fn push(&mut self, page: &Page, index: &LineIndex, span: Span, new_text: String) {
+        let range = index.range(span, self.encoding);
+        self.edits
+            .entry(page.name.clone())
+            .or_default()
+            .push(TextEdit { range, new_text });
+    }
+}
+
+/// renames `target`, found in page `current`, to `new_name`
+///
+/// a new page name is written relative to `current`, just like the link it
+/// replaces. columns of the edits are counted in `encoding`.
+pub fn rename(
+    target: &Target,
+    current: &str,
+    new_name: &str,
+    pages: &[Page],
+    encoding: Encoding,
+) -> Result<Rename, String> {
+    let new_name = new_name.trim();
+    if new_name.is_empty() {
+        return Err("the new name must not be empty".to_owned());
+    }
+    if new_name.contains(['|', '[', ']', '#', '\n']) {
+        return Err(format!("`{new_name}` can not be used in a link"));
+    }
+    match target {
+        Target::Page { page, .. } => {
+            let new_page = resolve_page(current, new_name);
+            if pages.iter().any(|p| p.name == new_page) {
+                return Err(format!("the page `{new_page}` already exists"));
+            }
+            Ok(rename_page(page, &new_page, pages, encoding))
+        }
+        Target::Heading { page, .. } => {
+            let heading = renamed_heading(target, current, pages);
+            Ok(rename_heading(page, new_name, heading, pages, encoding))
+        }
+    }
+}
+
+fn links(root: &Node) -> impl Iterator<Item = Link<'_>> {
+    ast::find_all(root, SyntaxKind::Link)
+        .into_iter()
+        .filter_map(Link::cast)
+        .filter(|link| link.scheme().is_none())
+}
+
+/// everything of a link target from the first `#` on
+fn anchor_suffix(target: &str) -> &str {
+    target.find('#').map_or("", |hash| &target[hash..])
+}
+
+fn rename_page(old: &str, new: &str, pages: &[Page], encoding: Encoding) -> Rename {
+    let mut rename = Rename {
+        moved: Some((old.to_owned(), new.to_owned())),
+        encoding,
+        ..Default::default()
+    };
+    for page in pages {
+        let index = LineIndex::new(&page.root.text());
+        let is_moved = page.name == old;
+        for link in links(&page.root) {
+            let Some(target) = link.target_node() else {
+                continue;
+            };
+            let written = link.page();
+            if written.is_empty() {
+                continue;
+            }
+            let resolved = resolve_page(&page.name, &written);
+            let suffix = anchor_suffix(&link.target()).to_owned();
+            let from = if is_moved { new } else { page.name.as_str() };
+
+            let replacement = if resolved == old {
+                if written.starts_with('/') {
+                    format!("/{new}")
+                } else {
+                    relative_page(from, new)
+                }
+            } else if is_moved && !written.starts_with('/') {
+                // relative links of the moved page now start from its new directory
+                relative_page(from, &resolved)
+            } else {
+                continue;
+            };
+            if replacement != written {
+                rename.push(
+                    page,
+                    &index,
+                    target.span(),
+                    format!("{replacement}{suffix}"),
+                );
+            }
+        }
+    }
+    rename
+}
+
+/// the span of the heading `target` renames, the heading under the cursor or
+/// the one the anchors of the link under the cursor lead to
+fn renamed_heading(target: &Target, current: &str, pages: &[Page]) -> Option<Span> {
+    let Target::Heading { page, title, span } = target else {
+        return None;
+    };
+    let root = &pages.iter().find(|p| p.name == *page)?.root;
+    let anchors = Anchors::new(root);
+    let link = pages.iter().find(|p| p.name == current).and_then(|p| {
+        p.root
+            .covering(span.start)
+            .into_iter()
+            .rev()
+            .find_map(Link::cast)
+    });
+    let found = match link.as_ref().and_then(Link::target_node) {
+        Some(target) => {
+            // the anchors of the link up to the one under the cursor
+            let text = target.text();
+            let start = text.find('#').map(|hash| hash + 1);
+            let end = span.end.checked_sub(target.span().start);
+            start
+                .zip(end)
+                .and_then(|(start, end)| text.get(start..end))
+                .and_then(|path| anchors.resolve(path))
+        }
+        None if page == current => anchors
+            .headings()
+            .find(|anchor| anchor.span().start <= span.start && span.end <= anchor.span().end),
+        None => None,
+    };
+    found
+        .filter(|anchor| anchor.kind == AnchorKind::Heading)
+        .or_else(|| anchors.headings().find(|anchor| anchor.name == *title))
+        .map(Anchor::span)
+}
+
+fn rename_heading(
+    page_name: &str,
+    new: &str,
+    heading: Option<Span>,
+    pages: &[Page],
+    encoding: Encoding,
+) -> Rename {
+    let mut rename = Rename {
+        encoding,
+        ..Default::default()
+    };
+    let (Some(heading), Some(owner)) = (heading, pages.iter().find(|p| p.name == page_name)) else {
+        return rename;
+    };
+    let Some(title) = ast::find_all(&owner.root, SyntaxKind::Heading)
+        .into_iter()
+        .filter_map(Heading::cast)
+        .find(|h| h.span() == heading)
+        .and_then(|h| h.title_span())
+    else {
+        return rename;
+    };
+    let anchors = Anchors::new(&owner.root);
+    // the unique ids of the headings once the title is replaced, like
+    // `Setup-2` turning into `Setup` when the first `Setup` is renamed
+    let text = owner.root.text();
+    let renamed_root = parser::parse(&format!(
+        "{}{new}{}",
+        &text[..title.start],
+        &text[title.end..]
+    ));
+    let renamed_anchors = Anchors::new(&renamed_root);
+    let ids: Vec<(Span, &str)> = anchors
+        .headings()
+        .zip(renamed_anchors.headings())
+        .map(|(old, renamed)| (old.span(), renamed.id.as_str()))
+        .collect();
+    for page in pages {
+        let index = LineIndex::new(&page.root.text());
+        if page.name == page_name {
+            rename.push(page, &index, title, new.to_owned());
+        }
+        for link in links(&page.root) {
+            let Some(target) = link.target_node() else {
+                continue;
+            };
+            if resolve_page(&page.name, &link.page()) != page_name {
+                continue;
+            }
+            let written = target.text();
+            let Some(hash) = written.find('#') else {
+                continue;
+            };
+            // the renamed heading wherever its path leads to it, and the
+            // unique ids of headings whose id changes
+            let mut path = Vec::new();
+            let mut renamed = Vec::new();
+            for anchor in written[hash + 1..].split('#') {
+                path.push(anchor);
+                let found = anchors
+                    .resolve(&path.join("#"))
+                    .filter(|found| found.kind == AnchorKind::Heading);
+                let id = found.and_then(|found| {
+                    let (_, id) = ids.iter().find(|(span, _)| *span == found.span())?;
+                    Some((found, *id))
+                });
+                renamed.push(match id {
+                    Some((found, id)) if anchor.trim() == found.id => id,
+                    Some((found, _)) if found.span() == heading => new,
+                    _ => anchor,
+                });
+            }
+            let replacement = format!("{}#{}", &written[..hash], renamed.join("#"));
+            if replacement != written {
+                rename.push(page, &index, target.span(), replacement);
+            }
+        }
+    }
+    rename
+}
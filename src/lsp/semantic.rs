@@ -0,0 +1,204 @@
+//! semantic tokens, highlighting driven by the syntax tree instead of regexes
+
+use tower_lsp::lsp_types::{
+    Position, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend,
+};
+
+use super::Document;
+use crate::ast::{Checkbox, CheckboxState, Heading};
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::span::Span;
+
+/// token types, the index into this list is the type sent to the client
+pub const TOKEN_TYPES: [SemanticTokenType; 9] = [
+    SemanticTokenType::new("heading"),
+    SemanticTokenType::new("markup"),
+    SemanticTokenType::STRING,
+    SemanticTokenType::new("math"),
+    SemanticTokenType::new("link"),
+    SemanticTokenType::new("tag"),
+    SemanticTokenType::new("checkbox"),
+    SemanticTokenType::COMMENT,
+    SemanticTokenType::MACRO,
+];
+
+/// token modifiers, bit `n` of a token's modifiers stands for the `n`th entry,
+/// headings carry their level and checkboxes their state
+pub const TOKEN_MODIFIERS: [SemanticTokenModifier; 17] = [
+    SemanticTokenModifier::new("level1"),
+    SemanticTokenModifier::new("level2"),
+    SemanticTokenModifier::new("level3"),
+    SemanticTokenModifier::new("level4"),
+    SemanticTokenModifier::new("level5"),
+    SemanticTokenModifier::new("level6"),
+    SemanticTokenModifier::new("bold"),
+    SemanticTokenModifier::new("italic"),
+    SemanticTokenModifier::new("strikethrough"),
+    SemanticTokenModifier::new("superscript"),
+    SemanticTokenModifier::new("subscript"),
+    SemanticTokenModifier::new("open"),
+    SemanticTokenModifier::new("started"),
+    SemanticTokenModifier::new("half"),
+    SemanticTokenModifier::new("mostly"),
+    SemanticTokenModifier::new("done"),
+    SemanticTokenModifier::new("rejected"),
+];
+
+const HEADING: u32 = 0;
+const MARKUP: u32 = 1;
+const STRING: u32 = 2;
+const MATH: u32 = 3;
+const LINK: u32 = 4;
+const TAG: u32 = 5;
+const CHECKBOX: u32 = 6;
+const COMMENT: u32 = 7;
+const MACRO: u32 = 8;
+
+const BOLD: u32 = 1 << 6;
+const ITALIC: u32 = 1 << 7;
+const STRIKETHROUGH: u32 = 1 << 8;
+const SUPERSCRIPT: u32 = 1 << 9;
+const SUBSCRIPT: u32 = 1 << 10;
+const STATE: u32 = 11;
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// a highlighted byte range with its type and modifier bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Highlight {
+    pub span: Span,
+    pub kind: u32,
+    pub modifiers: u32,
+}
+
+fn state_bit(state: CheckboxState) -> u32 {
+    let index = match state {
+        CheckboxState::Open => 0,
+        CheckboxState::Started => 1,
+        CheckboxState::Half => 2,
+        CheckboxState::Mostly => 3,
+        CheckboxState::Done => 4,
+        CheckboxState::Rejected => 5,
+    };
+    1 << (STATE + index)
+}
+
+/// the highlight a node gives to everything inside of it
+fn classify(node: &Node, kind: Option<u32>, modifiers: u32) -> (Option<u32>, u32) {
+    let markup = |bit: u32| (kind.or(Some(MARKUP)), modifiers | bit);
+    match node.kind() {
+        SyntaxKind::Heading => {
+            let level = Heading::cast(node).map_or(1, |h| h.level().clamp(1, 6));
+            (Some(HEADING), modifiers | 1 << (level - 1))
+        }
+        SyntaxKind::Bold => markup(BOLD),
+        SyntaxKind::Italic => markup(ITALIC),
+        SyntaxKind::Strikethrough => markup(STRIKETHROUGH),
+        SyntaxKind::Superscript => markup(SUPERSCRIPT),
+        SyntaxKind::Subscript => markup(SUBSCRIPT),
+        SyntaxKind::Code | SyntaxKind::CodeBlock => (Some(STRING), modifiers),
+        SyntaxKind::Math | SyntaxKind::MathBlock => (Some(MATH), modifiers),
+        SyntaxKind::Link | SyntaxKind::Transclusion => (Some(LINK), modifiers),
+        SyntaxKind::Tags => (Some(TAG), modifiers),
+        SyntaxKind::Checkbox => {
+            let state = Checkbox::cast(node).map_or(CheckboxState::Open, |c| c.state());
+            (Some(CHECKBOX), modifiers | state_bit(state))
+        }
+        SyntaxKind::Comment => (Some(COMMENT), modifiers),
+        SyntaxKind::Placeholder => (Some(MACRO), modifiers),
+        _ => (kind, modifiers),
+    }
+}
+
+fn collect(node: &Node, kind: Option<u32>, modifiers: u32, out: &mut Vec<Highlight>) {
+    let (kind, modifiers) = classify(node, kind, modifiers);
+
+    if !node.is_leaf() {
+        for child in node.children() {
+            collect(child, kind, modifiers, out);
+        }
+        return;
+    }
+    let Some(kind) = kind else {
+        return;
+    };
+    let span = node.span();
+    if matches!(
+        node.kind(),
+        SyntaxKind::NewLine | SyntaxKind::IndentWhiteSpace
+    ) || span.start == span.end
+    {
+        return;
+    }
+    // neighbouring leaves with the same highlight become one token
+    if let Some(last) = out.last_mut()
+        && last.span.end == span.start
+        && last.kind == kind
+        && last.modifiers == modifiers
+    {
+        last.span.end = span.end;
+        return;
+    }
+    out.push(Highlight {
+        span,
+        kind,
+        modifiers,
+    });
+}
+
+/// highlights of the whole tree in document order, line breaks and indentation are left out
+pub fn highlights(root: &Node) -> Vec<Highlight> {
+    let mut out = Vec::new();
+    collect(root, None, 0, &mut out);
+    out
+}
+
+/// semantic tokens of a document, only those overlapping `range` if given
+///
+/// tokens spanning several lines are split at the line breaks since not
+/// every client supports multiline tokens.
+pub fn tokens(document: &Document, range: Option<Span>) -> Vec<SemanticToken> {
+    let index = &document.index;
+    let mut tokens = Vec::new();
+    let mut previous = Position::new(0, 0);
+    for highlight in highlights(&document.root) {
+        let span = highlight.span;
+        if let Some(range) = range
+            && (span.end <= range.start || span.start >= range.end)
+        {
+            continue;
+        }
+        for line in index.line(span.start)..=index.line(span.end) {
+            let Some(line_span) = index.line_span(line) else {
+                break;
+            };
+            let start = span.start.max(line_span.start);
+            let end = span.end.min(line_span.end);
+            if start >= end {
+                continue;
+            }
+            let columns = index.range(Span::new(start, end), document.encoding);
+            let delta_line = columns.start.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                columns.start.character - previous.character
+            } else {
+                columns.start.character
+            };
+            tokens.push(SemanticToken {
+                delta_line,
+                delta_start,
+                length: columns.end.character - columns.start.character,
+                token_type: highlight.kind,
+                token_modifiers_bitset: highlight.modifiers,
+            });
+            previous = columns.start;
+        }
+    }
+    tokens
+}
@@ -1,30 +1,861 @@
-#![allow(unused)]
+use std::io::{self, IsTerminal as _, Read as _};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Duration;
 
-use self::kind::SyntaxKind;
-use self::lexer::Token;
-use self::parser::Bold;
-use self::parser::{Parser, Repr};
-use neorg_syntax::*;
+use vimwiki_syntax::export::{self, Format, Html, Plain};
+use vimwiki_syntax::line_index::{Encoding, LineIndex};
+use vimwiki_syntax::workspace::{CONFIG_FILE, Workspace};
+use vimwiki_syntax::*;
 
-fn main() {
-    // let input = " /italic/ \n  /italc _under lined text_ \n next line \n* ** _this o_ _ ~this is in tilda~ -this is in hypens- -";
-    let input = include_str!("../examples/tests/italics.norg");
-    let lexed = lexer::Lexer::new(input.into()).lex();
-    let mut binding = parser::Parser::new(lexed.clone());
-    crate::ast::print_ast(input);
+const USAGE: &str = "\
+usage: vimwiki-syntax <command> [args]
+
+commands:
+  parse [--json] [file]        print the syntax tree
+  tokens [--json] [file]       print the tokens of the lexer
+  check [--fix] [path]         report syntax errors and lints of a file, or
+                               also broken links of a workspace, --fix
+                               fixes what it can first
+  fmt [--check] [file...]      format files in place, or stdin to stdout
+  export [--to format] [--standalone] [--toc] [-o out] [file]
+                               export a page to html, md, json, pandoc,
+                               text or ansi
+  cat [--plain] [--width n] [file]
+                               print a page as text, styled on a terminal
+  build [--css path] <wiki> <out>
+                               export every page of a wiki to a static site
+  serve [--port n] [--out dir] [--css path] [wiki]
+                               preview the site of a wiki, rebuilt and
+                               reloaded on every change
+  query [--json] <selector> [path]
+                               print the nodes matching a selector like
+                               `heading[level=2] > list checkbox[state=open]`
+                               in a file or workspace
+  lsp                          run the language server on stdio
+  graph [--json] [dir]         print the link graph
+  tags [dir] [query...]        list tags, or the pages matching a query
+  diary [dir]                  regenerate the diary index
+  tasks [dir] [--all] [--tag q] [--due date] [--page prefix] [--json]
+                               list the tasks of a workspace
+
+files default to stdin, directories to the current one.";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let rest = args.get(1..).unwrap_or_default();
+    match args.first().map(String::as_str) {
+        Some("parse") => parse(rest),
+        Some("tokens") => tokens(rest),
+        Some("check") => check(rest),
+        Some("fmt") => fmt(rest),
+        Some("export") => export(rest),
+        Some("cat") => cat(rest),
+        Some("build") => build(rest),
+        Some("serve") => serve(rest),
+        Some("query") => query(rest),
+        Some("lsp") => lsp(),
+        Some("graph") => graph(rest),
+        Some("tags") => tags(rest),
+        Some("tasks") => tasks(rest),
+        Some("diary") => diary(Path::new(rest.first().map_or(".", String::as_str))),
+        Some("help" | "-h" | "--help") => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// the contents of `path`, stdin for `None` and `-`
+fn read(path: Option<&str>) -> Option<String> {
+    let result = match path {
+        None | Some("-") => {
+            let mut source = String::new();
+            io::stdin().read_to_string(&mut source).map(|_| source)
+        }
+        Some(path) => std::fs::read_to_string(path),
+    };
+    result
+        .map_err(|err| eprintln!("error: could not read {}: {err}", path.unwrap_or("stdin")))
+        .ok()
+}
+
+/// whether `--json` can be used, reporting it if not
+fn json_available() -> bool {
+    if cfg!(not(feature = "serde")) {
+        eprintln!("error: --json needs the `serde` feature");
+        return false;
+    }
+    true
+}
+
+/// the file argument and whether `--json` was given
+fn json_flag(args: &[String]) -> Option<(Option<&str>, bool)> {
+    let json = args.iter().any(|arg| arg == "--json");
+    if json && !json_available() {
+        return None;
+    }
+    let file = args.iter().map(String::as_str).find(|arg| *arg != "--json");
+    Some((file, json))
+}
+
+/// prints the syntax tree of a file, as a schema document with `--json`
+fn parse(args: &[String]) -> ExitCode {
+    let Some((file, json)) = json_flag(args) else {
+        return ExitCode::from(2);
+    };
+    let Some(source) = read(file) else {
+        return ExitCode::from(2);
+    };
+    if json {
+        #[cfg(feature = "serde")]
+        println!("{}", schema::tree_to_json(&parser::parse(&source)));
+    } else {
+        ast::print_ast(&source);
+    }
+    ExitCode::SUCCESS
+}
+
+/// prints the tokens of a file one per line, or as a schema document with
+/// `--json`
+fn tokens(args: &[String]) -> ExitCode {
+    let Some((file, json)) = json_flag(args) else {
+        return ExitCode::from(2);
+    };
+    let Some(source) = read(file) else {
+        return ExitCode::from(2);
+    };
+    let tokens = lexer::Lexer::new(source.as_str().into()).lex();
+    if json {
+        #[cfg(feature = "serde")]
+        println!("{}", schema::tokens_to_json(&tokens));
+    } else {
+        for token in tokens {
+            println!("{token}");
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// prints the syntax errors of `source` and returns how many there are
+fn syntax_errors(name: &str, source: &str) -> usize {
+    let root = parser::parse(source);
+    let index = LineIndex::new(source);
+    let mut count = 0;
+    for error in root.error_nodes() {
+        let message = match (error.error(), error.hint()) {
+            (Some(error), Some(hint)) => format!("{error}, {hint}"),
+            (Some(error), None) => error.to_owned(),
+            (None, _) => continue,
+        };
+        let position = index.position(error.span().start, Encoding::Utf32);
+        println!(
+            "{name}:{}:{}: error: {message}",
+            position.line + 1,
+            position.character + 1
+        );
+        count += 1;
+    }
+    count
+}
+
+/// the lint config of the workspace config file in `dir`, if there is one
+fn lint_config(dir: &Path) -> Option<lint::LintConfig> {
+    lint::LintConfig::discover(dir)
+        .map_err(|err| eprintln!("error: {err}"))
+        .ok()
+}
+
+/// prints the lints of `source`, returns whether any of them is an error
+fn lints(name: &str, source: &str, config: &lint::LintConfig) -> bool {
+    let index = LineIndex::new(source);
+    let mut failed = false;
+    for lint in lint::lint(&parser::parse(source), config) {
+        let position = index.position(lint.span.start, Encoding::Utf32);
+        println!(
+            "{name}:{}:{}: {}: {} [{}]",
+            position.line + 1,
+            position.character + 1,
+            lint.severity,
+            lint.message,
+            lint.rule
+        );
+        failed |= lint.severity == lint::Severity::Error;
+    }
+    failed
+}
+
+/// fixes the syntax errors and lints of `file` in place, returns its new
+/// text or `None` if it could not be written
+///
+/// a file whose fixes would add syntax errors is left as it is.
+fn fix_file(file: &str, source: String, config: &lint::LintConfig) -> Option<String> {
+    let fixed = match fix::fix(&source, config) {
+        Ok(fixed) if fixed.text != source => fixed,
+        Ok(_) => return Some(source),
+        Err(err) => {
+            eprintln!("warning: not fixing {file}: {err}");
+            return Some(source);
+        }
+    };
+    if let Err(err) = std::fs::write(file, &fixed.text) {
+        eprintln!("error: could not write {file}: {err}");
+        return None;
+    }
+    let skipped = match fixed.skipped {
+        0 => String::new(),
+        n => format!(", {n} left for another run"),
+    };
+    let plural = if fixed.applied == 1 { "" } else { "es" };
+    eprintln!("{file}: applied {} fix{plural}{skipped}", fixed.applied);
+    Some(fixed.text)
+}
+
+/// checks a file for syntax errors and lints, or a workspace for syntax
+/// errors, lints and broken links, and fails if there are any errors
+///
+/// with `--fix` the problems which can be fixed are fixed in the files
+/// first. stdin is fixed to stdout without a report.
+fn check(args: &[String]) -> ExitCode {
+    let fix = args.iter().any(|arg| arg == "--fix");
+    let path = args
+        .iter()
+        .map(String::as_str)
+        .find(|arg| *arg != "--fix")
+        .unwrap_or(".");
+    let is_config = Path::new(path)
+        .file_name()
+        .is_some_and(|name| name == CONFIG_FILE);
+    if path == "-" || (Path::new(path).is_file() && !is_config) {
+        let Some(source) = read(Some(path)) else {
+            return ExitCode::from(2);
+        };
+        let name = if path == "-" { "<stdin>" } else { path };
+        let dir = Path::new(path).parent().filter(|_| path != "-");
+        let Some(config) = lint_config(dir.unwrap_or(Path::new("."))) else {
+            return ExitCode::from(2);
+        };
+        if fix && path == "-" {
+            return match fix::fix(&source, &config) {
+                Ok(fixed) => {
+                    print!("{}", fixed.text);
+                    ExitCode::SUCCESS
+                }
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    print!("{source}");
+                    ExitCode::FAILURE
+                }
+            };
+        }
+        let source = if fix {
+            let Some(source) = fix_file(path, source, &config) else {
+                return ExitCode::FAILURE;
+            };
+            source
+        } else {
+            source
+        };
+        let errors = syntax_errors(name, &source);
+        return if lints(name, &source, &config) || errors > 0 {
+            ExitCode::FAILURE
+        } else {
+            ExitCode::SUCCESS
+        };
+    }
+    let Some(workspace) = open_workspace(Path::new(path)) else {
+        return ExitCode::from(2);
+    };
+    let config = if is_config {
+        lint::LintConfig::load(Path::new(path))
+            .map_err(|err| eprintln!("error: {err}"))
+            .ok()
+    } else {
+        lint_config(Path::new(path))
+    };
+    let Some(config) = config else {
+        return ExitCode::from(2);
+    };
+    if fix {
+        for (wiki, pages) in workspace.load_pages().iter().enumerate() {
+            for page in pages {
+                let file = workspace.path(wiki, &page.name).unwrap_or_default();
+                let file = file.display().to_string();
+                if fix_file(&file, page.root.text(), &config).is_none() {
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+    }
+    let pages = workspace.load_pages();
+    let mut failed = false;
+    for (wiki, pages) in pages.iter().enumerate() {
+        for page in pages {
+            let file = workspace.path(wiki, &page.name).unwrap_or_default();
+            let (file, source) = (file.display().to_string(), page.root.text());
+            failed |= syntax_errors(&file, &source) > 0;
+            failed |= lints(&file, &source, &config);
+        }
+    }
+    let reports = check::check_pages(&workspace, &pages);
+    for report in &reports {
+        let file = workspace
+            .path(report.wiki, &report.page)
+            .unwrap_or_default();
+        let position = pages[report.wiki]
+            .iter()
+            .find(|page| page.name == report.page)
+            .map(|page| {
+                LineIndex::new(&page.root.text()).position(report.span.start, Encoding::Utf32)
+            })
+            .unwrap_or_default();
+        let severity = if report.problem.is_error() {
+            "error"
+        } else {
+            "warning"
+        };
+        println!(
+            "{}:{}:{}: {severity}: {}",
+            file.display(),
+            position.line + 1,
+            position.character + 1,
+            report.problem
+        );
+    }
+    if failed || reports.iter().any(|report| report.problem.is_error()) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// prints the nodes of a file or the pages of a workspace matching a
+/// selector, with their captures
+fn query(args: &[String]) -> ExitCode {
+    let json = args.iter().any(|arg| arg == "--json");
+    let mut args = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| *arg != "--json");
+    if json && !json_available() {
+        return ExitCode::from(2);
+    }
+    let Some(selector) = args.next() else {
+        eprintln!("error: query expects a selector");
+        return ExitCode::from(2);
+    };
+    let query = match query::Query::parse(selector) {
+        Ok(query) => query,
+        Err(err) => {
+            eprintln!("error: invalid selector: {err}");
+            return ExitCode::from(2);
+        }
+    };
+    let path = args.next();
+    let mut files = Vec::new();
+    match path {
+        None | Some("-") => {
+            let Some(source) = read(path) else {
+                return ExitCode::from(2);
+            };
+            files.push(("<stdin>".to_owned(), source));
+        }
+        Some(path) if Path::new(path).is_file() => {
+            let Some(source) = read(Some(path)) else {
+                return ExitCode::from(2);
+            };
+            files.push((path.to_owned(), source));
+        }
+        Some(path) => {
+            let Some(workspace) = open_workspace(Path::new(path)) else {
+                return ExitCode::from(2);
+            };
+            for (wiki, pages) in workspace.load_pages().iter().enumerate() {
+                for page in pages {
+                    let file = workspace.path(wiki, &page.name).unwrap_or_default();
+                    files.push((file.display().to_string(), page.root.text()));
+                }
+            }
+        }
+    }
+    #[cfg(feature = "serde")]
+    let mut found = Vec::new();
+    for (file, source) in &files {
+        let root = parser::parse(source);
+        let index = LineIndex::new(source);
+        let position = |node: &parser::Node| {
+            let position = index.position(node.span().start, Encoding::Utf32);
+            (position.line + 1, position.character + 1)
+        };
+        for matched in query.select(&root) {
+            let (line, column) = position(matched.node);
+            let text = matched.node.text();
+            if json {
+                #[cfg(feature = "serde")]
+                let captures: serde_json::Map<_, _> = matched
+                    .captures
+                    .iter()
+                    .map(|(name, node)| {
+                        let span = node.span();
+                        let value = serde_json::json!({
+                            "kind": node.kind().to_string(),
+                            "span": [span.start, span.end],
+                            "text": node.text().trim(),
+                        });
+                        (name.clone(), value)
+                    })
+                    .collect();
+                #[cfg(feature = "serde")]
+                found.push(serde_json::json!({
+                    "file": file,
+                    "kind": matched.node.kind().to_string(),
+                    "span": [matched.span().start, matched.span().end],
+                    "line": line,
+                    "column": column,
+                    "text": text.trim(),
+                    "captures": captures,
+                }));
+                continue;
+            }
+            println!(
+                "{file}:{line}:{column}: {} {:?}",
+                matched.node.kind(),
+                text.trim()
+            );
+            for (name, node) in &matched.captures {
+                let (line, column) = position(node);
+                println!(
+                    "  @{name} {line}:{column}: {} {:?}",
+                    node.kind(),
+                    node.text().trim()
+                );
+            }
+        }
+    }
+    #[cfg(feature = "serde")]
+    if json {
+        println!("{}", serde_json::Value::Array(found));
+    }
+    ExitCode::SUCCESS
+}
+
+/// the workspace in `path`, a directory or a config file
+fn open_workspace(path: &Path) -> Option<Workspace> {
+    let workspace = if path.is_file() {
+        Workspace::load(path)
+    } else {
+        Workspace::discover(path)
+    };
+    workspace.map_err(|err| eprintln!("error: {err}")).ok()
+}
+
+/// prints the link graph of a workspace as DOT, or as JSON with `--json`
+fn graph(args: &[String]) -> ExitCode {
+    let json = args.iter().any(|arg| arg == "--json");
+    let path = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or(".", String::as_str);
+    if json && !json_available() {
+        return ExitCode::from(2);
+    }
+    let Some(workspace) = open_workspace(Path::new(path)) else {
+        return ExitCode::from(2);
+    };
+    let graph = graph::Graph::build(&workspace, &workspace.load_pages());
+    if json {
+        #[cfg(feature = "serde")]
+        println!("{}", graph.to_json());
+    } else {
+        print!("{}", graph.to_dot());
+    }
+    ExitCode::SUCCESS
+}
+
+/// rebuilds the tag cache of a workspace and prints the pages matching a
+/// query like `work & urgent | home`, or every tag without one
+fn tags(args: &[String]) -> ExitCode {
+    let (path, query) = match args {
+        [] => (".", None),
+        [path] => (path.as_str(), None),
+        [path, query @ ..] => (path.as_str(), Some(query.join(" "))),
+    };
+    let Some(workspace) = open_workspace(Path::new(path)) else {
+        return ExitCode::from(2);
+    };
+    #[cfg(feature = "serde")]
+    let cache = {
+        let (cache, errors) = tags::TagIndex::load_caches(&workspace);
+        for err in errors {
+            eprintln!("warning: {err}, rebuilding it");
+        }
+        Some(cache)
+    };
+    #[cfg(not(feature = "serde"))]
+    let cache = None;
+    let index = tags::TagIndex::rebuild(&workspace, cache.as_ref());
+    #[cfg(feature = "serde")]
+    if let Err(err) = index.save_caches(&workspace) {
+        eprintln!("warning: {err}");
+    }
+    match query {
+        Some(query) => {
+            for page in index.query(&tags::TagQuery::parse(&query)) {
+                println!("{}", page.id());
+            }
+        }
+        None => {
+            for tag in index.tags() {
+                println!("{tag}");
+            }
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+/// regenerates the diary index of the first wiki of a workspace
+fn diary(path: &Path) -> ExitCode {
+    let Some(workspace) = open_workspace(path) else {
+        return ExitCode::from(2);
+    };
+    let Some(config) = workspace.wiki(0) else {
+        return ExitCode::from(2);
+    };
+    let diary = diary::Diary::load(config);
+    let index = config.path(diary.index());
+    let source = std::fs::read_to_string(&index).unwrap_or_default();
+    if let Some(dir) = index.parent()
+        && let Err(err) = std::fs::create_dir_all(dir)
+            .and_then(|()| std::fs::write(&index, diary.update_index(&source)))
+    {
+        eprintln!("error: could not write {}: {err}", index.display());
+        return ExitCode::FAILURE;
+    }
+    println!("{}", index.display());
+    ExitCode::SUCCESS
+}
+
+/// prints the open tasks of a workspace, every task with `--all`
+///
+/// `--tag QUERY`, `--due YYYY-MM-DD` and `--page PREFIX` narrow them down,
+/// `--json` prints a JSON array.
+fn tasks(args: &[String]) -> ExitCode {
+    let mut filter = tasks::TaskFilter {
+        open: true,
+        ..Default::default()
+    };
+    let (mut path, mut json) = (".", false);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => filter.open = false,
+            "--json" => json = true,
+            "--tag" => filter.tags = args.next().map(|q| tags::TagQuery::parse(q)),
+            "--page" => filter.page = args.next().cloned(),
+            "--due" => match args.next().and_then(|d| diary::Date::parse(d)) {
+                Some(due) => filter.due = Some(due),
+                None => {
+                    eprintln!("error: --due expects a date like 2026-10-20");
+                    return ExitCode::from(2);
+                }
+            },
+            other => path = other,
+        }
+    }
+    if json && !json_available() {
+        return ExitCode::from(2);
+    }
+    let Some(workspace) = open_workspace(Path::new(path)) else {
+        return ExitCode::from(2);
+    };
+    let all = tasks::collect(&workspace.load_pages());
+    let found = filter.apply(&all);
+    if json {
+        #[cfg(feature = "serde")]
+        println!("{}", tasks::to_json(&found));
+        return ExitCode::SUCCESS;
+    }
+    for task in found {
+        let file = workspace
+            .path(task.page.wiki, &task.page.page)
+            .unwrap_or_default();
+        let due = task
+            .due
+            .map(|due| format!(" (due {due})"))
+            .unwrap_or_default();
+        println!(
+            "{}:{}: [{}] {}{due}",
+            file.display(),
+            task.line,
+            task.state.marker(),
+            task.text
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+/// formats files in place, or stdin to stdout
+///
+/// with `--check` nothing is written, the unformatted files are listed and
+/// the command fails if there are any.
+fn fmt(args: &[String]) -> ExitCode {
+    let check = args.iter().any(|arg| arg == "--check");
+    let files: Vec<_> = args.iter().filter(|arg| *arg != "--check").collect();
+    let stdin = files.iter().any(|file| *file == "-");
+    if stdin && files.len() > 1 {
+        eprintln!("error: fmt reads either stdin or files, `-` can not be mixed with files");
+        return ExitCode::from(2);
+    }
+    if files.is_empty() || stdin {
+        let Some(source) = read(None) else {
+            return ExitCode::from(2);
+        };
+        if check {
+            return if fmt::is_formatted(&source) {
+                ExitCode::SUCCESS
+            } else {
+                println!("<stdin>");
+                ExitCode::FAILURE
+            };
+        }
+        print!("{}", fmt::format(&source));
+        return ExitCode::SUCCESS;
+    }
+    let mut unformatted = false;
+    for file in files {
+        let Some(source) = read(Some(file)) else {
+            return ExitCode::from(2);
+        };
+        let formatted = fmt::format(&source);
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{file}");
+            unformatted = true;
+        } else if let Err(err) = std::fs::write(file, formatted) {
+            eprintln!("error: could not write {file}: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+    if unformatted {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// prints a page as text, styled with ansi escapes when stdout is a
+/// terminal and wrapped at `$COLUMNS`
+fn cat(args: &[String]) -> ExitCode {
+    let mut plain = !io::stdout().is_terminal();
+    let mut width = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(export::plain::DEFAULT_WIDTH);
+    let mut input = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--plain" => plain = true,
+            "--width" => match args.next().and_then(|n| n.parse().ok()) {
+                Some(n) => width = n,
+                None => {
+                    eprintln!("error: --width expects a number");
+                    return ExitCode::from(2);
+                }
+            },
+            other => input = Some(other),
+        }
+    }
+    let Some(source) = read(input) else {
+        return ExitCode::from(2);
+    };
+    let text = Plain::new()
+        .width(Some(width))
+        .ansi(!plain)
+        .render(&parser::parse(&source));
+    print!("{text}");
+    ExitCode::SUCCESS
 }
 
-fn print_lexed(l: Vec<Token>) {
-    let parsed = l;
+/// exports a page to html, markdown, json, pandoc's json AST or text
+fn export(args: &[String]) -> ExitCode {
+    let (mut format, mut standalone, mut toc) = (Format::Html, false, false);
+    let (mut input, mut output) = (None, None);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--to" => match args.next().map(|to| to.parse()) {
+                Some(Ok(to)) => format = to,
+                Some(Err(err)) => {
+                    eprintln!("error: {err}");
+                    return ExitCode::from(2);
+                }
+                None => {
+                    eprintln!("error: --to expects html, md, json, pandoc, text or ansi");
+                    return ExitCode::from(2);
+                }
+            },
+            "--standalone" => standalone = true,
+            "--toc" => toc = true,
+            "-o" | "--output" => output = args.next(),
+            other => input = Some(other),
+        }
+    }
+    let Some(source) = read(input) else {
+        return ExitCode::from(2);
+    };
+    let root = parser::parse(&source);
+    let exported = match format {
+        Format::Html => Html::new()
+            .standalone(standalone)
+            .toc(toc.then_some(6))
+            .render(&root),
+        format => export::export(&root, format),
+    };
+    match output {
+        Some(output) => {
+            if let Err(err) = std::fs::write(output, exported) {
+                eprintln!("error: could not write {output}: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+        None => print!("{exported}"),
+    }
+    ExitCode::SUCCESS
+}
+
+/// exports the wiki at `<wiki>` to a static site in `<out>`, only pages and
+/// files which changed since the last build are written
+fn build(args: &[String]) -> ExitCode {
+    let mut css = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--css" => css = args.next(),
+            other => paths.push(other),
+        }
+    }
+    let [root, out] = paths[..] else {
+        eprintln!("error: build expects a wiki and an output directory");
+        return ExitCode::from(2);
+    };
+    let Some(workspace) = open_workspace(Path::new(root)) else {
+        return ExitCode::from(2);
+    };
+    let site = site::Site::new(&workspace, 0, out);
+    let site = match css {
+        Some(css) => site.css(css),
+        None => site,
+    };
+    match site.build() {
+        Ok(report) => {
+            for name in report.written.iter().chain(&report.copied) {
+                println!("{name}");
+            }
+            for name in &report.removed {
+                println!("removed {name}");
+            }
+            eprintln!(
+                "{} written, {} copied, {} unchanged, {} removed",
+                report.written.len(),
+                report.copied.len(),
+                report.unchanged.len(),
+                report.removed.len()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    for i in parsed {
-        println!("{}", i)
+/// serves the site of a wiki on the loopback interface, rebuilding it and
+/// reloading the pages whenever the wiki changes
+fn serve(args: &[String]) -> ExitCode {
+    let (mut port, mut out, mut css, mut root) = (8080, None, None, ".");
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => match args.next().and_then(|port| port.parse().ok()) {
+                Some(number) => port = number,
+                None => {
+                    eprintln!("error: --port expects a number");
+                    return ExitCode::from(2);
+                }
+            },
+            "--out" => out = args.next().map(PathBuf::from),
+            "--css" => css = args.next(),
+            other => root = other,
+        }
+    }
+    let Some(workspace) = open_workspace(Path::new(root)) else {
+        return ExitCode::from(2);
+    };
+    let out = out.unwrap_or_else(|| {
+        std::env::temp_dir().join(format!("vimwiki-serve-{}", std::process::id()))
+    });
+    let site = site::Site::new(&workspace, 0, out);
+    let site = match css {
+        Some(css) => site.css(css),
+        None => site,
+    };
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let result = serve::serve(
+        &site,
+        addr,
+        Duration::from_millis(250),
+        |event| match event {
+            serve::Event::Ready(addr) => eprintln!("serving on http://{addr}/"),
+            serve::Event::Built(report) => {
+                for name in report.written.iter().chain(&report.copied) {
+                    eprintln!("built {name}");
+                }
+                for name in &report.removed {
+                    eprintln!("removed {name}");
+                }
+            }
+            serve::Event::Failed(err) => eprintln!("error: {err}"),
+        },
+    );
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
     }
 }
-fn print_parsed(mut p: Parser) {
-    let parsed = p.parse();
 
-    for i in parsed {
-        println!("{}", i)
+/// runs the language server on stdin and stdout
+#[cfg(feature = "lsp")]
+fn lsp() -> ExitCode {
+    match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => {
+            runtime.block_on(lsp::serve_stdio());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: could not start the runtime: {err}");
+            ExitCode::FAILURE
+        }
     }
 }
+
+#[cfg(not(feature = "lsp"))]
+fn lsp() -> ExitCode {
+    eprintln!("error: the language server needs the `lsp` feature");
+    ExitCode::from(2)
+}
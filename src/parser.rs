@@ -1,11 +1,20 @@
 use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
 use ecow::EcoString;
 
 use crate::kind::SyntaxKind;
-use crate::lexer::Token;
+use crate::lexer::{Lexer, Token};
 use crate::span::Span;
 
+/// lex and parse `source` into a `Root` node
+pub fn parse(source: &str) -> Node {
+    let tokens = Lexer::new(source.into()).lex();
+    Parser::new(tokens).parse_root()
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Parser {
     pub start: usize,
@@ -22,37 +31,140 @@ pub struct Node(Repr);
 pub struct Bold(pub SyntaxNode);
 
 impl Node {
+    /// a leaf node holding the text of a single token
+    pub fn leaf(kind: SyntaxKind, text: impl Into<EcoString>, span: Span) -> Self {
+        Self(Repr::SyntaxNode(SyntaxNode::new(kind, text.into(), span)))
+    }
+
+    /// an inner node, its span covers all of its children
+    pub fn inner(kind: SyntaxKind, children: Vec<Node>) -> Self {
+        Self(Repr::InnerNode(InnerNode::new(kind, children)))
+    }
+
+    /// an inner node without children, with an empty span at `offset`
+    pub fn empty(kind: SyntaxKind, offset: usize) -> Self {
+        Self(Repr::InnerNode(InnerNode {
+            span: Span::new(offset, offset),
+            ..InnerNode::new(kind, Vec::new())
+        }))
+    }
+
     pub fn type_is(&self) -> &str {
-        match self {
-            Self(Repr::SyntaxNode(_)) => "SyntaxNode",
-            Self(Repr::ErrorNode(_)) => "ErrorNode",
-        }
+        self.0.type_is()
     }
     pub fn text(&self) -> String {
         match self {
             Self(Repr::SyntaxNode(text)) => text.text.to_string(),
             Self(Repr::ErrorNode(err)) => err.text.to_string(),
+            Self(Repr::InnerNode(inner)) => inner.base.iter().map(Node::text).collect(),
         }
     }
     pub fn kind(&self) -> SyntaxKind {
-        match self {
-            Self(Repr::SyntaxNode(text)) => text.kind,
-            Self(Repr::ErrorNode(err)) => err.kind,
-        }
+        *self.0.kind()
     }
     // returns the span of syntax node
     pub fn span(&self) -> Span {
         match self {
             Self(Repr::SyntaxNode(text)) => text.span,
             Self(Repr::ErrorNode(err)) => err.span,
+            Self(Repr::InnerNode(inner)) => inner.span,
         }
     }
 
     pub fn errors(&self) -> Option<String> {
         match self {
-            Self(Repr::SyntaxNode(_)) => None,
             Self(Repr::ErrorNode(err)) => err.error.clone(),
+            _ => None,
+        }
+    }
+
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            Self(Repr::ErrorNode(err)) => err.hint.clone(),
+            _ => None,
+        }
+    }
+
+    /// children of an inner node, empty for leaves and errors
+    pub fn children(&self) -> &[Node] {
+        match self {
+            Self(Repr::InnerNode(inner)) => inner.children(),
+            _ => &[],
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        !matches!(self, Self(Repr::InnerNode(_)))
+    }
+
+    pub fn repr(&self) -> &Repr {
+        &self.0
+    }
+
+    /// all error nodes in this subtree, in document order
+    pub fn error_nodes(&self) -> Vec<&ErrorNode> {
+        match self {
+            Self(Repr::ErrorNode(err)) => vec![err],
+            Self(Repr::SyntaxNode(_)) => Vec::new(),
+            Self(Repr::InnerNode(inner)) => inner
+                .children()
+                .iter()
+                .flat_map(Node::error_nodes)
+                .collect(),
+        }
+    }
+
+    /// a copy of this subtree with every span moved by `delta` bytes
+    ///
+    /// the children of inner nodes are shared and only moved when they are
+    /// looked at, so this takes constant time
+    pub fn shifted(&self, delta: isize) -> Node {
+        let shift = |span: Span| {
+            Span::new(
+                span.start.saturating_add_signed(delta),
+                span.end.saturating_add_signed(delta),
+            )
+        };
+        match self {
+            Self(Repr::SyntaxNode(leaf)) => {
+                Node::leaf(leaf.kind, leaf.text.clone(), shift(leaf.span))
+            }
+            Self(Repr::ErrorNode(err)) => Self(Repr::ErrorNode(ErrorNode {
+                span: shift(err.span),
+                ..err.clone()
+            })),
+            Self(Repr::InnerNode(inner)) => Self(Repr::InnerNode(InnerNode {
+                kind: inner.kind,
+                span: shift(inner.span),
+                base: Arc::clone(&inner.base),
+                delta: inner.delta + delta,
+                children: OnceLock::new(),
+            })),
+        }
+    }
+
+    /// the chain of nodes from `self` down to the innermost node containing
+    /// the byte `offset`, empty if `self` does not contain it
+    pub fn covering(&self, offset: usize) -> Vec<&Node> {
+        let span = self.span();
+        if offset < span.start || offset >= span.end {
+            return Vec::new();
+        }
+        let mut chain = vec![self];
+        if let Some(child) = self
+            .children()
+            .iter()
+            .find(|c| c.span().start <= offset && offset < c.span().end)
+        {
+            chain.extend(child.covering(offset));
         }
+        chain
+    }
+}
+
+impl From<ErrorNode> for Node {
+    fn from(err: ErrorNode) -> Self {
+        Self(Repr::ErrorNode(err))
     }
 }
 
@@ -61,12 +173,14 @@ impl Repr {
         match self {
             Self::SyntaxNode(syn) => &syn.kind,
             Self::ErrorNode(err) => &err.kind,
+            Self::InnerNode(inner) => &inner.kind,
         }
     }
     pub fn type_is(&self) -> &str {
         match self {
             Self::SyntaxNode(_) => "SyntaxNode",
             Self::ErrorNode(_) => "ErrorNode",
+            Self::InnerNode(_) => "InnerNode",
         }
     }
 }
@@ -81,26 +195,22 @@ impl Display for Node {
 pub enum Repr {
     ErrorNode(ErrorNode),
     SyntaxNode(SyntaxNode),
+    InnerNode(InnerNode),
 }
 
 impl Display for Repr {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}: {:?} {}",
-            match self {
-                Self::SyntaxNode(syn) => syn.kind,
-                Self::ErrorNode(err) => err.kind,
-            },
-            match self {
-                Self::ErrorNode(err) => err.text.clone(),
-                Self::SyntaxNode(syn) => syn.text.clone(),
-            },
-            match self {
-                Self::SyntaxNode(_) => "".to_owned(),
-                Self::ErrorNode(err) => err.error.clone().unwrap_or_default(),
-            }
-        )
+        match self {
+            Self::SyntaxNode(syn) => write!(f, "{}: {:?} ", syn.kind, syn.text),
+            Self::ErrorNode(err) => write!(
+                f,
+                "{}: {:?} {}",
+                err.kind,
+                err.text,
+                err.error.clone().unwrap_or_default()
+            ),
+            Self::InnerNode(inner) => write!(f, "{}: {}", inner.kind, inner.span),
+        }
     }
 }
 
@@ -110,12 +220,69 @@ impl Display for ErrorNode {
     }
 }
 
+/// what the parser recovered from, which says how to fix it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// whitespace before the closing marker of an inline element
+    TrailingWhitespace,
+    /// an inline element which is not closed on its line
+    UnclosedDelimiter,
+    UnclosedLink,
+    /// a code block which is not closed before the end of the page
+    UnclosedCodeBlock,
+    /// a math block which is not closed before the end of the page
+    UnclosedMathBlock,
+}
+
+impl ErrorCode {
+    pub const ALL: [Self; 5] = [
+        Self::TrailingWhitespace,
+        Self::UnclosedDelimiter,
+        Self::UnclosedLink,
+        Self::UnclosedCodeBlock,
+        Self::UnclosedMathBlock,
+    ];
+
+    /// the id used as the code of diagnostics and in the json schema
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::TrailingWhitespace => "marker-whitespace",
+            Self::UnclosedDelimiter => "unclosed-delimiter",
+            Self::UnclosedLink => "unclosed-link",
+            Self::UnclosedCodeBlock => "unclosed-code-block",
+            Self::UnclosedMathBlock => "unclosed-math-block",
+        }
+    }
+
+    /// the error message of the node
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::TrailingWhitespace => "Trailing WhiteSpace",
+            Self::UnclosedDelimiter => "Unclosed delimeter",
+            Self::UnclosedLink => "Unclosed link",
+            Self::UnclosedCodeBlock | Self::UnclosedMathBlock => "Unclosed code block",
+        }
+    }
+}
+
+impl FromStr for ErrorCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|code| code.id() == s)
+            .ok_or_else(|| format!("unknown error code `{s}`"))
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ErrorNode {
     kind: SyntaxKind,
     pub text: EcoString,
     error: Option<String>,
     hint: Option<String>,
+    code: Option<ErrorCode>,
     span: Span,
 }
 
@@ -132,9 +299,36 @@ impl ErrorNode {
             text,
             error,
             hint,
+            code: None,
             span,
         }
     }
+
+    /// the node with the code of the error
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        Self {
+            code: Some(code),
+            ..self
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        self.hint.as_deref()
+    }
+
+    /// the code of the error, `None` for nodes which were not built by the
+    /// parser
+    pub fn code(&self) -> Option<ErrorCode> {
+        self.code
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -155,6 +349,92 @@ impl std::fmt::Display for SyntaxNode {
         write!(f, "{}: {:?} {}", self.kind, self.text, self.span)
     }
 }
+
+/// a node with children, like a heading or a link
+///
+/// the children are shared between copies of the node. a shifted copy keeps
+/// the children of the original in `base` together with how far they moved,
+/// and builds the moved children the first time they are asked for
+#[derive(Clone)]
+pub struct InnerNode {
+    kind: SyntaxKind,
+    span: Span,
+    base: Arc<[Node]>,
+    delta: isize,
+    children: OnceLock<Arc<[Node]>>,
+}
+
+impl InnerNode {
+    pub fn new(kind: SyntaxKind, children: Vec<Node>) -> Self {
+        let span = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => Span::new(first.span().start, last.span().end),
+            _ => Span::new(0, 0),
+        };
+        Self {
+            kind,
+            span,
+            base: children.into(),
+            delta: 0,
+            children: OnceLock::new(),
+        }
+    }
+
+    pub fn children(&self) -> &[Node] {
+        if self.delta == 0 {
+            return &self.base;
+        }
+        self.children
+            .get_or_init(|| self.base.iter().map(|c| c.shifted(self.delta)).collect())
+    }
+}
+
+impl std::fmt::Debug for InnerNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InnerNode")
+            .field("kind", &self.kind)
+            .field("span", &self.span)
+            .field("children", &self.children())
+            .finish()
+    }
+}
+
+impl PartialEq for InnerNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.span == other.span
+            && (self.delta == other.delta && Arc::ptr_eq(&self.base, &other.base)
+                || self.children() == other.children())
+    }
+}
+
+impl Eq for InnerNode {}
+
+impl Hash for InnerNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.kind.hash(state);
+        self.span.hash(state);
+        self.children().hash(state);
+    }
+}
+
+/// what kind of block a line starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Block {
+    Blank,
+    Heading(usize),
+    List,
+    Table,
+    Code,
+    Math,
+    Comment,
+    MultilineComment,
+    Placeholder,
+    HorizontalRule,
+    Paragraph,
+}
+
+const PLACEHOLDERS: [&str; 4] = ["title", "date", "template", "nohtml"];
+
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
         Self {
@@ -197,127 +477,824 @@ impl Parser {
         }
     }
 
+    /// parses the top level blocks of the document
     pub fn parse(&mut self) -> Vec<Node> {
         // Use a while let loop instead of checking self.current directly
         while let Some(token) = self.peek() {
             if token.kind == SyntaxKind::Eof {
                 break;
             }
-            let node = Node(self.scan());
-            self.nodes.push(node);
+            let nodes = self.block();
+            self.nodes.extend(nodes);
         }
         std::mem::take(&mut self.nodes)
     }
 
-    fn delimeter_by(&mut self,i: Token, pat: SyntaxKind, expr: Expr) -> LeafNode {
-           if let Some(next_token) = self.peek() {
-                            if next_token.kind == SyntaxKind::Text {
-                                text.push_str(&next_token.text);
-                                self.advance();
-                                if next_token.text.ends_with(' ') {
-                                    // if the text ends with WhiteSpace its not vaild
-                                    if let Some(closing_token) = self.peek() {
-                                        if closing_token.kind == pat {
-                                            self.advance();
-                            return LeafNode::new(i.text, Span::new(i.span.start, i.span.end), Some("Trailing WhiteSpace".to_owned()), None);
-                                            
-                                        }
-                                    }
-                                }
-                                if let Some(closing_token) = self.peek() {
-                                    if closing_token.kind == pat {
-                                        self.advance();
-                                        return Repr::SyntaxNode(SyntaxNode::new(
-                                            pat,
-                                            text.clone(),
-                                            Span::new(start, closing_token.span.end),
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-
-                        Repr::ErrorNode(ErrorNode {
-                            kind: SyntaxKind::Error,
-                            text,
-                            error,
-                            hint,
-                            span: i.span,
-                        });
-        LeafNode {
-            text: "".into(),
-            span: self.tokens[self.current].span,
-            error: None,
-            hints: None,
-        }
+    /// parses the whole document into a single `Root` node
+    pub fn parse_root(&mut self) -> Node {
+        let children = self.parse();
+        Node::inner(SyntaxKind::Root, children)
     }
 
+    /// errors collected while parsing
+    pub fn errors(&self) -> &[ErrorNode] {
+        &self.errors
+    }
+
+    /// parses a single inline element up to the end of the current line
     pub fn scan(&mut self) -> Repr {
         self.start = self.current;
+        let end = self.line_end(self.current);
+        self.inline_node(end).0
+    }
+
+    #[inline]
+    fn kind_at(&self, index: usize) -> SyntaxKind {
+        self.tokens
+            .get(index)
+            .map(|t| t.kind)
+            .unwrap_or(SyntaxKind::Eof)
+    }
+
+    #[inline]
+    fn text_at(&self, index: usize) -> &str {
+        self.tokens
+            .get(index)
+            .map(|t| t.text.as_str())
+            .unwrap_or("")
+    }
+
+    fn starts_with(&self, index: usize, kinds: &[SyntaxKind]) -> bool {
+        kinds
+            .iter()
+            .enumerate()
+            .all(|(offset, kind)| self.kind_at(index + offset) == *kind)
+    }
+
+    // -- lines --
+
+    /// index one past the end of the line starting at `from`, including its newline
+    fn line_end(&self, from: usize) -> usize {
+        let mut index = from;
+        while let Some(token) = self.tokens.get(index) {
+            match token.kind {
+                SyntaxKind::NewLine => return index + 1,
+                SyntaxKind::Eof => return index,
+                _ => index += 1,
+            }
+        }
+        index
+    }
+
+    /// index of the first token of the line which is not whitespace
+    fn line_start(&self, from: usize) -> usize {
+        let mut index = from;
+        while matches!(
+            self.kind_at(index),
+            SyntaxKind::WhiteSpace | SyntaxKind::IndentWhiteSpace
+        ) {
+            index += 1;
+        }
+        index
+    }
+
+    /// index one past the last token of the line which is not whitespace or a newline
+    fn content_end(&self, from: usize, end: usize) -> usize {
+        let mut index = end;
+        while index > from && self.kind_at(index - 1).is_trivia() {
+            index -= 1;
+        }
+        index
+    }
+
+    /// width of the leading whitespace of the line starting at `from`
+    fn indent_of(&self, from: usize) -> usize {
+        match self.kind_at(from) {
+            SyntaxKind::IndentWhiteSpace | SyntaxKind::WhiteSpace => {
+                self.text_at(from).chars().count()
+            }
+            _ => 0,
+        }
+    }
+
+    fn block_kind(&self, from: usize) -> Block {
+        let first = self.line_start(from);
+        let end = self.line_end(from);
+        let last = self.content_end(first, end);
+        match self.kind_at(first) {
+            SyntaxKind::NewLine | SyntaxKind::Eof => Block::Blank,
+            SyntaxKind::Percentage if self.kind_at(first + 1) == SyntaxKind::Percentage => {
+                if self.text_at(first + 2).starts_with('+') {
+                    Block::MultilineComment
+                } else {
+                    Block::Comment
+                }
+            }
+            SyntaxKind::Percentage
+                if first == from
+                    && self.kind_at(first + 1) == SyntaxKind::Text
+                    && PLACEHOLDERS.iter().any(|name| {
+                        let text = self.text_at(first + 1);
+                        text.strip_prefix(name)
+                            .is_some_and(|rest| rest.is_empty() || rest.starts_with([' ', '\t']))
+                    }) =>
+            {
+                Block::Placeholder
+            }
+            SyntaxKind::LeftCurlyBraces
+                if self.starts_with(
+                    first,
+                    &[
+                        SyntaxKind::LeftCurlyBraces,
+                        SyntaxKind::LeftCurlyBraces,
+                        SyntaxKind::LeftCurlyBraces,
+                    ],
+                ) =>
+            {
+                Block::Code
+            }
+            SyntaxKind::LeftCurlyBraces
+                if self.starts_with(
+                    first,
+                    &[
+                        SyntaxKind::LeftCurlyBraces,
+                        SyntaxKind::LeftCurlyBraces,
+                        SyntaxKind::Dollar,
+                    ],
+                ) =>
+            {
+                Block::Math
+            }
+            SyntaxKind::Equal => match self.heading_level(first, last) {
+                Some(level) => Block::Heading(level),
+                None => Block::Paragraph,
+            },
+            SyntaxKind::Pipe => Block::Table,
+            SyntaxKind::Hyphen
+                if last - first >= 4
+                    && (first..last).all(|i| self.kind_at(i) == SyntaxKind::Hyphen) =>
+            {
+                Block::HorizontalRule
+            }
+            _ if self.list_marker_len(first).is_some() => Block::List,
+            _ => Block::Paragraph,
+        }
+    }
+
+    /// number of `=` if the tokens from `first` to `last` form a heading
+    fn heading_level(&self, first: usize, last: usize) -> Option<usize> {
+        let open = (first..last)
+            .take_while(|&i| self.kind_at(i) == SyntaxKind::Equal)
+            .count();
+        let close = (first..last)
+            .rev()
+            .take_while(|&i| self.kind_at(i) == SyntaxKind::Equal)
+            .count();
+        let has_title = (first + open..last - close).any(|i| !self.kind_at(i).is_trivia());
+        (open == close && (1..=6).contains(&open) && first + open < last - close && has_title)
+            .then_some(open)
+    }
+
+    /// byte length of the list marker starting at `index`, if there is one
+    fn list_marker_len(&self, index: usize) -> Option<usize> {
+        let followed_by_space = matches!(
+            self.kind_at(index + 1),
+            SyntaxKind::WhiteSpace | SyntaxKind::NewLine | SyntaxKind::Eof
+        );
+        match self.kind_at(index) {
+            SyntaxKind::Hyphen | SyntaxKind::Astrisk | SyntaxKind::HashTag if followed_by_space => {
+                Some(1)
+            }
+            SyntaxKind::Text => {
+                let text = self.text_at(index);
+                let len = ordered_marker_len(text)?;
+                match text[len..].chars().next() {
+                    Some(' ' | '\t') => Some(len),
+                    None if followed_by_space => Some(len),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
 
-        let text = EcoString::new();
-
-        match self.advance() {
-            Some(i) => {
-                let start = i.span.start;
-                let mut parse_delimeted_expr =
-                    |pat: SyntaxKind,
-                     kind: Expr,
-                     mut text: EcoString,
-                     error: Option<String>,
-                     hint: Option<String>| {
-                        if let Some(next_token) = self.peek() {
-                            if next_token.kind == SyntaxKind::Text {
-                                text.push_str(&next_token.text);
-                                self.advance();
-                                if next_token.text.ends_with(' ') {
-                                    // if the text ends with WhiteSpace its not vaild
-                                    if let Some(closing_token) = self.peek() {
-                                        if closing_token.kind == pat {
-                                            self.advance();
-                                            return Repr::ErrorNode(ErrorNode {
-                                                kind: SyntaxKind::Error,
-                                                text,
-                                                error: Some("Trailing WhiteSpace".to_owned()),
-                                                hint,
-                                                span: Span::new(start, i.span.end),
-                                            });
-                                        }
-                                    }
-                                }
-                                if let Some(closing_token) = self.peek() {
-                                    if closing_token.kind == pat {
-                                        self.advance();
-                                        return Repr::SyntaxNode(SyntaxNode::new(
-                                            kind,
-                                            text.clone(),
-                                            Span::new(start, closing_token.span.end),
-                                        ));
-                                    }
-                                }
-                            }
-                        }
-
-                        Repr::ErrorNode(ErrorNode {
-                            kind: SyntaxKind::Error,
-                            text,
-                            error,
-                            hint,
-                            span: i.span,
-                        })
-                    };
-                match i.kind {
-                    SyntaxKind::Underscore => parse_delimeted_expr(),
-                    _ => Repr::SyntaxNode(SyntaxNode::new(i.kind, i.text.into(), i.span)),
+    /// items with a different style of marker start a new list
+    fn marker_style(&self, from: usize) -> Option<(char, char)> {
+        let first = self.line_start(from);
+        let len = self.list_marker_len(first)?;
+        let marker = self.text_at(first).get(..len)?;
+        let mut chars = marker.chars();
+        let head = chars.next()?;
+        let tail = chars.last().unwrap_or(head);
+        let class = match head {
+            '0'..='9' => '1',
+            'a'..='z' => 'a',
+            'A'..='Z' => 'A',
+            c => c,
+        };
+        Some((class, tail))
+    }
+
+    /// splits the token at `index` after `at` bytes, the first half gets `kind`
+    fn split_token(&mut self, index: usize, at: usize, kind: SyntaxKind) {
+        let Some(token) = self.tokens.get(index).cloned() else {
+            return;
+        };
+        if at == 0 || at >= token.text.len() {
+            if let Some(token) = self.tokens.get_mut(index) {
+                token.kind = kind;
+            }
+            return;
+        }
+        let (head, tail) = token.text.split_at(at);
+        let tail_kind = if tail.trim_start_matches([' ', '\t']).is_empty() {
+            SyntaxKind::WhiteSpace
+        } else {
+            token.kind
+        };
+        let split = token.span.start + at;
+        self.tokens[index] = Token {
+            kind,
+            text: head.to_owned(),
+            span: Span::new(token.span.start, split),
+        };
+        self.tokens.insert(
+            index + 1,
+            Token {
+                kind: tail_kind,
+                text: tail.to_owned(),
+                span: Span::new(split, token.span.end),
+            },
+        );
+    }
+
+    // -- node construction --
+
+    /// consumes tokens up to `end` as leaf nodes
+    fn leaves(&mut self, end: usize) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while self.current < end {
+            match self.advance() {
+                Some(token) => nodes.push(Node::leaf(token.kind, token.text, token.span)),
+                None => break,
+            }
+        }
+        nodes
+    }
+
+    fn error(&mut self, end: usize, code: ErrorCode, hint: Option<String>) -> Node {
+        let leaves = self.leaves(end);
+        let text: String = leaves.iter().map(Node::text).collect();
+        let span = match (leaves.first(), leaves.last()) {
+            (Some(first), Some(last)) => Span::new(first.span().start, last.span().end),
+            _ => {
+                let at = self
+                    .tokens
+                    .get(self.current)
+                    .map(|t| t.span.start)
+                    .unwrap_or(0);
+                Span::new(at, at)
+            }
+        };
+        let err = ErrorNode::new(
+            SyntaxKind::Error,
+            text.into(),
+            Some(code.message().to_owned()),
+            hint,
+            span,
+        )
+        .with_code(code);
+        self.errors.push(err.clone());
+        err.into()
+    }
+
+    // -- blocks --
+
+    fn block(&mut self) -> Vec<Node> {
+        let end = self.line_end(self.current);
+        match self.block_kind(self.current) {
+            Block::Blank => self.leaves(end),
+            Block::Heading(level) => vec![self.heading(level)],
+            Block::List => {
+                let indent = self.indent_of(self.current);
+                vec![self.list(indent)]
+            }
+            Block::Table => vec![self.table()],
+            Block::Code => vec![self.code_block(
+                SyntaxKind::CodeBlock,
+                &[
+                    SyntaxKind::RightCurlyBraces,
+                    SyntaxKind::RightCurlyBraces,
+                    SyntaxKind::RightCurlyBraces,
+                ],
+                "}}}",
+            )],
+            Block::Math => vec![self.code_block(
+                SyntaxKind::MathBlock,
+                &[
+                    SyntaxKind::RightCurlyBraces,
+                    SyntaxKind::RightCurlyBraces,
+                    SyntaxKind::Dollar,
+                ],
+                "}}$",
+            )],
+            Block::Comment => vec![Node::inner(SyntaxKind::Comment, self.leaves(end))],
+            Block::MultilineComment => vec![self.multiline_comment()],
+            Block::Placeholder => vec![Node::inner(SyntaxKind::Placeholder, self.leaves(end))],
+            Block::HorizontalRule => {
+                vec![Node::inner(SyntaxKind::HorizontalRule, self.leaves(end))]
+            }
+            Block::Paragraph => vec![self.paragraph()],
+        }
+    }
+
+    fn heading(&mut self, level: usize) -> Node {
+        let end = self.line_end(self.current);
+        let first = self.line_start(self.current);
+        let last = self.content_end(first, end);
+        let mut children = self.leaves(first + level);
+        children.extend(self.inline(last - level));
+        children.extend(self.leaves(end));
+        Node::inner(SyntaxKind::Heading, children)
+    }
+
+    fn paragraph(&mut self) -> Node {
+        let mut children = Vec::new();
+        loop {
+            let end = self.line_end(self.current);
+            children.extend(self.inline(end));
+            if self.kind_at(self.current) == SyntaxKind::Eof
+                || self.block_kind(self.current) != Block::Paragraph
+            {
+                break;
+            }
+        }
+        Node::inner(SyntaxKind::Paragraph, children)
+    }
+
+    fn list(&mut self, indent: usize) -> Node {
+        let style = self.marker_style(self.current);
+        let mut items = Vec::new();
+        while self.kind_at(self.current) != SyntaxKind::Eof
+            && self.block_kind(self.current) == Block::List
+            && self.indent_of(self.current) == indent
+            && self.marker_style(self.current) == style
+        {
+            items.push(self.list_item(indent));
+        }
+        Node::inner(SyntaxKind::List, items)
+    }
+
+    fn list_item(&mut self, indent: usize) -> Node {
+        let first = self.line_start(self.current);
+        let mut children = self.leaves(first);
+
+        if let Some(len) = self.list_marker_len(self.current) {
+            self.split_token(self.current, len, SyntaxKind::ListMarker);
+            let rest = self.text_at(self.current + 1);
+            let ws_len = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+            if ws_len > 0 {
+                self.split_token(self.current + 1, ws_len, SyntaxKind::WhiteSpace);
+            }
+        }
+        children.extend(self.leaves(self.current + 1));
+        if self.kind_at(self.current) == SyntaxKind::WhiteSpace {
+            children.extend(self.leaves(self.current + 1));
+        }
+        if let Some(checkbox) = self.checkbox() {
+            children.push(checkbox);
+        }
+
+        let end = self.line_end(self.current);
+        children.extend(self.inline(end));
+
+        while self.kind_at(self.current) != SyntaxKind::Eof {
+            let inner_indent = self.indent_of(self.current);
+            match self.block_kind(self.current) {
+                Block::List if inner_indent > indent => {
+                    children.push(self.list(inner_indent));
+                }
+                Block::Paragraph if inner_indent > indent => {
+                    let end = self.line_end(self.current);
+                    children.extend(self.inline(end));
+                }
+                _ => break,
+            }
+        }
+        Node::inner(SyntaxKind::ListItem, children)
+    }
+
+    fn checkbox(&mut self) -> Option<Node> {
+        let at = self.current;
+        let is_state = match self.kind_at(at + 1) {
+            SyntaxKind::WhiteSpace => self.text_at(at + 1) == " ",
+            SyntaxKind::Hyphen => true,
+            SyntaxKind::Text => matches!(self.text_at(at + 1), "." | "o" | "O" | "X"),
+            _ => false,
+        };
+        let is_checkbox = self.kind_at(at) == SyntaxKind::LeftSqBrackets
+            && is_state
+            && self.kind_at(at + 2) == SyntaxKind::RightSqBrackets
+            && matches!(
+                self.kind_at(at + 3),
+                SyntaxKind::WhiteSpace | SyntaxKind::NewLine | SyntaxKind::Eof
+            );
+        if !is_checkbox {
+            return None;
+        }
+        let checkbox = Node::inner(SyntaxKind::Checkbox, self.leaves(at + 3));
+        Some(checkbox)
+    }
+
+    fn table(&mut self) -> Node {
+        let mut rows = Vec::new();
+        while self.kind_at(self.current) != SyntaxKind::Eof
+            && self.block_kind(self.current) == Block::Table
+        {
+            rows.push(self.table_row());
+        }
+        Node::inner(SyntaxKind::Table, rows)
+    }
+
+    fn table_row(&mut self) -> Node {
+        let end = self.line_end(self.current);
+        let first = self.line_start(self.current);
+        let last = self.content_end(first, end);
+        let mut children = self.leaves(first);
+        while self.current < last {
+            if self.kind_at(self.current) == SyntaxKind::Pipe {
+                children.extend(self.leaves(self.current + 1));
+                continue;
+            }
+            // a `|` inside of a link separates its description, not a cell
+            let mut cell_end = self.current;
+            let mut in_link = false;
+            while cell_end < last {
+                match self.kind_at(cell_end) {
+                    SyntaxKind::Pipe if !in_link => break,
+                    SyntaxKind::LeftSqBrackets
+                        if self.kind_at(cell_end + 1) == SyntaxKind::LeftSqBrackets =>
+                    {
+                        in_link = true;
+                    }
+                    SyntaxKind::RightSqBrackets
+                        if self.kind_at(cell_end + 1) == SyntaxKind::RightSqBrackets =>
+                    {
+                        in_link = false;
+                    }
+                    _ => {}
+                }
+                cell_end += 1;
+            }
+            let cell = self.inline(cell_end);
+            children.push(Node::inner(SyntaxKind::TableCell, cell));
+        }
+        children.extend(self.leaves(end));
+        Node::inner(SyntaxKind::TableRow, children)
+    }
+
+    fn code_block(&mut self, kind: SyntaxKind, closing: &[SyntaxKind], close: &str) -> Node {
+        let end = self.line_end(self.current);
+        let mut children = self.leaves(end);
+        loop {
+            if self.kind_at(self.current) == SyntaxKind::Eof {
+                let hint = format!("close it with `{close}`");
+                let code = match kind {
+                    SyntaxKind::MathBlock => ErrorCode::UnclosedMathBlock,
+                    _ => ErrorCode::UnclosedCodeBlock,
+                };
+                children.push(self.error(self.current, code, Some(hint)));
+                break;
+            }
+            let is_closing = self.starts_with(self.line_start(self.current), closing);
+            let end = self.line_end(self.current);
+            children.extend(self.leaves(end));
+            if is_closing {
+                break;
+            }
+        }
+        Node::inner(kind, children)
+    }
+
+    fn multiline_comment(&mut self) -> Node {
+        let closing = (self.current + 3..self.tokens.len()).find(|&i| {
+            self.kind_at(i) == SyntaxKind::Text
+                && self.text_at(i).ends_with('+')
+                && self.starts_with(i + 1, &[SyntaxKind::Percentage, SyntaxKind::Percentage])
+        });
+        let end = match closing {
+            Some(i) => self.line_end(i),
+            None => self.tokens.len().saturating_sub(1),
+        };
+        Node::inner(SyntaxKind::Comment, self.leaves(end))
+    }
+
+    // -- inlines --
+
+    /// parses inline elements up to `end`
+    fn inline(&mut self, end: usize) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        while self.current < end && self.kind_at(self.current) != SyntaxKind::Eof {
+            nodes.push(self.inline_node(end));
+        }
+        nodes
+    }
+
+    fn inline_node(&mut self, end: usize) -> Node {
+        let at = self.current;
+        // inline elements never span multiple lines
+        let limit = (at..end)
+            .find(|&i| self.kind_at(i) == SyntaxKind::NewLine)
+            .unwrap_or(end);
+        match self.kind_at(at) {
+            SyntaxKind::LeftSqBrackets if self.kind_at(at + 1) == SyntaxKind::LeftSqBrackets => {
+                self.link(limit)
+            }
+            SyntaxKind::LeftCurlyBraces if self.kind_at(at + 1) == SyntaxKind::LeftCurlyBraces => {
+                self.transclusion(limit)
+            }
+            SyntaxKind::SemiColon if self.is_space_before(at) => match self.tags(limit) {
+                Some(tags) => tags,
+                None => self.leaf(),
+            },
+            SyntaxKind::Astrisk => {
+                self.parse_delimeted_expr(&[SyntaxKind::Astrisk], SyntaxKind::Bold, limit, true)
+            }
+            SyntaxKind::Underscore => self.parse_delimeted_expr(
+                &[SyntaxKind::Underscore],
+                SyntaxKind::Italic,
+                limit,
+                true,
+            ),
+            SyntaxKind::Tilda if self.kind_at(at + 1) == SyntaxKind::Tilda => self
+                .parse_delimeted_expr(
+                    &[SyntaxKind::Tilda, SyntaxKind::Tilda],
+                    SyntaxKind::Strikethrough,
+                    limit,
+                    true,
+                ),
+            SyntaxKind::SuperScriptMarker => self.parse_delimeted_expr(
+                &[SyntaxKind::SuperScriptMarker],
+                SyntaxKind::Superscript,
+                limit,
+                false,
+            ),
+            SyntaxKind::SubScriptMarker if self.kind_at(at + 1) == SyntaxKind::SubScriptMarker => {
+                self.parse_delimeted_expr(
+                    &[SyntaxKind::SubScriptMarker, SyntaxKind::SubScriptMarker],
+                    SyntaxKind::Subscript,
+                    limit,
+                    false,
+                )
+            }
+            SyntaxKind::CodeMarker => self.raw(SyntaxKind::CodeMarker, SyntaxKind::Code, limit),
+            SyntaxKind::Dollar => self.raw(SyntaxKind::Dollar, SyntaxKind::Math, limit),
+            _ => self.leaf(),
+        }
+    }
+
+    fn leaf(&mut self) -> Node {
+        let mut leaves = self.leaves(self.current + 1);
+        leaves
+            .pop()
+            .unwrap_or_else(|| Node::leaf(SyntaxKind::Eof, "", Span::new(0, 0)))
+    }
+
+    /// whether the token before `index` is whitespace, a newline or punctuation
+    fn is_boundary_before(&self, index: usize) -> bool {
+        let Some(prev) = index.checked_sub(1).and_then(|i| self.tokens.get(i)) else {
+            return true;
+        };
+        match prev.kind {
+            SyntaxKind::Text => prev
+                .text
+                .chars()
+                .last()
+                .is_none_or(|c| !c.is_alphanumeric()),
+            _ => true,
+        }
+    }
+
+    /// whether the token before `index` is whitespace or a newline
+    fn is_space_before(&self, index: usize) -> bool {
+        let Some(prev) = index.checked_sub(1).and_then(|i| self.tokens.get(i)) else {
+            return true;
+        };
+        match prev.kind {
+            SyntaxKind::Text => prev.text.ends_with([' ', '\t']),
+            kind => kind.is_trivia(),
+        }
+    }
+
+    /// whether the token at `index` is whitespace, a newline or punctuation
+    fn is_boundary_after(&self, index: usize, limit: usize) -> bool {
+        if index >= limit {
+            return true;
+        }
+        match self.kind_at(index) {
+            SyntaxKind::Text => self
+                .text_at(index)
+                .chars()
+                .next()
+                .is_none_or(|c| !c.is_alphanumeric()),
+            _ => true,
+        }
+    }
+
+    fn ends_with_whitespace(&self, index: usize) -> bool {
+        match self.kind_at(index) {
+            SyntaxKind::Text => self.text_at(index).ends_with([' ', '\t']),
+            kind => kind.is_trivia(),
+        }
+    }
+
+    /// `*bold*`, `_italic_`, `~~strikethrough~~`, `^superscript^` and `,,subscript,,`
+    ///
+    /// strict delimiters must be surrounded by whitespace or punctuation and
+    /// report unclosed or whitespace padded content as errors.
+    fn parse_delimeted_expr(
+        &mut self,
+        delimiter: &[SyntaxKind],
+        kind: SyntaxKind,
+        limit: usize,
+        strict: bool,
+    ) -> Node {
+        let open = self.current;
+        let content = open + delimiter.len();
+        let marker: String = (open..content).map(|i| self.text_at(i)).collect();
+
+        let is_opening = content < limit
+            && !self.kind_at(content).is_trivia()
+            && !self.starts_with(content, delimiter);
+        // `(-, *, 1.)` is punctuation in a sentence, not markup
+        let starts_content = matches!(
+            self.kind_at(content),
+            SyntaxKind::Text
+                | SyntaxKind::Astrisk
+                | SyntaxKind::Underscore
+                | SyntaxKind::Tilda
+                | SyntaxKind::CodeMarker
+                | SyntaxKind::Dollar
+                | SyntaxKind::LeftSqBrackets
+                | SyntaxKind::LeftCurlyBraces
+        );
+        if !is_opening || (strict && (!self.is_boundary_before(open) || !starts_content)) {
+            return self.leaf();
+        }
+
+        let Some(close) = (content + 1..limit).find(|&i| self.starts_with(i, delimiter)) else {
+            if strict {
+                let hint = format!("close it with `{marker}`");
+                return self.error(content, ErrorCode::UnclosedDelimiter, Some(hint));
+            }
+            return self.leaf();
+        };
+        let after = close + delimiter.len();
+
+        if self.ends_with_whitespace(close - 1) {
+            // if the text ends with WhiteSpace its not vaild
+            if strict {
+                let hint = format!("remove the whitespace before the closing `{marker}`");
+                return self.error(after, ErrorCode::TrailingWhitespace, Some(hint));
+            }
+            return self.leaf();
+        }
+        if strict && !self.is_boundary_after(after, limit) {
+            return self.leaf();
+        }
+
+        let mut children = self.leaves(content);
+        children.extend(self.inline(close));
+        children.extend(self.leaves(after));
+        Node::inner(kind, children)
+    }
+
+    /// `` `code` `` and `$math$`, whose content is not parsed any further
+    fn raw(&mut self, delimiter: SyntaxKind, kind: SyntaxKind, limit: usize) -> Node {
+        let open = self.current;
+        let close = (open + 1..limit).find(|&i| self.kind_at(i) == delimiter);
+        match close {
+            Some(close) if close > open + 1 => {
+                if delimiter == SyntaxKind::Dollar
+                    && (self.kind_at(open + 1).is_trivia() || self.ends_with_whitespace(close - 1))
+                {
+                    return self.leaf();
                 }
+                Node::inner(kind, self.leaves(close + 1))
             }
-            _ => Repr::SyntaxNode(SyntaxNode {
-                kind: SyntaxKind::Eof,
-                text: "".into(),
-                span: Span::default(),
-            }),
+            None if delimiter == SyntaxKind::CodeMarker => self.error(
+                open + 1,
+                ErrorCode::UnclosedDelimiter,
+                Some("close it with `` ` ``".to_owned()),
+            ),
+            _ => self.leaf(),
         }
     }
+
+    /// `[[target]]`, `[[target|description]]`
+    fn link(&mut self, limit: usize) -> Node {
+        let open = self.current;
+        let close = (open + 2..limit.saturating_sub(1)).find(|&i| {
+            self.starts_with(
+                i,
+                &[SyntaxKind::RightSqBrackets, SyntaxKind::RightSqBrackets],
+            )
+        });
+        let Some(close) = close else {
+            return self.error(
+                open + 2,
+                ErrorCode::UnclosedLink,
+                Some("close it with `]]`".to_owned()),
+            );
+        };
+        let pipe = (open + 2..close).find(|&i| self.kind_at(i) == SyntaxKind::Pipe);
+
+        let mut children = self.leaves(open + 2);
+        let target_end = pipe.unwrap_or(close);
+        if target_end > self.current {
+            let target = self.leaves(target_end);
+            children.push(Node::inner(SyntaxKind::LinkTarget, target));
+        }
+        if let Some(pipe) = pipe {
+            children.extend(self.leaves(pipe + 1));
+            if close > self.current {
+                let description = self.inline(close);
+                children.push(Node::inner(SyntaxKind::LinkDescription, description));
+            }
+        }
+        children.extend(self.leaves(close + 2));
+        Node::inner(SyntaxKind::Link, children)
+    }
+
+    /// `{{image.png}}`, `{{image.png|description}}`
+    fn transclusion(&mut self, limit: usize) -> Node {
+        let open = self.current;
+        let close = (open + 2..limit.saturating_sub(1)).find(|&i| {
+            self.starts_with(
+                i,
+                &[SyntaxKind::RightCurlyBraces, SyntaxKind::RightCurlyBraces],
+            )
+        });
+        match close {
+            Some(close) => Node::inner(SyntaxKind::Transclusion, self.leaves(close + 2)),
+            None => self.leaf(),
+        }
+    }
+
+    /// `:tag1:tag2:`
+    fn tags(&mut self, limit: usize) -> Option<Node> {
+        let mut colon = self.current;
+        let mut names = Vec::new();
+        loop {
+            let name_start = colon + 1;
+            let mut index = name_start;
+            let mut valid = true;
+            while index < limit && self.kind_at(index) != SyntaxKind::SemiColon {
+                let kind = self.kind_at(index);
+                if kind.is_trivia() || self.text_at(index).contains([' ', '\t']) {
+                    valid = false;
+                    break;
+                }
+                index += 1;
+            }
+            if !valid || index >= limit || index == name_start {
+                break;
+            }
+            names.push(name_start..index);
+            colon = index;
+        }
+        if names.is_empty() || !self.is_boundary_after(colon + 1, limit) {
+            return None;
+        }
+        let mut children = Vec::new();
+        for name in names {
+            children.extend(self.leaves(name.start));
+            let tag = self.leaves(name.end);
+            children.push(Node::inner(SyntaxKind::Tag, tag));
+        }
+        children.extend(self.leaves(colon + 1));
+        Some(Node::inner(SyntaxKind::Tags, children))
+    }
+}
+
+/// byte length of an ordered list marker like `1.`, `1)`, `a)` or `iv)`
+fn ordered_marker_len(text: &str) -> Option<usize> {
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        return match text[digits..].chars().next() {
+            Some('.' | ')') => Some(digits + 1),
+            _ => None,
+        };
+    }
+    let letters = text.chars().take_while(char::is_ascii_alphabetic).count();
+    let word = &text[..letters];
+    let is_roman =
+        word.chars().all(|c| "ivxlcdm".contains(c)) || word.chars().all(|c| "IVXLCDM".contains(c));
+    if (letters == 1 || (letters > 1 && is_roman)) && text[letters..].starts_with(')') {
+        Some(letters + 1)
+    } else {
+        None
+    }
 }
 
 impl Iterator for Parser {
@@ -334,25 +1311,6 @@ impl Iterator for Parser {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub enum Expr {
-    Bold(LeafNode),
-    Italics(LeafNode),
-    Error(LeafNode),
-}
-
-impl Expr {
-    fn errors(&self) -> Vec<String> {
-        match self {
-            Expr::Bold(leaf_node) => vec![format!("Unclosed delimeter {}", leaf_node.span.start)],
-            Expr::Italics(leaf_node) => {
-                vec![format!("Unclosed delimeter {}", leaf_node.span.start)]
-            }
-            Expr::Error(leaf_node) => vec![format!("Unclosed delimeter {}", leaf_node.span.start)],
-        }
-    }
-}
-
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LeafNode {
     text: EcoString,
@@ -370,4 +1328,20 @@ impl LeafNode {
             hints,
         }
     }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn hints(&self) -> Option<&str> {
+        self.hints.as_deref()
+    }
 }
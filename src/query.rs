@@ -0,0 +1,476 @@
+//! selecting nodes of a syntax tree with css-like selectors
+//!
+//! `heading[level=2] > list checkbox[state=open]` selects the open
+//! checkboxes of lists right below a second level heading. a selector is a
+//! chain of node kinds in snake case, like `list_item`, or `*` for any kind,
+//! joined by a space for any descendant or `>` for a direct child. several
+//! selectors are separated by commas.
+//!
+//! headings own their section: the blocks after a heading up to the next
+//! heading of the same or a higher level are its children, so `heading
+//! link` selects the links in the sections of headings.
+//!
+//! attributes are matched with `[name]`, `[name=value]`, `[name!=value]`,
+//! `[name^=prefix]` and `[name*=part]`, values may be quoted. every node has
+//! a `text`, other attributes depend on the kind:
+//!
+//! - `heading`: `level`, `title` and the `tag`s of its section
+//! - `link`: `target`, `page`, `anchor`, `description` and `scheme`
+//! - `transclusion`: `src` and `alt`
+//! - `list`: `ordered`, `true` or `false`
+//! - `list_item` and `checkbox`: the `state` of the checkbox, like `open`
+//!   or `done`
+//! - `tags` and `tag`: the tag `name`s
+//! - `code_block`: `lang`
+//! - `placeholder`: `name` and `value`
+//!
+//! `@name` after a kind captures the node it matched, so `heading @h link`
+//! returns every link with the heading it is under.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::ast::{self, CheckboxState, Heading, Link, ListItem, Placeholder, Tags};
+use crate::export;
+use crate::kind::SyntaxKind;
+use crate::parser::Node;
+use crate::span::Span;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message} at column {}", offset + 1)]
+pub struct QueryError {
+    /// byte offset into the query
+    pub offset: usize,
+    pub message: String,
+}
+
+/// a parsed query, one or more selectors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    selectors: Vec<Selector>,
+}
+
+/// compound selectors from the outermost to the selected node
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Selector {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    /// how this step relates to the one before, ignored for the first
+    combinator: Combinator,
+    /// `None` for `*`
+    kind: Option<SyntaxKind>,
+    attributes: Vec<Attribute>,
+    capture: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Attribute {
+    name: String,
+    test: Test,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Test {
+    Exists,
+    Equals(String),
+    NotEquals(String),
+    Prefix(String),
+    Contains(String),
+}
+
+/// a selected node and the nodes captured on the way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match<'a> {
+    pub node: &'a Node,
+    /// captures by name, from the outermost
+    pub captures: Vec<(String, &'a Node)>,
+}
+
+impl<'a> Match<'a> {
+    pub fn span(&self) -> Span {
+        self.node.span()
+    }
+
+    /// the node captured as `name`
+    pub fn capture(&self, name: &str) -> Option<&'a Node> {
+        self.captures
+            .iter()
+            .find(|(capture, _)| capture == name)
+            .map(|(_, node)| *node)
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let mut parser = QueryParser { query, offset: 0 };
+        let mut selectors = vec![parser.selector()?];
+        while parser.eat(',') {
+            selectors.push(parser.selector()?);
+        }
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek() {
+            return Err(parser.error(format!("unexpected `{c}`")));
+        }
+        Ok(Self { selectors })
+    }
+
+    /// the nodes below `root` matching any of the selectors, in document
+    /// order
+    pub fn select<'a>(&self, root: &'a Node) -> Vec<Match<'a>> {
+        let tree = Tree::new(root);
+        let mut matches = Vec::new();
+        for index in 0..tree.nodes.len() {
+            for selector in &self.selectors {
+                let mut captures = Vec::new();
+                let last = selector.steps.len() - 1;
+                if tree.matches(&selector.steps, last, index, &mut captures) {
+                    captures.reverse();
+                    matches.push(Match {
+                        node: tree.nodes[index],
+                        captures,
+                    });
+                    break;
+                }
+            }
+        }
+        matches
+    }
+}
+
+struct QueryParser<'q> {
+    query: &'q str,
+    offset: usize,
+}
+
+impl QueryParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.query[self.offset..].chars().next()
+    }
+
+    fn error(&self, message: impl Into<String>) -> QueryError {
+        QueryError {
+            offset: self.offset,
+            message: message.into(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.offset;
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.offset += c.len_utf8();
+        }
+        self.offset > start
+    }
+
+    /// skips whitespace and `c`, if it is next
+    fn eat(&mut self, c: char) -> bool {
+        let start = self.offset;
+        self.skip_whitespace();
+        if self.peek() == Some(c) {
+            self.offset += c.len_utf8();
+            return true;
+        }
+        self.offset = start;
+        false
+    }
+
+    fn name(&mut self) -> Option<&str> {
+        let start = self.offset;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            self.offset += 1;
+        }
+        (self.offset > start).then(|| &self.query[start..self.offset])
+    }
+
+    fn selector(&mut self) -> Result<Selector, QueryError> {
+        self.skip_whitespace();
+        let mut steps = vec![self.step(Combinator::Descendant)?];
+        loop {
+            let spaced = self.skip_whitespace();
+            let combinator = match self.peek() {
+                Some('>') => {
+                    self.offset += 1;
+                    self.skip_whitespace();
+                    Combinator::Child
+                }
+                Some(',') | None => break,
+                Some(_) if spaced => Combinator::Descendant,
+                Some(c) => return Err(self.error(format!("unexpected `{c}`"))),
+            };
+            steps.push(self.step(combinator)?);
+        }
+        Ok(Selector { steps })
+    }
+
+    fn step(&mut self, combinator: Combinator) -> Result<Step, QueryError> {
+        let start = self.offset;
+        let kind = if self.peek() == Some('*') {
+            self.offset += 1;
+            None
+        } else {
+            let Some(name) = self.name() else {
+                return Err(self.error("expected a node kind"));
+            };
+            let normalized = name.replace(['_', '-'], "").to_uppercase();
+            let kind = SyntaxKind::ALL
+                .into_iter()
+                .find(|kind| kind.to_string() == normalized);
+            if kind.is_none() {
+                let message = format!("unknown node kind `{name}`");
+                self.offset = start;
+                return Err(self.error(message));
+            }
+            kind
+        };
+        let mut attributes = Vec::new();
+        while self.peek() == Some('[') {
+            self.offset += 1;
+            attributes.push(self.attribute()?);
+        }
+        let mut capture = None;
+        if self.eat('@') {
+            let Some(name) = self.name() else {
+                return Err(self.error("expected a capture name"));
+            };
+            capture = Some(name.to_owned());
+        }
+        Ok(Step {
+            combinator,
+            kind,
+            attributes,
+            capture,
+        })
+    }
+
+    fn attribute(&mut self) -> Result<Attribute, QueryError> {
+        self.skip_whitespace();
+        let Some(name) = self.name().map(str::to_owned) else {
+            return Err(self.error("expected an attribute name"));
+        };
+        self.skip_whitespace();
+        let rest = &self.query[self.offset..];
+        let (operator, test): (&str, fn(String) -> Test) = if rest.starts_with("!=") {
+            ("!=", Test::NotEquals)
+        } else if rest.starts_with("^=") {
+            ("^=", Test::Prefix)
+        } else if rest.starts_with("*=") {
+            ("*=", Test::Contains)
+        } else if rest.starts_with('=') {
+            ("=", Test::Equals)
+        } else if self.eat(']') {
+            return Ok(Attribute {
+                name,
+                test: Test::Exists,
+            });
+        } else {
+            return Err(self.error("expected `=`, `!=`, `^=`, `*=` or `]`"));
+        };
+        self.offset += operator.len();
+        self.skip_whitespace();
+        let value = self.value()?;
+        if !self.eat(']') {
+            return Err(self.error("expected `]`"));
+        }
+        Ok(Attribute {
+            name,
+            test: test(value),
+        })
+    }
+
+    fn value(&mut self) -> Result<String, QueryError> {
+        let rest = &self.query[self.offset..];
+        if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            let Some(end) = rest[1..].find(quote) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.offset += end + 2;
+            return Ok(rest[1..=end].to_owned());
+        }
+        let end = rest.find(']').unwrap_or(rest.len());
+        self.offset += end;
+        Ok(rest[..end].trim().to_owned())
+    }
+}
+
+/// the nodes of a tree with headings owning their sections
+struct Tree<'a> {
+    /// in document order
+    nodes: Vec<&'a Node>,
+    parents: Vec<Option<usize>>,
+}
+
+impl<'a> Tree<'a> {
+    fn new(root: &'a Node) -> Self {
+        let mut tree = Self {
+            nodes: vec![root],
+            parents: vec![None],
+        };
+        // open headings by level
+        let mut sections: Vec<(usize, usize)> = Vec::new();
+        for block in root.children() {
+            let level = Heading::cast(block).map(|heading| heading.level());
+            if let Some(level) = level {
+                while sections.last().is_some_and(|(open, _)| *open >= level) {
+                    sections.pop();
+                }
+            }
+            let parent = sections.last().map_or(0, |(_, index)| *index);
+            let index = tree.add(block, parent);
+            if let Some(level) = level {
+                sections.push((level, index));
+            }
+        }
+        tree
+    }
+
+    /// adds `node` and its subtree below `parent`, returns its index
+    fn add(&mut self, node: &'a Node, parent: usize) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(node);
+        self.parents.push(Some(parent));
+        for child in node.children() {
+            self.add(child, index);
+        }
+        index
+    }
+
+    /// whether `steps[..=step]` match with `steps[step]` on `index`
+    fn matches(
+        &self,
+        steps: &[Step],
+        step: usize,
+        index: usize,
+        captures: &mut Vec<(String, &'a Node)>,
+    ) -> bool {
+        let current = &steps[step];
+        if !self.matches_step(current, index) {
+            return false;
+        }
+        let captured = captures.len();
+        if let Some(name) = &current.capture {
+            captures.push((name.clone(), self.nodes[index]));
+        }
+        if step == 0 {
+            return true;
+        }
+        let mut parent = self.parents[index];
+        while let Some(candidate) = parent {
+            if self.matches(steps, step - 1, candidate, captures) {
+                return true;
+            }
+            if current.combinator == Combinator::Child {
+                break;
+            }
+            parent = self.parents[candidate];
+        }
+        captures.truncate(captured);
+        false
+    }
+
+    fn matches_step(&self, step: &Step, index: usize) -> bool {
+        let node = self.nodes[index];
+        if step.kind.is_some_and(|kind| kind != node.kind()) {
+            return false;
+        }
+        step.attributes.iter().all(|attribute| {
+            let values = self.attribute(index, &attribute.name);
+            match &attribute.test {
+                Test::Exists => !values.is_empty(),
+                Test::Equals(value) => values.iter().any(|v| v == value),
+                Test::NotEquals(value) => values.iter().all(|v| v != value),
+                Test::Prefix(value) => values.iter().any(|v| v.starts_with(value.as_str())),
+                Test::Contains(value) => values.iter().any(|v| v.contains(value.as_str())),
+            }
+        })
+    }
+
+    /// the values of the attribute `name` of a node, empty if it has none
+    fn attribute(&self, index: usize, name: &str) -> Vec<String> {
+        let node = self.nodes[index];
+        let value = match (node.kind(), name) {
+            (_, "text") => Some(node.text().trim().to_owned()),
+            (SyntaxKind::Heading, "tag") => return self.heading_tags(index),
+            (SyntaxKind::Heading, _) => Heading::cast(node).and_then(|heading| match name {
+                "level" => Some(heading.level().to_string()),
+                "title" => Some(heading.title()),
+                _ => None,
+            }),
+            (SyntaxKind::Link, _) => Link::cast(node).and_then(|link| match name {
+                "target" => Some(link.target()),
+                "page" => Some(link.page()),
+                "anchor" => link.anchor(),
+                "description" => link.description(),
+                "scheme" => link.scheme(),
+                _ => None,
+            }),
+            (SyntaxKind::Transclusion, "src") => Some(export::transclusion(node).0),
+            (SyntaxKind::Transclusion, "alt") => export::transclusion(node).1,
+            (SyntaxKind::List, "ordered") => Some(export::is_ordered(node).to_string()),
+            (SyntaxKind::ListItem, "state") => ListItem::cast(node)
+                .and_then(|item| item.checkbox())
+                .map(|checkbox| state(checkbox.state()).to_owned()),
+            (SyntaxKind::Checkbox, "state") => {
+                ast::Checkbox::cast(node).map(|checkbox| state(checkbox.state()).to_owned())
+            }
+            (SyntaxKind::Tags, "name") => {
+                return Tags::cast(node)
+                    .map(|tags| tags.names())
+                    .unwrap_or_default();
+            }
+            (SyntaxKind::Tag, "name") => Some(node.text()),
+            (SyntaxKind::CodeBlock, "lang") => Some(export::verbatim(node).0),
+            (SyntaxKind::Placeholder, _) => Placeholder::cast(node).and_then(|p| match name {
+                "name" => Some(p.name()),
+                "value" => Some(p.value()),
+                _ => None,
+            }),
+            _ => None,
+        };
+        value.into_iter().collect()
+    }
+
+    /// the tags in the section of a heading, outside of its subsections
+    fn heading_tags(&self, heading: usize) -> Vec<String> {
+        (0..self.nodes.len())
+            .filter(|&index| self.parents[index] == Some(heading))
+            .map(|index| self.nodes[index])
+            .filter(|block| block.kind() != SyntaxKind::Heading)
+            .flat_map(|block| ast::find_all(block, SyntaxKind::Tags))
+            .filter_map(Tags::cast)
+            .flat_map(|tags| tags.names())
+            .collect()
+    }
+}
+
+/// the name of a checkbox state in queries
+fn state(state: CheckboxState) -> &'static str {
+    match state {
+        CheckboxState::Open => "open",
+        CheckboxState::Started => "started",
+        CheckboxState::Half => "half",
+        CheckboxState::Mostly => "mostly",
+        CheckboxState::Done => "done",
+        CheckboxState::Rejected => "rejected",
+    }
+}
@@ -0,0 +1,126 @@
+//! incremental reparsing of edited documents
+//!
+//! the top level parser keeps no state between blocks and decides where a
+//! block ends by looking at the line after it. so after an edit only the
+//! blocks from the one before the edit up to the first block boundary which
+//! survived the edit have to be parsed again, everything else is reused.
+//! reused blocks share their children with the old tree, and the ones after
+//! the edit are moved by `Node::shifted` which does not copy them.
+
+use ropey::Rope;
+
+use crate::kind::SyntaxKind;
+use crate::lexer::Lexer;
+use crate::parser::{Node, Parser};
+use crate::span::Span;
+
+/// a text edit, `span` is the replaced byte range of the text before the edit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edit {
+    pub span: Span,
+    pub text: String,
+}
+
+impl Edit {
+    pub fn new(span: Span, text: impl Into<String>) -> Self {
+        Self {
+            span,
+            text: text.into(),
+        }
+    }
+
+    /// applies the edit to `rope`
+    pub fn apply(&self, rope: &mut Rope) {
+        let start = rope.byte_to_char(self.span.start.min(rope.len_bytes()));
+        let end = rope.byte_to_char(self.span.end.min(rope.len_bytes()));
+        rope.remove(start..end);
+        rope.insert(start, &self.text);
+    }
+
+    /// how much the text after the edit moved
+    fn delta(&self) -> isize {
+        self.text.len() as isize - (self.span.end - self.span.start) as isize
+    }
+}
+
+/// lexes and parses the byte range `span` of `text` as a document of its own
+fn parse_window(text: &Rope, span: Span) -> Vec<Node> {
+    let window = Rope::from(text.byte_slice(span.start..span.end));
+    let tokens = Lexer::new(window).lex();
+    Parser::new(tokens)
+        .parse()
+        .iter()
+        .map(|node| node.shifted(span.start as isize))
+        .collect()
+}
+
+/// whether a top level node starting at `offset` of `text` begins a line
+fn starts_line(text: &Rope, offset: usize) -> bool {
+    offset == 0 || (offset <= text.len_bytes() && text.byte(offset - 1) == b'\n')
+}
+
+/// reparses `root` after `edit`, `text` is the whole text after the edit
+///
+/// the result is the same tree `parser::parse` builds for `text`.
+pub fn reparse(root: &Node, text: &Rope, edit: &Edit) -> Node {
+    let blocks = root.children();
+    let delta = edit.delta();
+    let len = text.len_bytes();
+
+    // the first block touched by the edit. parsing starts over at a block
+    // before the edited line, since the block in front of it looked at that
+    // line to decide where it ends
+    let touched = blocks
+        .iter()
+        .position(|block| block.span().end > edit.span.start)
+        .unwrap_or(blocks.len());
+    let edited_line = text.line_to_byte(text.byte_to_line(edit.span.start.min(len)));
+    let first = (0..touched)
+        .rev()
+        .find(|&i| {
+            let start = blocks[i].span().start;
+            start < edited_line && starts_line(text, start)
+        })
+        .unwrap_or(0);
+    let start = blocks
+        .get(first)
+        .map_or(0, |b| b.span().start.min(edit.span.start));
+
+    // old blocks after the edit which start a line are where parsing may resync
+    let boundaries: Vec<usize> = (touched..blocks.len())
+        .filter(|&i| {
+            let old = blocks[i].span().start;
+            old > edit.span.end && starts_line(text, old.saturating_add_signed(delta))
+        })
+        .collect();
+
+    let mut children: Vec<Node> = blocks[..first].to_vec();
+
+    // grow the window until a boundary is found again, keeping one block of
+    // lookahead behind it
+    let mut count = 2.min(boundaries.len());
+    while count >= 2 {
+        let end = blocks[boundaries[count - 1]]
+            .span()
+            .start
+            .saturating_add_signed(delta);
+        let parsed = parse_window(text, Span::new(start, end));
+        let resync = boundaries[..count - 1].iter().find_map(|&i| {
+            let at = blocks[i].span().start.saturating_add_signed(delta);
+            let index = parsed.iter().position(|node| node.span().start == at)?;
+            Some((i, index))
+        });
+        if let Some((old, new)) = resync {
+            children.extend(parsed.into_iter().take(new));
+            children.extend(blocks[old..].iter().map(|node| node.shifted(delta)));
+            return Node::inner(SyntaxKind::Root, children);
+        }
+        if count == boundaries.len() {
+            break;
+        }
+        count = (count * 2).min(boundaries.len());
+    }
+
+    children.extend(parse_window(text, Span::new(start, len)));
+    Node::inner(SyntaxKind::Root, children)
+}
@@ -0,0 +1,257 @@
+//! a stable json format for token streams and syntax trees
+//!
+//! every document carries the schema [`VERSION`] it was written with and is
+//! only read back if the version matches. kinds are the `Display` names of
+//! [`SyntaxKind`] and spans are `[start, end]` byte offsets.
+//!
+//! ```json
+//! { "version": 1, "tokens": [{ "kind": "TEXT", "text": "a", "span": [0, 1] }] }
+//! { "version": 1, "tree": NODE, "diagnostics": [{ "span": [0, 1], "error": "..", "hint": "..", "code": ".." }] }
+//! ```
+//!
+//! a `NODE` has its `kind` and `span`, leaves their `text` and inner nodes
+//! their `children`. error nodes also carry their `error`, `hint` and the id
+//! of their [`ErrorCode`] as `code`, if they have one. the
+//! diagnostics repeat the errors of the tree in document order, they are
+//! ignored when reading a tree back.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::kind::SyntaxKind;
+use crate::lexer::Token;
+use crate::parser::{ErrorCode, ErrorNode, Node, Repr};
+use crate::span::Span;
+
+/// version of the format, bumped on every incompatible change
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported schema version {0}, expected {VERSION}")]
+    Version(u32),
+    #[error("unknown syntax kind `{0}`")]
+    UnknownKind(String),
+    #[error("unknown error code `{0}`")]
+    UnknownCode(String),
+    #[error("{0} node at {1:?} {2}")]
+    Malformed(String, [usize; 2], &'static str),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TokenData {
+    kind: String,
+    text: String,
+    span: [usize; 2],
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct NodeData {
+    kind: String,
+    span: [usize; 2],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    children: Option<Vec<NodeData>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Diagnostic {
+    span: [usize; 2],
+    error: Option<String>,
+    hint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TokensDocument {
+    version: u32,
+    tokens: Vec<TokenData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TreeDocument {
+    version: u32,
+    tree: NodeData,
+    #[serde(default)]
+    diagnostics: Vec<Diagnostic>,
+}
+
+/// only the version, checked before the rest is read
+#[derive(Debug, Deserialize)]
+struct Header {
+    version: u32,
+}
+
+fn span(span: Span) -> [usize; 2] {
+    [span.start, span.end]
+}
+
+fn kind(name: &str) -> Result<SyntaxKind, SchemaError> {
+    name.parse()
+        .map_err(|_| SchemaError::UnknownKind(name.to_owned()))
+}
+
+fn check_version(json: &str) -> Result<(), SchemaError> {
+    let header: Header = serde_json::from_str(json)?;
+    if header.version != VERSION {
+        return Err(SchemaError::Version(header.version));
+    }
+    Ok(())
+}
+
+impl From<&Node> for NodeData {
+    fn from(node: &Node) -> Self {
+        let mut data = Self {
+            kind: node.kind().to_string(),
+            span: span(node.span()),
+            text: None,
+            children: None,
+            error: None,
+            hint: None,
+            code: None,
+        };
+        match node.repr() {
+            Repr::InnerNode(_) => {
+                data.children = Some(node.children().iter().map(NodeData::from).collect());
+            }
+            Repr::SyntaxNode(_) => data.text = Some(node.text()),
+            Repr::ErrorNode(error) => {
+                data.text = Some(node.text());
+                data.error = error.error().map(str::to_owned);
+                data.hint = error.hint().map(str::to_owned);
+                data.code = error.code().map(|code| code.id().to_owned());
+            }
+        }
+        data
+    }
+}
+
+impl TryFrom<NodeData> for Node {
+    type Error = SchemaError;
+
+    fn try_from(data: NodeData) -> Result<Self, Self::Error> {
+        let kind = kind(&data.kind)?;
+        let [start, end] = data.span;
+        let malformed = |reason| Err(SchemaError::Malformed(data.kind.clone(), data.span, reason));
+        if let Some(text) = &data.text {
+            if end < start {
+                return malformed("ends before it starts");
+            }
+            if end - start != text.len() {
+                return malformed("does not span its text");
+            }
+        }
+        match (data.children, data.text) {
+            (Some(children), _) => {
+                let children: Vec<Node> = children
+                    .into_iter()
+                    .map(Node::try_from)
+                    .collect::<Result<_, _>>()?;
+                // the text of a tree has no gaps, every node starts where the
+                // one before it ends
+                if let Some(pair) = children
+                    .windows(2)
+                    .find(|pair| pair[0].span().end != pair[1].span().start)
+                {
+                    return Err(SchemaError::Malformed(
+                        pair[1].kind().to_string(),
+                        span(pair[1].span()),
+                        "does not start where the node before it ends",
+                    ));
+                }
+                if children.is_empty() {
+                    return Ok(Node::empty(kind, start));
+                }
+                Ok(Node::inner(kind, children))
+            }
+            (None, Some(text)) if kind.is_error() => {
+                let error = ErrorNode::new(
+                    kind,
+                    text.into(),
+                    data.error,
+                    data.hint,
+                    Span::new(start, end),
+                );
+                Ok(Node::from(match data.code {
+                    Some(code) => error.with_code(
+                        code.parse::<ErrorCode>()
+                            .map_err(|_| SchemaError::UnknownCode(code))?,
+                    ),
+                    None => error,
+                }))
+            }
+            (None, Some(text)) => Ok(Node::leaf(kind, text, Span::new(start, end))),
+            (None, None) => malformed("has neither text nor children"),
+        }
+    }
+}
+
+/// `tokens` as a json document
+pub fn tokens_to_json(tokens: &[Token]) -> String {
+    let document = TokensDocument {
+        version: VERSION,
+        tokens: tokens
+            .iter()
+            .map(|token| TokenData {
+                kind: token.kind.to_string(),
+                text: token.text.clone(),
+                span: span(token.span),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+/// the tokens of a document written by [`tokens_to_json`]
+pub fn tokens_from_json(json: &str) -> Result<Vec<Token>, SchemaError> {
+    check_version(json)?;
+    let document: TokensDocument = serde_json::from_str(json)?;
+    document
+        .tokens
+        .into_iter()
+        .map(|token| {
+            let [start, end] = token.span;
+            Ok(Token {
+                kind: kind(&token.kind)?,
+                text: token.text,
+                span: Span::new(start, end),
+            })
+        })
+        .collect()
+}
+
+/// the tree below `root` and its errors as a json document
+pub fn tree_to_json(root: &Node) -> String {
+    let document = TreeDocument {
+        version: VERSION,
+        tree: NodeData::from(root),
+        diagnostics: root
+            .error_nodes()
+            .into_iter()
+            .map(|error| Diagnostic {
+                span: span(error.span()),
+                error: error.error().map(str::to_owned),
+                hint: error.hint().map(str::to_owned),
+                code: error.code().map(|code| code.id().to_owned()),
+            })
+            .collect(),
+    };
+    serde_json::to_string_pretty(&document).unwrap_or_default()
+}
+
+/// the tree of a document written by [`tree_to_json`]
+pub fn tree_from_json(json: &str) -> Result<Node, SchemaError> {
+    check_version(json)?;
+    let document: TreeDocument = serde_json::from_str(json)?;
+    Node::try_from(document.tree)
+}
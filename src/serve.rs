@@ -0,0 +1,321 @@
+//! a local preview of the static site which follows changes to the wiki
+//!
+//! the wiki is built into an output directory and served over http on the
+//! loopback interface. the files of the wiki are polled for changes, each
+//! change rebuilds the site incrementally and reloads the open pages. pages
+//! get a script listening for reloads on an event stream at
+//! [`RELOAD_PATH`].
+
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use thiserror::Error;
+
+use crate::site::{BuildReport, Site, SiteError};
+
+/// path of the event stream pages listen to for reloads
+pub const RELOAD_PATH: &str = "/__livereload";
+
+/// script injected into every served page
+pub const RELOAD_SCRIPT: &str = "<script>new EventSource(\"/__livereload\")\
+    .onmessage = () => location.reload();</script>";
+
+#[derive(Debug, Error)]
+pub enum ServeError {
+    #[error("could not listen on {addr}: {source}")]
+    Bind {
+        addr: SocketAddr,
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Site(#[from] SiteError),
+}
+
+/// `html` with the reload script in front of `</body>`, or at the end
+pub fn inject(html: &str) -> String {
+    match html.rfind("</body>") {
+        Some(end) => format!("{}{RELOAD_SCRIPT}\n{}", &html[..end], &html[end..]),
+        None => format!("{html}{RELOAD_SCRIPT}\n"),
+    }
+}
+
+/// `%20` and the like decoded, `None` if the result is not utf-8
+fn decode(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut rest = path.bytes();
+    while let Some(byte) = rest.next() {
+        if byte == b'%' {
+            let hex = [rest.next()?, rest.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// the file below `out` a request for `path` is answered with
+///
+/// directories are answered with their `index.html`, paths without an
+/// extension with the page of that name. paths leaving `out` give `None`.
+pub fn resolve(out: &Path, path: &str) -> Option<PathBuf> {
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let path = decode(path)?;
+    let mut file = out.to_owned();
+    for component in Path::new(path.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(part) => file.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if file.is_dir() {
+        file.push("index.html");
+    } else if !file.is_file() && file.extension().is_none() {
+        file.set_extension("html");
+    }
+    file.is_file().then_some(file)
+}
+
+fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("json") => "application/json",
+        Some("txt" | "wiki" | "md") => "text/plain; charset=utf-8",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("svg") => "image/svg+xml",
+        Some("webp") => "image/webp",
+        Some("pdf") => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+/// the pages listening for reloads
+#[derive(Debug, Clone, Default)]
+pub struct Reloader {
+    clients: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Reloader {
+    fn add(&self, stream: TcpStream) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.push(stream);
+        }
+    }
+
+    /// tells every page to reload, pages which went away are dropped
+    pub fn reload(&self) {
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain_mut(|client| {
+                client
+                    .write_all(b"data: reload\n\n")
+                    .and_then(|()| client.flush())
+                    .is_ok()
+            });
+        }
+    }
+
+    /// number of pages listening
+    pub fn clients(&self) -> usize {
+        self.clients.lock().map_or(0, |clients| clients.len())
+    }
+}
+
+/// answers a single request for a file below `out`
+fn respond(mut stream: TcpStream, out: &Path, reloader: &Reloader) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    // the headers are not needed
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+    let mut parts = request.split_whitespace();
+    let (method, path) = (
+        parts.next().unwrap_or_default(),
+        parts.next().unwrap_or("/"),
+    );
+    if method != "GET" && method != "HEAD" {
+        return stream.write_all(
+            b"HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+    }
+    if path == RELOAD_PATH {
+        stream.write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+              Cache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+        )?;
+        stream.flush()?;
+        reloader.add(stream);
+        return Ok(());
+    }
+    let Some(file) = resolve(out, path) else {
+        let body = b"not found\n";
+        write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n",
+            body.len()
+        )?;
+        return stream.write_all(body);
+    };
+    let mut body = std::fs::read(&file)?;
+    if file.extension().is_some_and(|ext| ext == "html") {
+        body = inject(&String::from_utf8_lossy(&body)).into_bytes();
+    }
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        content_type(&file),
+        body.len()
+    )?;
+    if method == "GET" {
+        stream.write_all(&body)?;
+    }
+    stream.flush()
+}
+
+/// answers requests on `listener` with the files below `out` in the
+/// background, each connection on its own thread
+pub fn spawn(listener: TcpListener, out: PathBuf, reloader: Reloader) {
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let (out, reloader) = (out.clone(), reloader.clone());
+            std::thread::spawn(move || {
+                let _ = respond(stream, &out, &reloader);
+            });
+        }
+    });
+}
+
+/// modification times and sizes of the files below a directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Watcher {
+    root: PathBuf,
+    /// directories which are not watched, like the output directory
+    ignore: Vec<PathBuf>,
+    files: BTreeMap<PathBuf, (Option<u64>, u64)>,
+}
+
+impl Watcher {
+    /// watches the files below `root` except for those below `ignore`
+    pub fn new(root: &Path, ignore: &[&Path]) -> Self {
+        // canonical paths so `./out` and `out` are the same
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_owned());
+        let mut watcher = Self {
+            root: canonical(root),
+            ignore: ignore.iter().map(|path| canonical(path)).collect(),
+            files: BTreeMap::new(),
+        };
+        watcher.files = watcher.scan();
+        watcher
+    }
+
+    fn scan(&self) -> BTreeMap<PathBuf, (Option<u64>, u64)> {
+        fn walk(watcher: &Watcher, dir: &Path, files: &mut BTreeMap<PathBuf, (Option<u64>, u64)>) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                // hidden files like `.git` and the tag cache
+                if entry.file_name().to_string_lossy().starts_with('.')
+                    || watcher.ignore.iter().any(|ignore| path.starts_with(ignore))
+                {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.is_dir() {
+                    walk(watcher, &path, files);
+                } else {
+                    let modified = metadata
+                        .modified()
+                        .ok()
+                        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                        .map(|time| time.as_nanos() as u64);
+                    files.insert(path, (modified, metadata.len()));
+                }
+            }
+        }
+        let mut files = BTreeMap::new();
+        walk(self, &self.root, &mut files);
+        files
+    }
+
+    /// the files which were added, changed or removed since the last call
+    pub fn changes(&mut self) -> Vec<PathBuf> {
+        let files = self.scan();
+        let mut changed: Vec<_> = files
+            .iter()
+            .filter(|(path, state)| self.files.get(*path) != Some(state))
+            .map(|(path, _)| path.clone())
+            .collect();
+        changed.extend(
+            self.files
+                .keys()
+                .filter(|path| !files.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+        self.files = files;
+        changed
+    }
+}
+
+/// what [`serve`] is doing
+#[derive(Debug)]
+pub enum Event {
+    /// the site is served on this address
+    Ready(SocketAddr),
+    Built(BuildReport),
+    /// a rebuild failed, the previous build is still served
+    Failed(SiteError),
+}
+
+/// builds `site`, serves it on `addr` and rebuilds it whenever the wiki
+/// changes, checking every `interval`. only returns if the first build
+/// fails or `addr` can not be bound
+pub fn serve(
+    site: &Site,
+    addr: SocketAddr,
+    interval: Duration,
+    mut events: impl FnMut(Event),
+) -> Result<(), ServeError> {
+    let root = site.root().ok_or(SiteError::NoWiki(site.wiki()))?;
+    events(Event::Built(site.build()?));
+    let mut watcher = Watcher::new(root, &[site.out()]);
+    let listener = TcpListener::bind(addr).map_err(|source| ServeError::Bind { addr, source })?;
+    let reloader = Reloader::default();
+    events(Event::Ready(listener.local_addr().unwrap_or(addr)));
+    spawn(listener, site.out().to_owned(), reloader.clone());
+    loop {
+        std::thread::sleep(interval);
+        if watcher.changes().is_empty() {
+            continue;
+        }
+        match site.build() {
+            Ok(report) => {
+                let changed = !(report.written.is_empty()
+                    && report.copied.is_empty()
+                    && report.removed.is_empty());
+                events(Event::Built(report));
+                if changed {
+                    reloader.reload();
+                }
+            }
+            Err(err) => events(Event::Failed(err)),
+        }
+    }
+}
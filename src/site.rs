@@ -0,0 +1,443 @@
+//! a static html site of a whole wiki, like `:VimwikiAll2HTML`
+//!
+//! every page is exported to the same path below the output directory with
+//! an `.html` extension. links are rewritten to point to the exported pages,
+//! images and `local:` files inside the wiki are copied along. the diary
+//! index and a `tags` page listing every tag are generated.
+//!
+//! a manifest in the output directory records what each output was built
+//! from. pages are only exported again when their source or a page they link
+//! to changed, files are only copied again when their modification time
+//! changed. outputs of pages and files which are gone are deleted. the
+//! manifest needs the `serde` feature, without it every build is a full one.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::anchor::{Anchors, html_id};
+use crate::ast::{self, Link};
+use crate::check::linked_file;
+use crate::diary::Diary;
+use crate::export::{Html, transclusion};
+use crate::graph::{Graph, PageId};
+use crate::kind::SyntaxKind;
+use crate::tags::TagIndex;
+use crate::workspace::{Page, Workspace, relative_page};
+
+/// name of the manifest kept in the output directory
+pub const MANIFEST_FILE: &str = ".vimwiki_site.json";
+
+/// name of the generated page listing every tag
+pub const TAGS_PAGE: &str = "tags";
+
+/// version of the manifest, bumped when the html of pages changes so every
+/// page is exported again
+const VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum SiteError {
+    #[error("could not access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("the workspace has no wiki {0}")]
+    NoWiki(usize),
+}
+
+/// how an output was built
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Output {
+    /// hash of the page and the pages it links to
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    hash: Option<u64>,
+    /// modification time of a copied file in nanoseconds since the epoch
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    modified: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct Manifest {
+    /// only read back with the `serde` feature
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    version: u32,
+    /// by path relative to the output directory
+    outputs: BTreeMap<String, Output>,
+}
+
+impl Manifest {
+    /// the manifest in `dir`, an empty one if it is missing or outdated
+    #[cfg(feature = "serde")]
+    fn load(dir: &Path) -> Self {
+        std::fs::read_to_string(dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|text| serde_json::from_str::<Self>(&text).ok())
+            .filter(|manifest| manifest.version == VERSION)
+            .unwrap_or_default()
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn load(_dir: &Path) -> Self {
+        Self::default()
+    }
+
+    #[cfg(feature = "serde")]
+    fn save(&self, dir: &Path) -> Result<(), SiteError> {
+        let path = dir.join(MANIFEST_FILE);
+        let json = serde_json::to_string_pretty(self).unwrap_or_default();
+        std::fs::write(&path, json).map_err(io(&path))
+    }
+
+    #[cfg(not(feature = "serde"))]
+    fn save(&self, _dir: &Path) -> Result<(), SiteError> {
+        Ok(())
+    }
+}
+
+/// what a build did, paths are relative to the output directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// exported pages
+    pub written: Vec<String>,
+    pub copied: Vec<String>,
+    /// outputs which were up to date
+    pub unchanged: Vec<String>,
+    /// stale outputs which were deleted
+    pub removed: Vec<String>,
+}
+
+/// 64 bit FNV-1a, stable across builds unlike the std hasher
+#[derive(Debug, Clone, Copy)]
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(mut self, bytes: &[u8]) -> Self {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+        self
+    }
+}
+
+/// modification time of `path` in nanoseconds since the epoch
+fn modified(path: &Path) -> Option<u64> {
+    let time = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+/// `path` relative to `base`, with `/` separators and `..` resolved. `None`
+/// if the path is outside of `base`
+fn relative(path: &Path, base: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in path.strip_prefix(base).ok()?.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy()),
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            _ => {}
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn io(path: &Path) -> impl FnOnce(std::io::Error) -> SiteError + '_ {
+    move |source| SiteError::Io {
+        path: path.to_owned(),
+        source,
+    }
+}
+
+/// the static site of a wiki of a workspace
+#[derive(Debug, Clone)]
+pub struct Site<'a> {
+    workspace: &'a Workspace,
+    wiki: usize,
+    out: PathBuf,
+    css: Option<String>,
+}
+
+impl<'a> Site<'a> {
+    /// the site of wiki `wiki` of `workspace`, built into `out`
+    pub fn new(workspace: &'a Workspace, wiki: usize, out: impl Into<PathBuf>) -> Self {
+        Self {
+            workspace,
+            wiki,
+            out: out.into(),
+            css: None,
+        }
+    }
+
+    /// a stylesheet every page links to, relative to the output directory
+    pub fn css(mut self, path: impl Into<String>) -> Self {
+        self.css = Some(path.into());
+        self
+    }
+
+    /// index of the wiki in the workspace
+    pub fn wiki(&self) -> usize {
+        self.wiki
+    }
+
+    /// root directory of the wiki
+    pub fn root(&self) -> Option<&Path> {
+        Some(&self.workspace.wiki(self.wiki)?.root)
+    }
+
+    /// the output directory
+    pub fn out(&self) -> &Path {
+        &self.out
+    }
+
+    /// the pages of the wiki with the generated diary index and tags page
+    pub fn pages(&self, pages: &[Vec<Page>]) -> Vec<Page> {
+        let mut own = pages.get(self.wiki).cloned().unwrap_or_default();
+        let Some(config) = self.workspace.wiki(self.wiki) else {
+            return own;
+        };
+        let mut generate = |name: &str, update: &dyn Fn(&str) -> String| match own
+            .iter_mut()
+            .find(|page| page.name == name)
+        {
+            Some(page) => *page = Page::new(name, &update(&page.root.text())),
+            None => own.push(Page::new(name, &update(""))),
+        };
+        let diary = Diary::new(config, pages.get(self.wiki).map_or(&[], Vec::as_slice));
+        if !diary.entries().is_empty() {
+            generate(diary.index(), &|source| diary.update_index(source));
+        }
+        let tags = TagIndex::build(pages);
+        if tags
+            .entries()
+            .iter()
+            .any(|entry| entry.page.wiki == self.wiki)
+        {
+            generate(TAGS_PAGE, &|source| {
+                tags.update_links(source, self.wiki, TAGS_PAGE, None)
+            });
+        }
+        own.sort_by(|a, b| a.name.cmp(&b.name));
+        own
+    }
+
+    /// the files inside the wiki root which page `page` links to or embeds,
+    /// relative to the root
+    pub fn files(&self, page: &Page) -> BTreeSet<String> {
+        let Some(config) = self.workspace.wiki(self.wiki) else {
+            return BTreeSet::new();
+        };
+        let path = config.path(&page.name);
+        let mut files = BTreeSet::new();
+        for link in ast::find_all(&page.root, SyntaxKind::Link)
+            .into_iter()
+            .filter_map(Link::cast)
+        {
+            if let Some(scheme @ ("local" | "file")) = link.scheme().as_deref() {
+                let target = link.target();
+                let file = linked_file(&path, &target[scheme.len() + 1..]);
+                files.extend(relative(&file, &config.root));
+            }
+        }
+        for node in ast::find_all(&page.root, SyntaxKind::Transclusion) {
+            let (source, _) = transclusion(node);
+            if source.is_empty() || source.contains("://") || source.starts_with('/') {
+                continue;
+            }
+            if let Some(dir) = path.parent() {
+                files.extend(relative(&dir.join(source), &config.root));
+            }
+        }
+        files
+    }
+
+    /// hashes of `pages` and the pages each of them links to
+    fn hashes(&self, pages: &[Page], graph: &Graph) -> HashMap<String, u64> {
+        let own: HashMap<_, _> = pages
+            .iter()
+            .map(|page| {
+                (
+                    page.name.as_str(),
+                    Fnv::new().write(page.root.text().as_bytes()),
+                )
+            })
+            .collect();
+        let css = self.css.as_deref().unwrap_or_default();
+        pages
+            .iter()
+            .map(|page| {
+                let mut hash = own[page.name.as_str()].write(css.as_bytes());
+                for linked in graph.outgoing(&PageId::new(self.wiki, &page.name)) {
+                    if linked.wiki == self.wiki
+                        && let Some(linked) = own.get(linked.page.as_str())
+                    {
+                        hash = hash.write(&linked.0.to_le_bytes());
+                    }
+                }
+                (page.name.clone(), hash.0)
+            })
+            .collect()
+    }
+
+    /// page `page` as a standalone html document
+    pub fn render(&self, page: &Page, pages: &[Page]) -> String {
+        let from = page.name.as_str();
+        let html = Html::new()
+            .standalone(true)
+            .links(|link| self.href(from, link, pages));
+        let html = match &self.css {
+            Some(css) => html.css(relative_page(from, css)),
+            None => html,
+        };
+        html.render(&page.root)
+    }
+
+    /// where `link` in page `from` points to in the site, `None` for links
+    /// to urls and other wikis
+    fn href(&self, from: &str, link: &Link, pages: &[Page]) -> Option<String> {
+        let config = self.workspace.wiki(self.wiki)?;
+        let target = link.target();
+        if let Some(scheme @ ("local" | "file")) = link.scheme().as_deref() {
+            let file = linked_file(&config.path(from), &target[scheme.len() + 1..]);
+            return Some(match relative(&file, &config.root) {
+                Some(file) => relative_page(from, &file),
+                None => format!("file://{}", file.display()),
+            });
+        }
+        let location = self.workspace.resolve(self.wiki, from, &target)?;
+        if location.wiki != self.wiki {
+            return None;
+        }
+        let fragment = location
+            .anchor
+            .filter(|anchor| !anchor.is_empty())
+            .map(|anchor| {
+                let id = pages
+                    .iter()
+                    .find(|page| page.name == location.page)
+                    .and_then(|page| {
+                        Anchors::new(&page.root)
+                            .resolve(&anchor)
+                            .map(|a| a.html_id())
+                    })
+                    .unwrap_or_else(|| html_id(anchor.rsplit('#').next().unwrap_or_default()));
+                format!("#{id}")
+            })
+            .unwrap_or_default();
+        if location.page == from && !fragment.is_empty() {
+            return Some(fragment);
+        }
+        Some(format!(
+            "{}.html{fragment}",
+            relative_page(from, &location.page)
+        ))
+    }
+
+    /// exports every page which changed, copies the files they link to and
+    /// deletes stale outputs
+    pub fn build(&self) -> Result<BuildReport, SiteError> {
+        let config = self
+            .workspace
+            .wiki(self.wiki)
+            .ok_or(SiteError::NoWiki(self.wiki))?;
+        std::fs::create_dir_all(&self.out).map_err(io(&self.out))?;
+        let mut all = self.workspace.load_pages();
+        let pages = self.pages(&all);
+        if let Some(own) = all.get_mut(self.wiki) {
+            own.clone_from(&pages);
+        }
+        let graph = Graph::build(self.workspace, &all);
+        let hashes = self.hashes(&pages, &graph);
+
+        let old = Manifest::load(&self.out);
+        let mut manifest = Manifest {
+            version: VERSION,
+            outputs: BTreeMap::new(),
+        };
+        let mut report = BuildReport::default();
+        let is_current = |name: &str, output: &Output| {
+            old.outputs.get(name) == Some(output) && self.out.join(name).is_file()
+        };
+
+        for page in &pages {
+            let name = format!("{}.html", page.name);
+            let output = Output {
+                hash: hashes.get(&page.name).copied(),
+                modified: None,
+            };
+            if is_current(&name, &output) {
+                report.unchanged.push(name.clone());
+            } else {
+                self.write(&name, self.render(page, &pages).as_bytes())?;
+                report.written.push(name.clone());
+            }
+            manifest.outputs.insert(name, output);
+        }
+
+        let files: BTreeSet<_> = pages.iter().flat_map(|page| self.files(page)).collect();
+        for name in files {
+            let source = config.root.join(&name);
+            if !source.is_file() || manifest.outputs.contains_key(&name) {
+                continue;
+            }
+            let output = Output {
+                hash: None,
+                modified: modified(&source),
+            };
+            if is_current(&name, &output) {
+                report.unchanged.push(name.clone());
+            } else {
+                let contents = std::fs::read(&source).map_err(io(&source))?;
+                self.write(&name, &contents)?;
+                report.copied.push(name.clone());
+            }
+            manifest.outputs.insert(name, output);
+        }
+
+        for name in old.outputs.keys() {
+            if manifest.outputs.contains_key(name) {
+                continue;
+            }
+            let path = self.out.join(name);
+            if path.is_file() {
+                std::fs::remove_file(&path).map_err(io(&path))?;
+            }
+            // directories left empty go as well
+            for dir in path.ancestors().skip(1) {
+                if dir == self.out || std::fs::remove_dir(dir).is_err() {
+                    break;
+                }
+            }
+            report.removed.push(name.clone());
+        }
+
+        manifest.save(&self.out)?;
+        Ok(report)
+    }
+
+    /// writes `contents` to `name` below the output directory
+    fn write(&self, name: &str, contents: &[u8]) -> Result<(), SiteError> {
+        let path = self.out.join(name);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(io(dir))?;
+        }
+        std::fs::write(&path, contents).map_err(io(&path))
+    }
+}
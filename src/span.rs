@@ -1,7 +1,7 @@
 use std::fmt::{Debug, Display};
 
-use ropey::str_utils::{byte_to_char_idx, byte_to_line_idx, line_to_char_idx};
-use tower_lsp::lsp_types::{Position, Range};
+use crate::line_index::{Encoding, LineIndex, Range};
+use ropey::str_utils::byte_to_char_idx;
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Span {
@@ -19,7 +19,7 @@ impl SpanOps for str {
     fn span(&self) -> Span {
         Span {
             start: 0,
-            end: self.bytes().len(),
+            end: self.len(),
         }
     }
 }
@@ -44,11 +44,12 @@ impl Span {
         Self { start, end }
     }
 
-    /// -- FIX: erratic
+    /// the same range in char offsets of `text`
     pub fn to_char_offset(&self, text: &str) -> Span {
-        let s = byte_to_char_idx(text, self.start);
-        let e = byte_to_char_idx(text, self.end);
-        text[s..e].span()
+        Span::new(
+            byte_to_char_idx(text, self.start),
+            byte_to_char_idx(text, self.end),
+        )
     }
 
     /// returns (beginning of char_offset, char len)
@@ -58,40 +59,15 @@ impl Span {
         (s + 1, e.saturating_sub(s))
     }
 
-    /// Position in a text document expressed as zero-based line and character offset.
-    /// A position is between two characters like an 'insert' cursor in a editor.
-    ///
-    ///     // Line position in a document (zero-based).
-    ///     // Character offset on a line in a document (zero-based). The meaning of this
-    ///     // offset is determined by the negotiated `PositionEncodingKind`.
+    /// the lsp range of this span in `text`, columns in UTF-16 code units
     ///
-    ///     // If the character value is greater than the line length it defaults back
-    ///     // to the line length.
-    ///
-    /// zero based indexing
-    /// take a &str and try to convert the given span to lsp range
-    #[doc(hidden)]
-    #[doc = r" This is a doc comment."]
+    /// builds a `LineIndex` on every call, use one directly when converting
+    /// many spans of the same text or when another encoding was negotiated.
     pub fn into_lsp_range(self, text: &str) -> Result<Range, anyhow::Error> {
-        // Determine 0-indexed start and end lines.
-        let line_start = byte_to_line_idx(text, self.start) as u32;
-        let line_end = byte_to_line_idx(text, self.end) as u32;
-
-        // Determine 0-indexed character offsets.
-        let char_start_offset = byte_to_char_idx(text, self.start) as u32;
-        let char_end_offset = byte_to_char_idx(text, self.end) as u32;
-
-        // Compute the first char index for the start and end lines.
-        let line_start_first_idx = line_to_char_idx(text, line_start as usize) as u32;
-        let line_end_first_idx = line_to_char_idx(text, line_end as usize) as u32;
-
-        let start_diff = char_start_offset.saturating_sub(line_start_first_idx);
-        let end_diff = char_end_offset.saturating_sub(line_end_first_idx);
-
-        Ok(Range {
-            start: Position::new(line_start, start_diff),
-            end: Position::new(line_end, end_diff),
-        })
+        if self.start > self.end || self.end > text.len() {
+            anyhow::bail!("{self} is out of bounds for a text of {} bytes", text.len());
+        }
+        Ok(LineIndex::new(text).range(self, Encoding::Utf16))
     }
 }
 
@@ -0,0 +1,395 @@
+//! an index of the tags of a workspace
+//!
+//! like `:VimwikiRebuildTags` every `:tag:` of every page is recorded with the
+//! heading it belongs to. tags above the first heading belong to the whole
+//! page. the index of each wiki is cached in a JSON file in its root
+//! together with the modification time of each page, so a rebuild only
+//! parses pages which changed. the file is kept apart from `.vimwiki_tags`,
+//! which belongs to vimwiki itself. the cache needs the `serde` feature.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ast::{self, Heading, Tags};
+use crate::graph::PageId;
+use crate::kind::SyntaxKind;
+use crate::parser::{self, Node};
+use crate::span::Span;
+use crate::workspace::{Page, Workspace, relative_page};
+
+/// directory in the root of a wiki the cache is kept in
+pub const CACHE_DIR: &str = ".vimwiki-syntax";
+
+/// name of the cache file in [`CACHE_DIR`]
+pub const CACHE_FILE: &str = "tags.json";
+
+/// heading of the section `links` generates
+pub const SECTION: &str = "Generated Tags";
+
+/// version of the cache format, caches of other versions are not read
+const VERSION: u32 = 2;
+
+#[derive(Debug, Error)]
+pub enum TagError {
+    #[error("could not access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[cfg(feature = "serde")]
+    #[error("invalid tag cache: {0}")]
+    Cache(#[from] serde_json::Error),
+    #[error("tag cache has version {0}, expected {VERSION}")]
+    Version(u32),
+}
+
+/// an occurrence of a tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagEntry {
+    pub tag: String,
+    pub page: PageId,
+    /// title of the heading the tag is below, `None` for page tags
+    pub heading: Option<String>,
+    pub span: Span,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct CachedTag {
+    tag: String,
+    heading: Option<String>,
+    start: usize,
+    end: usize,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct CachedPage {
+    page: String,
+    /// nanoseconds since the epoch
+    modified: Option<u64>,
+    tags: Vec<CachedTag>,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct Cache {
+    version: u32,
+    pages: Vec<CachedPage>,
+}
+
+/// the tags of one page, in document order
+fn scan(id: &PageId, root: &Node) -> Vec<TagEntry> {
+    let mut entries = Vec::new();
+    let mut heading = None;
+    for block in root.children() {
+        if let Some(h) = Heading::cast(block) {
+            heading = Some(h.title());
+        }
+        for tags in ast::find_all(block, SyntaxKind::Tags)
+            .into_iter()
+            .filter_map(Tags::cast)
+        {
+            entries.extend(tags.tags().map(|tag| TagEntry {
+                tag: tag.text(),
+                page: id.clone(),
+                heading: heading.clone(),
+                span: tag.span(),
+            }));
+        }
+    }
+    entries
+}
+
+/// the cache file of the wiki with the root `root`
+pub fn cache_path(root: &Path) -> PathBuf {
+    root.join(CACHE_DIR).join(CACHE_FILE)
+}
+
+/// modification time of `path` in nanoseconds since the epoch
+fn modified(path: &Path) -> Option<u64> {
+    let time = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(time.duration_since(UNIX_EPOCH).ok()?.as_nanos() as u64)
+}
+
+/// a set of tags a page has to have
+///
+/// `work urgent | home` or `work & urgent | home` matches pages tagged with
+/// both `work` and `urgent`, and pages tagged with `home`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagQuery {
+    /// alternatives, each a list of tags which all have to be present
+    pub any: Vec<Vec<String>>,
+}
+
+impl TagQuery {
+    pub fn parse(query: &str) -> Self {
+        let any = query
+            .split('|')
+            .map(|all| {
+                all.split(|c: char| c == '&' || c.is_whitespace())
+                    .map(|tag| tag.trim_matches(':'))
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_owned)
+                    .collect::<Vec<_>>()
+            })
+            .filter(|all| !all.is_empty())
+            .collect();
+        Self { any }
+    }
+
+    pub fn matches(&self, tags: &BTreeSet<&str>) -> bool {
+        self.any
+            .iter()
+            .any(|all| all.iter().all(|tag| tags.contains(tag.as_str())))
+    }
+}
+
+/// every tag of a workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagIndex {
+    /// sorted by page, then in document order
+    entries: Vec<TagEntry>,
+    /// modification time of each indexed page, `None` if it was not read
+    /// from disk
+    modified: BTreeMap<PageId, Option<u64>>,
+}
+
+impl TagIndex {
+    /// indexes `pages`, the pages of each wiki of a workspace
+    pub fn build(pages: &[Vec<Page>]) -> Self {
+        let mut index = Self::default();
+        for (wiki, pages) in pages.iter().enumerate() {
+            for page in pages {
+                let id = PageId::new(wiki, &page.name);
+                index.entries.extend(scan(&id, &page.root));
+                index.modified.insert(id, None);
+            }
+        }
+        index.sort();
+        index
+    }
+
+    /// indexes the pages of `workspace` on disk, reusing the entries of
+    /// `cache` for pages which were not modified since
+    pub fn rebuild(workspace: &Workspace, cache: Option<&Self>) -> Self {
+        let mut cached: HashMap<&PageId, Vec<&TagEntry>> = HashMap::new();
+        if let Some(cache) = cache {
+            for entry in &cache.entries {
+                cached.entry(&entry.page).or_default().push(entry);
+            }
+        }
+        let mut index = Self::default();
+        for (wiki, config) in workspace.wikis.iter().enumerate() {
+            for name in config.pages() {
+                let id = PageId::new(wiki, name);
+                let path = config.path(&id.page);
+                let time = modified(&path);
+                let fresh = cache
+                    .is_some_and(|cache| time.is_some() && cache.modified.get(&id) == Some(&time));
+                if fresh {
+                    let entries = cached.get(&id).into_iter().flatten();
+                    index.entries.extend(entries.map(|&entry| entry.clone()));
+                } else if let Ok(text) = std::fs::read_to_string(&path) {
+                    index.entries.extend(scan(&id, &parser::parse(&text)));
+                } else {
+                    continue;
+                }
+                index.modified.insert(id, time);
+            }
+        }
+        index.sort();
+        index
+    }
+
+    fn sort(&mut self) {
+        self.entries
+            .sort_by(|a, b| (&a.page, a.span.start).cmp(&(&b.page, b.span.start)));
+    }
+
+    /// reads the cache of wiki number `wiki` written by `save`
+    #[cfg(feature = "serde")]
+    pub fn load(path: &Path, wiki: usize) -> Result<Self, TagError> {
+        let source = std::fs::read_to_string(path).map_err(|source| TagError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        let cache: Cache = serde_json::from_str(&source)?;
+        if cache.version != VERSION {
+            return Err(TagError::Version(cache.version));
+        }
+        let mut index = Self::default();
+        for page in cache.pages {
+            let id = PageId::new(wiki, page.page);
+            index
+                .entries
+                .extend(page.tags.into_iter().map(|tag| TagEntry {
+                    tag: tag.tag,
+                    page: id.clone(),
+                    heading: tag.heading,
+                    span: Span::new(tag.start, tag.end),
+                }));
+            index.modified.insert(id, page.modified);
+        }
+        index.sort();
+        Ok(index)
+    }
+
+    /// writes the pages of wiki number `wiki` to the cache file at `path`,
+    /// creating its directory
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: &Path, wiki: usize) -> Result<(), TagError> {
+        let pages = self
+            .modified
+            .iter()
+            .filter(|(id, _)| id.wiki == wiki)
+            .map(|(id, &modified)| CachedPage {
+                page: id.page.clone(),
+                modified,
+                tags: self
+                    .entries
+                    .iter()
+                    .filter(|entry| &entry.page == id)
+                    .map(|entry| CachedTag {
+                        tag: entry.tag.clone(),
+                        heading: entry.heading.clone(),
+                        start: entry.span.start,
+                        end: entry.span.end,
+                    })
+                    .collect(),
+            })
+            .collect();
+        let cache = Cache {
+            version: VERSION,
+            pages,
+        };
+        let json = serde_json::to_string(&cache)?;
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| std::fs::write(path, json))
+            .map_err(|source| TagError::Io {
+                path: path.to_owned(),
+                source,
+            })
+    }
+
+    /// reads the caches of every wiki of `workspace`
+    ///
+    /// wikis without a cache are left out, the errors of caches which could
+    /// not be read are returned along with the index.
+    #[cfg(feature = "serde")]
+    pub fn load_caches(workspace: &Workspace) -> (Self, Vec<TagError>) {
+        let mut index = Self::default();
+        let mut errors = Vec::new();
+        for (wiki, config) in workspace.wikis.iter().enumerate() {
+            match Self::load(&cache_path(&config.root), wiki) {
+                Ok(cache) => {
+                    index.entries.extend(cache.entries);
+                    index.modified.extend(cache.modified);
+                }
+                Err(TagError::Io { source, .. })
+                    if source.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => errors.push(err),
+            }
+        }
+        index.sort();
+        (index, errors)
+    }
+
+    /// writes the cache of every wiki of `workspace`
+    #[cfg(feature = "serde")]
+    pub fn save_caches(&self, workspace: &Workspace) -> Result<(), TagError> {
+        for (wiki, config) in workspace.wikis.iter().enumerate() {
+            self.save(&cache_path(&config.root), wiki)?;
+        }
+        Ok(())
+    }
+
+    /// every occurrence of every tag, by page and in document order
+    pub fn entries(&self) -> &[TagEntry] {
+        &self.entries
+    }
+
+    /// the names of all tags, sorted
+    pub fn tags(&self) -> BTreeSet<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.tag.as_str())
+            .collect()
+    }
+
+    /// every indexed page and its tags
+    fn page_tags(&self) -> BTreeMap<&PageId, BTreeSet<&str>> {
+        let mut pages: BTreeMap<_, BTreeSet<_>> = self
+            .modified
+            .keys()
+            .map(|id| (id, BTreeSet::new()))
+            .collect();
+        for entry in &self.entries {
+            pages
+                .entry(&entry.page)
+                .or_default()
+                .insert(entry.tag.as_str());
+        }
+        pages
+    }
+
+    /// the pages matching `query`, sorted
+    pub fn query(&self, query: &TagQuery) -> Vec<&PageId> {
+        self.page_tags()
+            .into_iter()
+            .filter(|(_, tags)| query.matches(tags))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// a `Generated Tags` section for page `from` of wiki `wiki`, listing a
+    /// link to every occurrence of `tags` or of all tags, grouped by tag
+    pub fn links(&self, wiki: usize, from: &str, tags: Option<&[&str]>) -> String {
+        let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for entry in self.entries.iter().filter(|entry| entry.page.wiki == wiki) {
+            if tags.is_some_and(|tags| !tags.contains(&entry.tag.as_str())) {
+                continue;
+            }
+            let page = if entry.page.page == from {
+                String::new()
+            } else {
+                relative_page(from, &entry.page.page)
+            };
+            let target = match &entry.heading {
+                Some(heading) => format!("{page}#{heading}"),
+                None if page.is_empty() => continue,
+                None => page,
+            };
+            let links = groups.entry(&entry.tag).or_default();
+            if !links.contains(&target) {
+                links.push(target);
+            }
+        }
+        let mut section = format!("= {SECTION} =\n");
+        for (tag, links) in groups {
+            section.push_str(&format!("\n== {tag} ==\n"));
+            for link in links {
+                section.push_str(&format!("- [[{link}]]\n"));
+            }
+        }
+        section
+    }
+
+    /// `source` of page `from` with its `Generated Tags` section replaced by
+    /// `links`, or with the section appended
+    pub fn update_links(
+        &self,
+        source: &str,
+        wiki: usize,
+        from: &str,
+        tags: Option<&[&str]>,
+    ) -> String {
+        ast::replace_section(source, SECTION, &self.links(wiki, from, tags))
+    }
+}
@@ -0,0 +1,214 @@
+//! tasks collected from the checkbox list items of a workspace
+//!
+//! every list item with a checkbox is a task. its text can carry tags and a
+//! due date written as `(due: 2026-10-20)`.
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use crate::ast::{self, CheckboxState, Heading, ListItem, Tags};
+use crate::diary::Date;
+use crate::graph::PageId;
+use crate::kind::SyntaxKind;
+use crate::line_index::LineIndex;
+use crate::parser::Node;
+use crate::span::Span;
+use crate::tags::TagQuery;
+use crate::workspace::Page;
+
+/// a checkbox list item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    pub page: PageId,
+    pub state: CheckboxState,
+    /// the text of the item without the due date
+    pub text: String,
+    /// title of the heading the item is below
+    pub heading: Option<String>,
+    /// texts of the list items the item is nested in, outermost first
+    pub path: Vec<String>,
+    pub tags: Vec<String>,
+    pub due: Option<Date>,
+    /// span of the list item, with its nested lists
+    pub span: Span,
+    /// 1 based line of the item
+    pub line: usize,
+}
+
+impl Task {
+    /// neither done nor rejected
+    pub fn is_open(&self) -> bool {
+        !matches!(self.state, CheckboxState::Done | CheckboxState::Rejected)
+    }
+}
+
+/// `open`, `started`, `half`, `mostly`, `done` or `rejected`
+pub fn state_name(state: CheckboxState) -> &'static str {
+    match state {
+        CheckboxState::Open => "open",
+        CheckboxState::Started => "started",
+        CheckboxState::Half => "half",
+        CheckboxState::Mostly => "mostly",
+        CheckboxState::Done => "done",
+        CheckboxState::Rejected => "rejected",
+    }
+}
+
+/// the due date in `text` and `text` without it
+pub fn due_date(text: &str) -> (Option<Date>, String) {
+    let Some(start) = text.find("(due:") else {
+        return (None, text.to_owned());
+    };
+    let Some(len) = text[start..].find(')') else {
+        return (None, text.to_owned());
+    };
+    let Some(date) = Date::parse(text[start + 5..start + len].trim()) else {
+        return (None, text.to_owned());
+    };
+    let rest = format!("{}{}", &text[..start], &text[start + len + 1..]);
+    (
+        Some(date),
+        rest.split_whitespace().collect::<Vec<_>>().join(" "),
+    )
+}
+
+/// the tasks of page `id`, in document order
+pub fn tasks(id: &PageId, root: &Node) -> Vec<Task> {
+    let index = LineIndex::new(&root.text());
+    let mut tasks = Vec::new();
+    let mut heading = None;
+    for block in root.children() {
+        if let Some(h) = Heading::cast(block) {
+            heading = Some(h.title());
+        } else if block.kind() == SyntaxKind::List {
+            let mut walk = Walk {
+                id,
+                heading: heading.as_deref(),
+                index: &index,
+                path: Vec::new(),
+                tasks: &mut tasks,
+            };
+            walk.list(block);
+        }
+    }
+    tasks
+}
+
+struct Walk<'a> {
+    id: &'a PageId,
+    heading: Option<&'a str>,
+    index: &'a LineIndex,
+    path: Vec<String>,
+    tasks: &'a mut Vec<Task>,
+}
+
+impl Walk<'_> {
+    fn list(&mut self, list: &Node) {
+        for item in list.children().iter().filter_map(ListItem::cast) {
+            let (due, text) = due_date(&item.text());
+            if let Some(checkbox) = item.checkbox() {
+                let tags = item
+                    .content()
+                    .flat_map(|node| ast::find_all(node, SyntaxKind::Tags))
+                    .filter_map(Tags::cast)
+                    .flat_map(|tags| tags.names())
+                    .collect();
+                self.tasks.push(Task {
+                    page: self.id.clone(),
+                    state: checkbox.state(),
+                    text: text.clone(),
+                    heading: self.heading.map(str::to_owned),
+                    path: self.path.clone(),
+                    tags,
+                    due,
+                    span: item.span(),
+                    line: self.index.line(item.span().start) + 1,
+                });
+            }
+            self.path.push(text);
+            for sublist in item.sublists() {
+                self.list(sublist);
+            }
+            self.path.pop();
+        }
+    }
+}
+
+/// the tasks of `pages`, the pages of each wiki of a workspace
+pub fn collect(pages: &[Vec<Page>]) -> Vec<Task> {
+    pages
+        .iter()
+        .enumerate()
+        .flat_map(|(wiki, pages)| {
+            pages
+                .iter()
+                .flat_map(move |page| tasks(&PageId::new(wiki, &page.name), &page.root))
+        })
+        .collect()
+}
+
+/// which tasks to keep, every field left empty keeps all tasks
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+    /// only tasks which are neither done nor rejected
+    pub open: bool,
+    /// only tasks whose tags match
+    pub tags: Option<TagQuery>,
+    /// only tasks due on or before this day
+    pub due: Option<Date>,
+    /// only tasks of pages whose name starts with this
+    pub page: Option<String>,
+}
+
+impl TaskFilter {
+    pub fn matches(&self, task: &Task) -> bool {
+        let tags = task.tags.iter().map(String::as_str).collect();
+        (!self.open || task.is_open())
+            && self.tags.as_ref().is_none_or(|query| query.matches(&tags))
+            && self
+                .due
+                .is_none_or(|by| task.due.is_some_and(|due| due <= by))
+            && self
+                .page
+                .as_deref()
+                .is_none_or(|prefix| task.page.page.starts_with(prefix))
+    }
+
+    pub fn apply<'a>(&self, tasks: &'a [Task]) -> Vec<&'a Task> {
+        tasks.iter().filter(|task| self.matches(task)).collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct JsonTask<'a> {
+    wiki: usize,
+    page: &'a str,
+    line: usize,
+    state: &'static str,
+    text: &'a str,
+    heading: Option<&'a str>,
+    path: &'a [String],
+    tags: &'a [String],
+    due: Option<String>,
+}
+
+/// `tasks` as a JSON array
+#[cfg(feature = "serde")]
+pub fn to_json(tasks: &[&Task]) -> String {
+    let tasks: Vec<_> = tasks
+        .iter()
+        .map(|task| JsonTask {
+            wiki: task.page.wiki,
+            page: &task.page.page,
+            line: task.line,
+            state: state_name(task.state),
+            text: &task.text,
+            heading: task.heading.as_deref(),
+            path: &task.path,
+            tags: &task.tags,
+            due: task.due.map(|due| due.to_string()),
+        })
+        .collect();
+    serde_json::to_string_pretty(&tasks).unwrap_or_default()
+}
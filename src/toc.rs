@@ -0,0 +1,86 @@
+//! table of contents generated from the headings of a page
+//!
+//! like `:VimwikiTOC` the contents are a `= Contents =` heading followed by a
+//! nested list of links to the headings. an existing contents section is
+//! replaced, otherwise it is inserted at the top of the page below the
+//! `%` placeholders.
+
+use crate::anchor::Anchors;
+use crate::ast::{self, Placeholder};
+use crate::parser::{self, Node};
+use crate::span::Span;
+
+/// heading of the contents section
+pub const HEADER: &str = "Contents";
+
+/// a heading listed in the contents
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TocEntry {
+    /// 1 to 6
+    pub level: usize,
+    pub title: String,
+    /// what follows the `#` in a link to the heading, unique in the page
+    pub anchor: String,
+    /// span of the heading
+    pub span: Span,
+}
+
+/// the headings of `root` down to level `max_depth`, except for the contents
+/// heading itself
+pub fn entries(root: &Node, max_depth: usize) -> Vec<TocEntry> {
+    Anchors::new(root)
+        .headings()
+        .filter(|anchor| anchor.level <= max_depth)
+        .filter(|anchor| anchor.name != HEADER && !anchor.name.is_empty())
+        .map(|anchor| TocEntry {
+            level: anchor.level,
+            title: anchor.name.clone(),
+            anchor: anchor.id.clone(),
+            span: anchor.span(),
+        })
+        .collect()
+}
+
+/// the contents section of `root`, `None` if it has no headings
+pub fn toc(root: &Node, max_depth: usize) -> Option<String> {
+    let entries = entries(root, max_depth);
+    let top = entries.iter().map(|entry| entry.level).min()?;
+    let mut toc = format!("= {HEADER} =\n");
+    for entry in &entries {
+        let indent = "  ".repeat(entry.level - top);
+        toc.push_str(&format!(
+            "{indent}- [[#{}|{}]]\n",
+            entry.anchor, entry.title
+        ));
+    }
+    Some(toc)
+}
+
+/// the edit bringing the contents of `root` up to date, as the replaced span
+/// and its new text. `None` if there are no headings or nothing changes
+pub fn edit(root: &Node, max_depth: usize) -> Option<(Span, String)> {
+    let toc = toc(root, max_depth)?;
+    // the contents are a flat section up to the next heading
+    let edit = match ast::section(root, HEADER, false) {
+        Some(span) => (span, toc),
+        None => {
+            // below the placeholders and the blank lines after them
+            let blocks = root.children();
+            let start = blocks
+                .iter()
+                .find(|b| Placeholder::cast(b).is_none() && !b.kind().is_trivia())
+                .map_or(root.span().end, |b| b.span().start);
+            (Span::new(start, start), format!("{toc}\n"))
+        }
+    };
+    let text = root.text();
+    (text.get(edit.0.start..edit.0.end) != Some(edit.1.as_str())).then_some(edit)
+}
+
+/// `source` with its contents section updated or inserted
+pub fn update(source: &str, max_depth: usize) -> String {
+    match edit(&parser::parse(source), max_depth) {
+        Some((span, text)) => format!("{}{text}{}", &source[..span.start], &source[span.end..]),
+        None => source.to_owned(),
+    }
+}
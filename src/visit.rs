@@ -0,0 +1,195 @@
+//! walking and rewriting the syntax tree
+//!
+//! a [`Visitor`] is called on entering and leaving every node of a tree in
+//! document order, through a hook per node kind. a [`Rewriter`] builds a new
+//! tree, replacing or removing nodes on the way.
+
+use crate::kind::SyntaxKind;
+use crate::parser::{Node, Repr};
+use crate::span::Span;
+
+/// how a walk goes on after entering a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Walk {
+    Continue,
+    /// the children of the node are not visited, it is still left
+    Skip,
+    /// nothing else is visited, not even the nodes entered so far are left
+    Stop,
+}
+
+macro_rules! visitor {
+    ($($kind:ident => $enter:ident, $leave:ident;)*) => {
+        /// hooks called while walking a tree with [`walk`]
+        ///
+        /// every hook does nothing by default. nodes of other kinds, which are
+        /// all single tokens, go to [`Visitor::enter_token`] and
+        /// [`Visitor::leave_token`].
+        pub trait Visitor {
+            /// dispatches to the hook of the kind of `node`
+            fn enter(&mut self, node: &Node) -> Walk {
+                match node.kind() {
+                    $(SyntaxKind::$kind => self.$enter(node),)*
+                    _ => self.enter_token(node),
+                }
+            }
+
+            /// dispatches to the hook of the kind of `node`
+            fn leave(&mut self, node: &Node) {
+                match node.kind() {
+                    $(SyntaxKind::$kind => self.$leave(node),)*
+                    _ => self.leave_token(node),
+                }
+            }
+
+            fn enter_token(&mut self, _node: &Node) -> Walk {
+                Walk::Continue
+            }
+
+            fn leave_token(&mut self, _node: &Node) {}
+
+            $(
+                #[doc = concat!("called on entering a `", stringify!($kind), "` node")]
+                fn $enter(&mut self, _node: &Node) -> Walk {
+                    Walk::Continue
+                }
+
+                #[doc = concat!("called on leaving a `", stringify!($kind), "` node")]
+                fn $leave(&mut self, _node: &Node) {}
+            )*
+        }
+    };
+}
+
+visitor! {
+    Root => enter_root, leave_root;
+    Heading => enter_heading, leave_heading;
+    Paragraph => enter_paragraph, leave_paragraph;
+    Bold => enter_bold, leave_bold;
+    Italic => enter_italic, leave_italic;
+    Strikethrough => enter_strikethrough, leave_strikethrough;
+    Code => enter_code, leave_code;
+    Superscript => enter_superscript, leave_superscript;
+    Subscript => enter_subscript, leave_subscript;
+    Math => enter_math, leave_math;
+    Link => enter_link, leave_link;
+    LinkTarget => enter_link_target, leave_link_target;
+    LinkDescription => enter_link_description, leave_link_description;
+    Transclusion => enter_transclusion, leave_transclusion;
+    Tags => enter_tags, leave_tags;
+    Tag => enter_tag, leave_tag;
+    List => enter_list, leave_list;
+    ListItem => enter_list_item, leave_list_item;
+    ListMarker => enter_list_marker, leave_list_marker;
+    Checkbox => enter_checkbox, leave_checkbox;
+    Table => enter_table, leave_table;
+    TableRow => enter_table_row, leave_table_row;
+    TableCell => enter_table_cell, leave_table_cell;
+    CodeBlock => enter_code_block, leave_code_block;
+    MathBlock => enter_math_block, leave_math_block;
+    Comment => enter_comment, leave_comment;
+    Placeholder => enter_placeholder, leave_placeholder;
+    HorizontalRule => enter_horizontal_rule, leave_horizontal_rule;
+    Error => enter_error, leave_error;
+}
+
+/// walks the subtree of `node` in document order, returns [`Walk::Stop`]
+/// if the visitor stopped the walk
+pub fn walk<V: Visitor + ?Sized>(node: &Node, visitor: &mut V) -> Walk {
+    match visitor.enter(node) {
+        Walk::Stop => return Walk::Stop,
+        Walk::Skip => {}
+        Walk::Continue => {
+            for child in node.children() {
+                if walk(child, visitor) == Walk::Stop {
+                    return Walk::Stop;
+                }
+            }
+        }
+    }
+    visitor.leave(node);
+    Walk::Continue
+}
+
+/// what becomes of a node in a rewritten tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rewrite {
+    /// the node stays, with its children rewritten
+    Keep,
+    /// the node and its subtree are replaced by these nodes, which are not
+    /// rewritten themselves. no nodes removes it
+    Replace(Vec<Node>),
+}
+
+/// hooks called while rewriting a tree with [`rewrite`]
+pub trait Rewriter {
+    /// called before the children of `node` are rewritten
+    fn rewrite(&mut self, _node: &Node) -> Rewrite {
+        Rewrite::Keep
+    }
+
+    /// called with every kept node once its children are rewritten, the
+    /// result takes its place
+    fn fold(&mut self, node: Node) -> Node {
+        node
+    }
+}
+
+/// a new tree from `root` with the changes of `rewriter`
+///
+/// the spans of the new tree are laid out again from the start of `root`,
+/// so they match the text of the new tree. a root which is replaced becomes
+/// an inner node of its kind holding the replacement.
+pub fn rewrite<R: Rewriter + ?Sized>(root: &Node, rewriter: &mut R) -> Node {
+    let mut nodes = rewrite_node(root, rewriter);
+    let new = if nodes.len() == 1 && nodes[0].kind() == root.kind() {
+        nodes.remove(0)
+    } else {
+        Node::inner(root.kind(), nodes)
+    };
+    layout(&new, root.span().start)
+}
+
+fn rewrite_node<R: Rewriter + ?Sized>(node: &Node, rewriter: &mut R) -> Vec<Node> {
+    if let Rewrite::Replace(nodes) = rewriter.rewrite(node) {
+        return nodes;
+    }
+    let node = match node.repr() {
+        Repr::InnerNode(_) => Node::inner(
+            node.kind(),
+            node.children()
+                .iter()
+                .flat_map(|child| rewrite_node(child, rewriter))
+                .collect(),
+        ),
+        _ => node.clone(),
+    };
+    vec![rewriter.fold(node)]
+}
+
+/// `node` with its leaves placed one after another from `start`, empty
+/// inner nodes get an empty span where the node before them ends
+fn layout(node: &Node, start: usize) -> Node {
+    match node.repr() {
+        Repr::InnerNode(_) if node.children().is_empty() => Node::empty(node.kind(), start),
+        Repr::InnerNode(_) => {
+            let mut offset = start;
+            let children = node
+                .children()
+                .iter()
+                .map(|child| {
+                    let child = layout(child, offset);
+                    offset = child.span().end;
+                    child
+                })
+                .collect();
+            Node::inner(node.kind(), children)
+        }
+        Repr::SyntaxNode(_) => {
+            let text = node.text();
+            let span = Span::new(start, start + text.len());
+            Node::leaf(node.kind(), text, span)
+        }
+        Repr::ErrorNode(_) => node.shifted(start as isize - node.span().start as isize),
+    }
+}
@@ -0,0 +1,397 @@
+//! wikis, their pages and links between them
+//!
+//! a workspace is a list of wikis like vimwiki's `g:vimwiki_list`. it is
+//! loaded from a TOML file:
+//!
+//! ```toml
+//! [[wiki]]
+//! name = "personal"
+//! root = "~/vimwiki"
+//!
+//! [[wiki]]
+//! name = "work"
+//! root = "work"
+//! extension = "md"
+//! index = "main"
+//! diary = "journal"
+//! ```
+//!
+//! relative roots are relative to the directory of the file.
+
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+use toml::de::{DeTable, DeValue};
+
+use crate::parser::{self, Node};
+
+/// name of the file a workspace is loaded from when a directory is opened
+pub const CONFIG_FILE: &str = "vimwiki.toml";
+
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error("could not read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid workspace config: {0}")]
+    Config(#[from] toml::de::Error),
+    #[error("invalid workspace config: {0}")]
+    Invalid(String),
+    #[error("the workspace config does not list any wiki")]
+    NoWiki,
+}
+
+fn default_extension() -> String {
+    "wiki".to_owned()
+}
+
+fn default_index() -> String {
+    "index".to_owned()
+}
+
+fn default_diary() -> String {
+    "diary".to_owned()
+}
+
+/// a page of a wiki, parsed
+#[derive(Debug, Clone)]
+pub struct Page {
+    /// path relative to the wiki root without extension, like `dir/page`
+    pub name: String,
+    pub root: Node,
+}
+
+impl Page {
+    pub fn new(name: impl Into<String>, text: &str) -> Self {
+        Self {
+            name: name.into(),
+            root: parser::parse(text),
+        }
+    }
+}
+
+/// a single wiki
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WikiConfig {
+    /// name used by `wn.name:` links
+    pub name: Option<String>,
+    pub root: PathBuf,
+    /// extension of pages, without the dot
+    pub extension: String,
+    /// page opened for the wiki and for links to a directory
+    pub index: String,
+    /// directory of diary pages, relative to the root
+    pub diary: String,
+    /// name of the diary index page inside of the diary directory
+    pub diary_index: String,
+}
+
+impl WikiConfig {
+    /// a wiki at `root` with vimwiki's defaults
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            name: None,
+            root: root.into(),
+            extension: default_extension(),
+            index: default_index(),
+            diary: default_diary(),
+            diary_index: default_diary(),
+        }
+    }
+
+    /// the names of all pages below the root, sorted
+    ///
+    /// hidden directories like `.git` are skipped, and so are symlinks to
+    /// directories, which could lead back up into the wiki.
+    pub fn pages(&self) -> Vec<String> {
+        fn walk(dir: &Path, extension: &str, files: &mut Vec<PathBuf>) {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                return;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                // the type of the entry itself, symlinks are not followed
+                let Ok(kind) = entry.file_type() else {
+                    continue;
+                };
+                if kind.is_dir() {
+                    if !entry.file_name().to_string_lossy().starts_with('.') {
+                        walk(&path, extension, files);
+                    }
+                } else if path.extension().is_some_and(|ext| ext == extension) && path.is_file() {
+                    files.push(path);
+                }
+            }
+        }
+        let mut files = Vec::new();
+        walk(&self.root, &self.extension, &mut files);
+        let mut pages: Vec<_> = files.iter().filter_map(|path| self.page(path)).collect();
+        pages.sort();
+        pages
+    }
+
+    /// reads and parses every page, unreadable files are skipped
+    pub fn load(&self) -> Vec<Page> {
+        self.pages()
+            .into_iter()
+            .filter_map(|name| {
+                let text = std::fs::read_to_string(self.path(&name)).ok()?;
+                Some(Page::new(name, &text))
+            })
+            .collect()
+    }
+
+    /// the file of page `name`
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.root.join(format!("{name}.{}", self.extension))
+    }
+
+    /// the page stored in `path`, `None` if it is not a page of this wiki
+    pub fn page(&self, path: &Path) -> Option<String> {
+        if path.extension()? != self.extension.as_str() {
+            return None;
+        }
+        let relative = path.strip_prefix(&self.root).ok()?.with_extension("");
+        let parts: Vec<_> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("/"))
+    }
+
+    /// whether page `name` lives in the diary
+    pub fn is_diary(&self, name: &str) -> bool {
+        name.strip_prefix(self.diary.trim_matches('/'))
+            .is_some_and(|rest| rest.starts_with('/'))
+    }
+}
+
+/// page name a link target written in page `from` points to
+///
+/// targets are relative to the directory of `from` unless they start with `/`,
+/// an empty target points to `from` itself.
+pub fn resolve_page(from: &str, target: &str) -> String {
+    let target = target.trim();
+    if target.is_empty() {
+        return from.to_owned();
+    }
+    let (mut parts, target): (Vec<&str>, &str) = match target.strip_prefix('/') {
+        Some(absolute) => (Vec::new(), absolute),
+        None => {
+            let mut dir: Vec<_> = from.split('/').collect();
+            dir.pop();
+            (dir, target)
+        }
+    };
+    for part in target.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// the shortest link target leading from page `from` to page `to`
+pub fn relative_page(from: &str, to: &str) -> String {
+    let mut dir: Vec<_> = from.split('/').collect();
+    dir.pop();
+    let to: Vec<_> = to.split('/').collect();
+    let common = dir
+        .iter()
+        .zip(&to)
+        .take_while(|(a, b)| a == b)
+        .count()
+        .min(to.len().saturating_sub(1));
+    let mut parts = vec![".."; dir.len() - common];
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+/// a page of a wiki of the workspace, and the anchor a link points to in it
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    /// index of the wiki in the workspace
+    pub wiki: usize,
+    pub page: String,
+    pub anchor: Option<String>,
+}
+
+/// the string under the first of `keys` in `table` which is set
+fn string(table: &DeTable, keys: &[&str]) -> Result<Option<String>, WorkspaceError> {
+    let Some((key, value)) = keys
+        .iter()
+        .find_map(|key| table.get(*key).map(|value| (key, value.get_ref())))
+    else {
+        return Ok(None);
+    };
+    match value {
+        DeValue::String(value) => Ok(Some(value.to_string())),
+        _ => Err(WorkspaceError::Invalid(format!("`{key}` must be a string"))),
+    }
+}
+
+/// a `[[wiki]]` table, keys vimwiki uses in `g:vimwiki_list` work as well
+fn wiki_config(table: &DeTable) -> Result<WikiConfig, WorkspaceError> {
+    let root = string(table, &["root", "path"])?
+        .ok_or_else(|| WorkspaceError::Invalid("a wiki has no `root`".to_owned()))?;
+    Ok(WikiConfig {
+        name: string(table, &["name"])?,
+        root: root.into(),
+        extension: string(table, &["extension", "ext"])?.unwrap_or_else(default_extension),
+        index: string(table, &["index"])?.unwrap_or_else(default_index),
+        diary: string(table, &["diary", "diary_rel_path"])?.unwrap_or_else(default_diary),
+        diary_index: string(table, &["diary_index"])?.unwrap_or_else(default_diary),
+    })
+}
+
+/// all wikis the user works with
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Workspace {
+    pub wikis: Vec<WikiConfig>,
+}
+
+impl Workspace {
+    /// a workspace of a single wiki at `root`
+    pub fn single(root: impl Into<PathBuf>) -> Self {
+        Self {
+            wikis: vec![WikiConfig::new(root)],
+        }
+    }
+
+    /// parses a TOML config, relative roots are joined to `base`
+    pub fn from_toml(source: &str, base: &Path) -> Result<Self, WorkspaceError> {
+        let config = DeTable::parse(source).map_err(|mut err| {
+            err.set_input(Some(source));
+            err
+        })?;
+        let wikis = match config.get_ref().get("wiki").map(|wiki| wiki.get_ref()) {
+            None => Vec::new(),
+            Some(DeValue::Array(wikis)) => wikis
+                .iter()
+                .map(|wiki| match wiki.get_ref() {
+                    DeValue::Table(table) => wiki_config(table),
+                    _ => Err(WorkspaceError::Invalid("`wiki` must be tables".to_owned())),
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            Some(_) => {
+                return Err(WorkspaceError::Invalid(
+                    "`wiki` must be an array of tables".to_owned(),
+                ));
+            }
+        };
+        if wikis.is_empty() {
+            return Err(WorkspaceError::NoWiki);
+        }
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let wikis = wikis
+            .into_iter()
+            .map(|mut wiki| {
+                if let (Ok(rest), Some(home)) = (wiki.root.strip_prefix("~"), &home) {
+                    wiki.root = home.join(rest);
+                } else if wiki.root.is_relative() {
+                    wiki.root = base.join(&wiki.root);
+                }
+                wiki.extension = wiki.extension.trim_start_matches('.').to_owned();
+                wiki
+            })
+            .collect();
+        Ok(Self { wikis })
+    }
+
+    /// loads the TOML config at `path`
+    pub fn load(path: &Path) -> Result<Self, WorkspaceError> {
+        let source = std::fs::read_to_string(path).map_err(|source| WorkspaceError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+        Self::from_toml(&source, path.parent().unwrap_or(Path::new("")))
+    }
+
+    /// the workspace of directory `dir`, from its config file if it has one
+    pub fn discover(dir: &Path) -> Result<Self, WorkspaceError> {
+        let config = dir.join(CONFIG_FILE);
+        if config.is_file() {
+            Self::load(&config)
+        } else {
+            Ok(Self::single(dir))
+        }
+    }
+
+    pub fn wiki(&self, index: usize) -> Option<&WikiConfig> {
+        self.wikis.get(index)
+    }
+
+    /// index of the wiki called `name`
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.wikis
+            .iter()
+            .position(|wiki| wiki.name.as_deref() == Some(name))
+    }
+
+    /// the wiki and page stored at `path`, nested wikis take precedence
+    pub fn locate(&self, path: &Path) -> Option<(usize, String)> {
+        self.wikis
+            .iter()
+            .enumerate()
+            .filter_map(|(index, wiki)| {
+                Some((index, wiki.page(path)?, wiki.root.components().count()))
+            })
+            .max_by_key(|(_, _, depth)| *depth)
+            .map(|(index, page, _)| (index, page))
+    }
+
+    /// reads and parses the pages of every wiki, in the order of the wikis
+    pub fn load_pages(&self) -> Vec<Vec<Page>> {
+        self.wikis.iter().map(WikiConfig::load).collect()
+    }
+
+    /// the file of page `page` of wiki `wiki`
+    pub fn path(&self, wiki: usize, page: &str) -> Option<PathBuf> {
+        Some(self.wiki(wiki)?.path(page))
+    }
+
+    /// where a link target written in page `from` of wiki `wiki` points to
+    ///
+    /// understands `wikiN:` and `wn.name:` interwiki links and `diary:` links,
+    /// other schemes like `https:` are not pages and give `None`. a target
+    /// ending in `/` points to the index of that directory.
+    pub fn resolve(&self, wiki: usize, from: &str, target: &str) -> Option<Location> {
+        let (target, anchor) = match target.split_once('#') {
+            Some((target, anchor)) => (target, Some(anchor.trim().to_owned())),
+            None => (target, None),
+        };
+        let target = target.trim();
+        let (wiki, page) = match target.split_once(':') {
+            Some((scheme, rest)) if !scheme.contains('/') => {
+                if let Some(number) = scheme.strip_prefix("wiki")
+                    && let Ok(number) = number.parse::<usize>()
+                {
+                    (number, resolve_page("", &format!("/{rest}")))
+                } else if let Some(name) = scheme.strip_prefix("wn.") {
+                    (self.find(name)?, resolve_page("", &format!("/{rest}")))
+                } else if scheme == "diary" {
+                    let diary = self.wiki(wiki)?.diary.trim_matches('/');
+                    (wiki, resolve_page("", &format!("/{diary}/{rest}")))
+                } else {
+                    return None;
+                }
+            }
+            _ => (wiki, resolve_page(from, target)),
+        };
+        let config = self.wiki(wiki)?;
+        let page = if page.is_empty() {
+            config.index.clone()
+        } else if target.ends_with('/') {
+            format!("{page}/{}", config.index)
+        } else {
+            page
+        };
+        Some(Location { wiki, page, anchor })
+    }
+}
@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::anchor::{AnchorKind, Anchors, html_id};
+    use vimwiki_syntax::export::Html;
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::span::Span;
+
+    const PAGE: &str = "= Chapter One =\n:draft:\n== Setup ==\ntext\n= Chapter Two =\n\
+        == Setup ==\n=== Deep Dive ===\n:todo:later:\n== Setup ==\n";
+
+    #[test]
+    fn computes_ids() {
+        let root = parse(PAGE);
+        let anchors = Anchors::new(&root);
+        let all: Vec<_> = anchors
+            .iter()
+            .map(|a| (a.kind, a.id.as_str(), a.full_path(), a.level))
+            .collect();
+        assert_eq!(
+            all,
+            [
+                (
+                    AnchorKind::Heading,
+                    "Chapter One",
+                    "Chapter One".to_owned(),
+                    1
+                ),
+                (AnchorKind::Tag, "draft", "Chapter One#draft".to_owned(), 0),
+                (
+                    AnchorKind::Heading,
+                    "Setup",
+                    "Chapter One#Setup".to_owned(),
+                    2
+                ),
+                (
+                    AnchorKind::Heading,
+                    "Chapter Two",
+                    "Chapter Two".to_owned(),
+                    1
+                ),
+                (
+                    AnchorKind::Heading,
+                    "Setup-2",
+                    "Chapter Two#Setup".to_owned(),
+                    2
+                ),
+                (
+                    AnchorKind::Heading,
+                    "Deep Dive",
+                    "Chapter Two#Setup#Deep Dive".to_owned(),
+                    3
+                ),
+                (
+                    AnchorKind::Tag,
+                    "todo",
+                    "Chapter Two#Setup#Deep Dive#todo".to_owned(),
+                    0
+                ),
+                (
+                    AnchorKind::Tag,
+                    "later",
+                    "Chapter Two#Setup#Deep Dive#later".to_owned(),
+                    0
+                ),
+                (
+                    AnchorKind::Heading,
+                    "Setup-3",
+                    "Chapter Two#Setup".to_owned(),
+                    2
+                ),
+            ]
+        );
+        assert_eq!(anchors.headings().count(), 6);
+        assert_eq!(anchors.iter().nth(5).unwrap().html_id(), "Deep-Dive");
+        assert_eq!(html_id("  a  b\tc "), "a-b-c");
+
+        // a tag does not count towards the headings of the same name
+        let root = parse(":foo:\n= foo =\n= foo =\n:foo:\n");
+        let ids: Vec<_> = Anchors::new(&root)
+            .iter()
+            .map(|a| (a.kind, a.id.clone()))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                (AnchorKind::Tag, "foo".to_owned()),
+                (AnchorKind::Heading, "foo".to_owned()),
+                (AnchorKind::Heading, "foo-2".to_owned()),
+                (AnchorKind::Tag, "foo-2".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_anchors() {
+        let root = parse(PAGE);
+        let anchors = Anchors::new(&root);
+        let id = |anchor: &str| anchors.resolve(anchor).map(|a| a.id.clone());
+
+        assert_eq!(id("Setup").as_deref(), Some("Setup"));
+        assert_eq!(id("#Setup").as_deref(), Some("Setup"));
+        assert_eq!(id(" Setup-3 ").as_deref(), Some("Setup-3"));
+        assert_eq!(id("Chapter Two#Setup").as_deref(), Some("Setup-2"));
+        assert_eq!(
+            id("Chapter Two#Deep Dive#Setup").as_deref(),
+            Some("Setup-3")
+        );
+        assert_eq!(id("Chapter Two#Setup-3").as_deref(), Some("Setup-3"));
+        assert_eq!(id("later").as_deref(), Some("later"));
+        assert_eq!(id("Deep Dive#Chapter One"), None);
+        assert_eq!(id("Missing"), None);
+        assert_eq!(id("#"), None);
+
+        let setup = anchors.resolve("Chapter Two#Setup").unwrap();
+        assert_eq!(setup.node.kind(), SyntaxKind::Heading);
+        assert_eq!(setup.span(), Span::new(57, 69));
+        assert_eq!(&PAGE[setup.span().start..setup.span().end], "== Setup ==\n");
+        let tag = anchors.resolve("todo").unwrap();
+        assert_eq!(tag.node.kind(), SyntaxKind::Tag);
+        assert_eq!(&PAGE[tag.span().start..tag.span().end], "todo");
+    }
+
+    #[test]
+    fn keeps_tags_apart_from_headings() {
+        let root = parse(":foo:\n= foo =\n== bar ==\n:foo:\n");
+        let anchors = Anchors::new(&root);
+        let ids: Vec<_> = anchors.iter().map(|a| a.html_id()).collect();
+        assert_eq!(ids, ["tag-foo", "foo", "bar", "tag-foo-2"]);
+
+        // the heading wins even though the tag comes first
+        let foo = anchors.resolve("foo").unwrap();
+        assert_eq!(foo.kind, AnchorKind::Heading);
+        assert_eq!(
+            anchors.resolve("foo#bar").unwrap().kind,
+            AnchorKind::Heading
+        );
+        assert_eq!(anchors.resolve("foo-2").unwrap().kind, AnchorKind::Tag);
+
+        let html = Html::new().render(&root);
+        assert_eq!(html.matches("id=\"foo\"").count(), 1);
+        assert!(html.contains("<span class=\"tag\" id=\"tag-foo\">foo</span>"));
+    }
+}
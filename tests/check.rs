@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use vimwiki_syntax::check::{Problem, Report, check, check_pages};
+    use vimwiki_syntax::span::Span;
+    use vimwiki_syntax::workspace::{Page, WikiConfig, Workspace};
+
+    fn problems(reports: &[Report]) -> Vec<(usize, &str, &Problem)> {
+        reports
+            .iter()
+            .map(|r| (r.wiki, r.page.as_str(), &r.problem))
+            .collect()
+    }
+
+    #[test]
+    fn reports_broken_links() {
+        let mut work = WikiConfig::new("/work");
+        work.name = Some("work".to_owned());
+        let workspace = Workspace {
+            wikis: vec![WikiConfig::new("/wiki"), work],
+        };
+        let index = "= Index =\n[[todo]] [[missing]] [[todo#Next]] [[todo#Later]]\n\
+            [[todo#Next#Soon]] [[todo#Soon#Next]] [[wiki1:plans]] [[wiki7:plans]]\n\
+            [[wn.work:plans#Goals]] [[https://example.com]] [[#Index]]\n";
+        let pages = vec![
+            vec![
+                Page::new("index", index),
+                Page::new("todo", "= Next =\n== Soon ==\n"),
+                Page::new("lonely", "[[lonely]]\n"),
+            ],
+            vec![Page::new("index", ""), Page::new("plans", "= Goals =\n")],
+        ];
+        let reports = check_pages(&workspace, &pages);
+        assert_eq!(
+            problems(&reports),
+            [
+                (0, "index", &Problem::MissingPage("missing".to_owned())),
+                (
+                    0,
+                    "index",
+                    &Problem::MissingAnchor {
+                        page: "todo".to_owned(),
+                        anchor: "Later".to_owned()
+                    }
+                ),
+                (
+                    0,
+                    "index",
+                    &Problem::MissingAnchor {
+                        page: "todo".to_owned(),
+                        anchor: "Soon#Next".to_owned()
+                    }
+                ),
+                (0, "index", &Problem::MissingWiki("wiki7".to_owned())),
+                (0, "lonely", &Problem::Orphan),
+            ]
+        );
+        assert_eq!(reports[0].span, Span::new(21, 28));
+        assert_eq!(reports[4].span, Span::new(0, 0));
+        assert!(reports[0].problem.is_error());
+        assert!(!reports[4].problem.is_error());
+    }
+
+    #[test]
+    fn checks_files_on_disk() {
+        let dir = std::env::temp_dir().join(format!("vimwiki-check-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("notes")).unwrap();
+        std::fs::write(dir.join("notes/image.png"), "").unwrap();
+        std::fs::write(
+            dir.join("index.wiki"),
+            "[[notes/page]] [[file:notes/image.png]] [[local:notes/gone.png]]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("notes/page.wiki"),
+            "[[file:image.png]] [[/index]]\n",
+        )
+        .unwrap();
+
+        let reports = check(&Workspace::single(&dir));
+        assert_eq!(
+            problems(&reports),
+            [(
+                0,
+                "index",
+                &Problem::MissingFile(dir.join("notes/gone.png"))
+            )]
+        );
+        assert_eq!(
+            reports[0].problem.to_string(),
+            format!(
+                "file `{}` does not exist",
+                Path::new(&dir).join("notes/gone.png").display()
+            )
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
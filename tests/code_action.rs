@@ -0,0 +1,116 @@
+#[cfg(test)]
+#[cfg(feature = "lsp")]
+mod test {
+    use tower_lsp::lsp_types::{Position, Range, TextEdit};
+    use vimwiki_syntax::lsp::code_action::{
+        convert_list, fix, missing_page, nth_marker, renumber_list, toggle_checkbox,
+    };
+    use vimwiki_syntax::lsp::{Document, Page};
+    use vimwiki_syntax::parser::parse;
+
+    fn edit(line: u32, start: u32, end: u32, text: &str) -> TextEdit {
+        TextEdit {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            new_text: text.to_owned(),
+        }
+    }
+
+    fn fixes(source: &str) -> Vec<(String, TextEdit)> {
+        let document = Document::new(source);
+        document
+            .root
+            .error_nodes()
+            .into_iter()
+            .filter_map(|error| fix(&document, error))
+            .collect()
+    }
+
+    #[test]
+    fn fixes_unclosed_markup() {
+        assert_eq!(
+            fixes("some *bold text\n"),
+            [("Close with `*`".to_owned(), edit(0, 15, 15, "*"))]
+        );
+        assert_eq!(
+            fixes("*bold *\n"),
+            [("Remove trailing whitespace".to_owned(), edit(0, 5, 6, ""))]
+        );
+        assert_eq!(
+            fixes("see [[page|desc \n"),
+            [("Close link with `]]`".to_owned(), edit(0, 15, 15, "]]"))]
+        );
+        assert_eq!(
+            fixes("{{{\ncode"),
+            [(
+                "Close code block with `}}}`".to_owned(),
+                edit(1, 4, 4, "\n}}}\n")
+            )]
+        );
+    }
+
+    #[test]
+    fn toggles_checkboxes() {
+        let source = "- [ ] parent\n  - [ ] one\n  - [X] two\n- no box\n";
+        let document = Document::new(source);
+        assert_eq!(
+            toggle_checkbox(&document, 20),
+            Some(vec![edit(0, 3, 4, "X"), edit(1, 5, 6, "X")])
+        );
+        // toggling the parent carries the children along
+        assert_eq!(
+            toggle_checkbox(&document, 2),
+            Some(vec![edit(0, 3, 4, "X"), edit(1, 5, 6, "X")])
+        );
+        assert_eq!(
+            toggle_checkbox(&document, 31),
+            Some(vec![edit(2, 5, 6, " ")])
+        );
+        assert_eq!(
+            toggle_checkbox(&document, 40),
+            Some(vec![edit(3, 2, 2, "[ ] ")])
+        );
+        assert_eq!(toggle_checkbox(&Document::new("text\n"), 1), None);
+    }
+
+    #[test]
+    fn converts_and_renumbers_lists() {
+        assert_eq!(nth_marker("1.", 3), "3.");
+        assert_eq!(nth_marker("a)", 28), "ab)");
+        assert_eq!(nth_marker("I)", 4), "IV)");
+        assert_eq!(nth_marker("-", 4), "-");
+
+        let document = Document::new("1. one\n3. two\n1. three\n");
+        assert_eq!(
+            renumber_list(&document, 0),
+            Some(vec![edit(1, 0, 2, "2."), edit(2, 0, 2, "3.")])
+        );
+        assert_eq!(
+            convert_list(&document, 0, "-"),
+            Some(vec![
+                edit(0, 0, 2, "-"),
+                edit(1, 0, 2, "-"),
+                edit(2, 0, 2, "-")
+            ])
+        );
+
+        let bullets = Document::new("- one\n- two\n");
+        assert_eq!(renumber_list(&bullets, 0), None);
+        assert_eq!(
+            convert_list(&bullets, 0, "1."),
+            Some(vec![edit(0, 0, 1, "1."), edit(1, 0, 1, "2.")])
+        );
+    }
+
+    #[test]
+    fn finds_missing_pages() {
+        let pages = vec![Page::new("dir/index", ""), Page::new("dir/todo", "")];
+        let source = "[[todo]] [[new page]] [[https://example.com]]\n";
+        let root = parse(source);
+        assert_eq!(missing_page(&root, "dir/index", 3, &pages), None);
+        assert_eq!(
+            missing_page(&root, "dir/index", 12, &pages),
+            Some("dir/new page".to_owned())
+        );
+        assert_eq!(missing_page(&root, "dir/index", 30, &pages), None);
+    }
+}
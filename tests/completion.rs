@@ -0,0 +1,93 @@
+#[cfg(test)]
+#[cfg(feature = "lsp")]
+mod test {
+    use tower_lsp::lsp_types::{CompletionTextEdit, Position, Range};
+    use vimwiki_syntax::lsp::completion::{Context, complete, context};
+    use vimwiki_syntax::lsp::{Document, Page};
+    use vimwiki_syntax::span::Span;
+
+    fn pages() -> Vec<Page> {
+        vec![
+            Page::new("index", "%title Home\n= Welcome =\n:home:start:\n"),
+            Page::new(
+                "projects/vimwiki",
+                "= Roadmap =\n== Next Steps ==\n:project:\n",
+            ),
+        ]
+    }
+
+    #[test]
+    fn completion_context() {
+        assert_eq!(
+            context("see [[proj", 10),
+            Some(Context::Page {
+                span: Span::new(6, 10)
+            })
+        );
+        assert_eq!(
+            context("see [[index#We", 14),
+            Some(Context::Anchor {
+                page: "index".to_owned(),
+                span: Span::new(12, 14)
+            })
+        );
+        assert_eq!(
+            context("text :pro", 9),
+            Some(Context::Tag {
+                span: Span::new(6, 9)
+            })
+        );
+        assert_eq!(context("[[done]] plain", 14), None);
+        assert_eq!(context("[[page|descr", 12), None);
+    }
+
+    #[test]
+    fn completes_pages_with_titles() {
+        let document = Document::new("see [[in");
+        let items = complete(&document, Position::new(0, 8), "", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["index", "projects/vimwiki"]);
+        assert_eq!(items[0].detail.as_deref(), Some("Home"));
+        assert_eq!(
+            items[0].text_edit,
+            Some(CompletionTextEdit::Edit(tower_lsp::lsp_types::TextEdit {
+                range: Range::new(Position::new(0, 6), Position::new(0, 8)),
+                new_text: "index".to_owned(),
+            }))
+        );
+    }
+
+    #[test]
+    fn completes_relative_to_the_current_page() {
+        let document = Document::new("[[\n[[vimwiki#");
+        let items = complete(&document, Position::new(0, 2), "projects/other", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["../index", "vimwiki"]);
+
+        let items = complete(&document, Position::new(1, 10), "projects/other", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["Roadmap", "Next Steps", "project"]);
+    }
+
+    #[test]
+    fn completes_anchors_and_tags() {
+        let document = Document::new("= Local =\n[[projects/vimwiki#\n[[#\n:p");
+        let items = complete(&document, Position::new(1, 19), "", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["Roadmap", "Next Steps", "project"]);
+
+        let items = complete(&document, Position::new(2, 3), "", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["Local"]);
+
+        // duplicate headings complete to their unique ids
+        let duplicates = Document::new("= Setup =\n= Setup =\n[[#");
+        let items = complete(&duplicates, Position::new(2, 3), "", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["Setup", "Setup-2"]);
+
+        let items = complete(&document, Position::new(3, 2), "", &pages());
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, ["home", "project", "start"]);
+    }
+}
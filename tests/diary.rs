@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::diary::{Date, Diary, Entry};
+    use vimwiki_syntax::workspace::{Page, WikiConfig};
+
+    fn date(text: &str) -> Date {
+        Date::parse(text).unwrap()
+    }
+
+    fn diary() -> Diary {
+        Diary::new(
+            &WikiConfig::new("/wiki"),
+            &[
+                Page::new("index", "= Index =\n"),
+                Page::new("diary/diary", "= Diary =\n"),
+                Page::new("diary/2026-10-19", "%title Monday\n= Release day =\ntext\n"),
+                Page::new("diary/2026-10-02", "no heading\n"),
+                Page::new("diary/2026-09-30", "= Planning =\n"),
+                Page::new("diary/2025-12-31", "== New Year's Eve ==\n"),
+                Page::new("diary/notes", "= Notes =\n"),
+                Page::new("diary/2026-02-30", "= Invalid =\n"),
+                Page::new("other/2026-01-01", "= Elsewhere =\n"),
+            ],
+        )
+    }
+
+    #[test]
+    fn parses_dates() {
+        assert_eq!(Date::parse("2026-10-19"), Date::new(2026, 10, 19));
+        assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+        assert_eq!(Date::parse("2026-02-29"), None);
+        assert_eq!(Date::parse("1900-02-29"), None);
+        assert_eq!(Date::parse("2026-13-01"), None);
+        assert_eq!(Date::parse("2026-1-01"), None);
+        assert_eq!(Date::parse("2026-10-19-x"), None);
+        assert_eq!(Date::parse("+026-10-19"), None);
+        assert_eq!(date("2026-10-19").month_name(), "October");
+
+        assert_eq!(Date::from_days(0), date("1970-01-01"));
+        assert_eq!(Date::from_days(20_545), date("2026-04-02"));
+        assert_eq!(Date::from_days(-1), date("1969-12-31"));
+        assert_eq!(Date::from_days(11_016), date("2000-02-29"));
+        assert!(Date::today() > date("2026-01-01"));
+    }
+
+    #[test]
+    fn enumerates_entries() {
+        let diary = diary();
+        let entries: Vec<_> = diary
+            .entries()
+            .iter()
+            .map(|e| (e.date.to_string(), e.page.as_str(), e.caption.as_deref()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (
+                    "2025-12-31".to_owned(),
+                    "diary/2025-12-31",
+                    Some("New Year's Eve")
+                ),
+                (
+                    "2026-09-30".to_owned(),
+                    "diary/2026-09-30",
+                    Some("Planning")
+                ),
+                ("2026-10-02".to_owned(), "diary/2026-10-02", None),
+                (
+                    "2026-10-19".to_owned(),
+                    "diary/2026-10-19",
+                    Some("Release day")
+                ),
+            ]
+        );
+        assert_eq!(diary.index(), "diary/diary");
+        assert_eq!(diary.page(date("2026-10-20")), "diary/2026-10-20");
+    }
+
+    #[test]
+    fn navigates_entries() {
+        let diary = diary();
+        let page = |entry: Option<&Entry>| entry.map(|e| e.page.clone());
+        assert_eq!(
+            page(diary.get(date("2026-10-02"))).as_deref(),
+            Some("diary/2026-10-02")
+        );
+        assert_eq!(page(diary.get(date("2026-10-03"))), None);
+        assert_eq!(
+            page(diary.previous(date("2026-10-19"))).as_deref(),
+            Some("diary/2026-10-02")
+        );
+        assert_eq!(
+            page(diary.next(date("2026-10-02"))).as_deref(),
+            Some("diary/2026-10-19")
+        );
+        assert_eq!(
+            page(diary.next(date("2026-01-01"))).as_deref(),
+            Some("diary/2026-09-30")
+        );
+        assert_eq!(page(diary.previous(date("2025-12-31"))), None);
+        assert_eq!(page(diary.next(date("2026-10-19"))), None);
+    }
+
+    #[test]
+    fn generates_index() {
+        let diary = diary();
+        let links = "= Diary =\n\n\
+            == 2026 ==\n\n\
+            === October ===\n\
+            - [[2026-10-19|Release day]]\n\
+            - [[2026-10-02]]\n\n\
+            === September ===\n\
+            - [[2026-09-30|Planning]]\n\n\
+            == 2025 ==\n\n\
+            === December ===\n\
+            - [[2025-12-31|New Year's Eve]]\n";
+        assert_eq!(diary.links(), links);
+        assert_eq!(diary.update_index(""), links);
+        assert_eq!(
+            diary.update_index("%title Journal\n\n= Diary =\n\n== 2020 ==\n- [[old]]\n"),
+            format!("%title Journal\n\n{links}")
+        );
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::ast::{Link, find_all};
+    use vimwiki_syntax::export::{Format, Html, export, html};
+    #[cfg(feature = "serde")]
+    use vimwiki_syntax::export::{json, pandoc};
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::parser::parse;
+
+    const PAGE: &str = "%title Notes\n= Intro =\n\
+        Some *bold* and `a<b` with [[other#Part Two|a link]] :todo:\n\
+        | a | b |\n|---|---|\n| c | d |\n\
+        - [X] done\n  1. sub\n\
+        {{{rust\nfn main() {}\n}}}\n";
+
+    #[test]
+    fn exports_html() {
+        assert_eq!(
+            Html::new().render(&parse(PAGE)),
+            "<h1 id=\"Intro\">Intro</h1>\n\
+             <p>\nSome <strong>bold</strong> and <code>a&lt;b</code> with \
+             <a href=\"other.html#Part-Two\">a link</a> \
+             <span class=\"tag\" id=\"tag-todo\">todo</span>\n</p>\n\
+             <table>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n\
+             <tbody>\n<tr>\n<td>c</td>\n<td>d</td>\n</tr>\n</tbody>\n</table>\n\
+             <ul>\n<li class=\"done4\">done\n<ol>\n<li>sub</li>\n</ol>\n</li>\n</ul>\n\
+             <pre><code class=\"language-rust\">fn main() {}\n</code></pre>\n"
+        );
+
+        let standalone = Html::new()
+            .standalone(true)
+            .toc(Some(6))
+            .css("style.css")
+            .links(|link: &Link| (link.page() == "other").then(|| "elsewhere.html".to_owned()))
+            .render(&parse(PAGE));
+        assert!(standalone.starts_with("<!DOCTYPE html>"));
+        assert!(standalone.contains("<title>Notes</title>"));
+        assert!(standalone.contains("<link rel=\"stylesheet\" href=\"style.css\" />"));
+        assert!(standalone.contains("<li><a href=\"#Intro\">Intro</a></li>"));
+        assert!(standalone.contains("<a href=\"elsewhere.html\">a link</a>"));
+    }
+
+    #[test]
+    fn resolves_hrefs() {
+        let href = |source: &str| {
+            let root = parse(source);
+            find_all(&root, SyntaxKind::Link)
+                .into_iter()
+                .find_map(Link::cast)
+                .map(|link| html::href(&link))
+                .unwrap_or_default()
+        };
+        assert_eq!(href("[[dir/page]]\n"), "dir/page.html");
+        assert_eq!(href("[[dir/]]\n"), "dir/index.html");
+        assert_eq!(href("[[wiki1:page#a b]]\n"), "page.html#a-b");
+        assert_eq!(href("[[diary:2026-10-19]]\n"), "diary/2026-10-19.html");
+        assert_eq!(href("[[https://example.com]]\n"), "https://example.com");
+        assert_eq!(href("[[local:files/a.pdf]]\n"), "files/a.pdf");
+    }
+
+    #[test]
+    fn exports_markdown() {
+        assert_eq!(
+            export(&parse(PAGE), Format::Markdown),
+            "---\ntitle: Notes\n---\n\n# Intro\n\n\
+             Some **bold** and `a<b` with [a link](other.md#Part-Two) :todo:\n\n\
+             | a | b |\n| --- | --- |\n| c | d |\n\n\
+             - [x] done\n  1. sub\n\n\
+             ```rust\nfn main() {}\n```\n"
+        );
+        assert_eq!("md".parse(), Ok(Format::Markdown));
+        assert!("pdf".parse::<Format>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn exports_json() {
+        let value = json::value(&parse("*b*\n"));
+        assert_eq!(value["kind"], "ROOT");
+        assert_eq!(value["children"][0]["kind"], "PARAGRAPH");
+        assert_eq!(value["children"][0]["children"][0]["kind"], "BOLD");
+        assert_eq!(
+            value["children"][0]["children"][0]["span"],
+            serde_json::json!([0, 3])
+        );
+        assert_eq!(
+            value["children"][0]["children"][0]["children"][1]["text"],
+            "b"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn exports_pandoc() {
+        let document = pandoc::value(&parse(PAGE));
+        assert_eq!(
+            document["pandoc-api-version"],
+            serde_json::json!([1, 23, 1])
+        );
+        assert_eq!(document["meta"]["title"]["c"][0]["c"], "Notes");
+        let blocks = document["blocks"].as_array().unwrap();
+        let kinds: Vec<_> = blocks
+            .iter()
+            .map(|block| block["t"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            kinds,
+            ["Header", "Para", "Table", "BulletList", "CodeBlock"]
+        );
+        assert_eq!(blocks[0]["c"][1][0], "Intro");
+        assert_eq!(
+            blocks[1]["c"][2],
+            serde_json::json!({ "t": "Strong", "c": [{ "t": "Str", "c": "bold" }] })
+        );
+        assert_eq!(blocks[1]["c"][6]["t"], "Code");
+        assert_eq!(blocks[1]["c"][10]["c"][2][0], "other.html#Part-Two");
+        assert_eq!(blocks[3]["c"][0][0]["c"][0]["c"], "☒");
+        assert_eq!(
+            blocks[3]["c"][0][1]["c"][0],
+            serde_json::json!([1, { "t": "Decimal" }, { "t": "Period" }])
+        );
+
+        let list = |source: &str| pandoc::value(&parse(source))["blocks"][0]["c"][0].clone();
+        assert_eq!(
+            list(
+                "iv) four
+"
+            ),
+            serde_json::json!([4, { "t": "LowerRoman" }, { "t": "OneParen" }])
+        );
+        assert_eq!(
+            list(
+                "B) two
+"
+            ),
+            serde_json::json!([2, { "t": "UpperAlpha" }, { "t": "OneParen" }])
+        );
+        assert_eq!("pandoc".parse(), Ok(Format::Pandoc));
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::fix::{Fix, FixError, TextEdit, apply, error_fix, fix, fixes};
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::lint::{LintConfig, Rule, Severity};
+    use vimwiki_syntax::parser::{ErrorCode, ErrorNode, parse};
+    use vimwiki_syntax::span::Span;
+
+    #[test]
+    fn applies_fixes_without_conflicts() {
+        let fixes = [
+            Fix::edit("b", TextEdit::new(Span::new(4, 7), "B")),
+            Fix::new(
+                "a",
+                vec![TextEdit::insert(0, "<"), TextEdit::insert(3, ">")],
+            ),
+            Fix::edit("overlaps b", TextEdit::delete(Span::new(6, 8))),
+            Fix::edit("outside", TextEdit::delete(Span::new(10, 20))),
+        ];
+        let fixed = apply("abc defg", &fixes);
+        assert_eq!(fixed.text, "<abc> Bg");
+        assert_eq!((fixed.applied, fixed.skipped), (2, 2));
+        assert_eq!(apply("abc", &[]).text, "abc");
+    }
+
+    #[test]
+    fn fixes_lints_and_errors() {
+        let source = "= A = \n- a\n* b\n\n1. x\n3. y\n[[page|page]] *bold\n{{{\ncode\n";
+        let titles: Vec<_> = fixes(source, &parse(source), &LintConfig::default())
+            .into_iter()
+            .map(|fix| fix.title)
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                "Remove trailing whitespace",
+                "Replace `*` with `-`",
+                "Renumber to `2.`",
+                "Remove the description",
+                "Close with `*`",
+                "Close code block with `}}}`",
+            ]
+        );
+        let fixed = fix(source, &LintConfig::default()).unwrap();
+        assert_eq!(
+            fixed.text,
+            "= A =\n- a\n- b\n\n1. x\n2. y\n[[page]] *bold*\n{{{\ncode\n}}}\n"
+        );
+        assert!(parse(&fixed.text).error_nodes().is_empty());
+    }
+
+    #[test]
+    fn fixes_errors_by_code() {
+        let source = "[[page\n{{$\nx\n";
+        let root = parse(source);
+        let errors = root.error_nodes();
+        assert_eq!(
+            errors.iter().map(|error| error.code()).collect::<Vec<_>>(),
+            [
+                Some(ErrorCode::UnclosedLink),
+                Some(ErrorCode::UnclosedMathBlock)
+            ]
+        );
+        let fixed = fix(source, &LintConfig::default()).unwrap();
+        assert_eq!(fixed.text, "[[page]]\n{{$\nx\n}}$\n");
+
+        // the message alone does not say how to fix an error
+        let error = ErrorNode::new(
+            SyntaxKind::Error,
+            "[[".into(),
+            Some("Unclosed link".to_owned()),
+            None,
+            Span::new(0, 2),
+        );
+        assert_eq!(error_fix("[[", &error), None);
+        let error = error.with_code(ErrorCode::UnclosedLink);
+        assert_eq!(
+            error_fix("[[", &error).map(|fix| fix.edits),
+            Some(vec![TextEdit::insert(2, "]]")])
+        );
+    }
+
+    #[test]
+    fn rejects_fixes_which_move_errors() {
+        // closing `~~` right after it opens leaves the error, further on
+        assert!(matches!(
+            fix("~~~~\n", &LintConfig::default()),
+            Err(FixError::NewErrors(1))
+        ));
+        // errors the fixes do not touch only move with the text before them
+        let fixed = fix("a \n*a [[b|c*\n", &LintConfig::default()).unwrap();
+        assert_eq!(fixed.text, "a\n*a [[b|c*]]\n");
+    }
+
+    #[test]
+    fn leaves_lints_turned_off() {
+        let mut config = LintConfig::default();
+        config.set(Rule::TrailingWhitespace, Severity::Off);
+        let fixed = fix("a \n%% lint-disable\n1. a\n3. b\n", &config).unwrap();
+        assert_eq!(fixed.text, "a \n%% lint-disable\n1. a\n3. b\n");
+        assert_eq!(fixed.applied, 0);
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::fmt::{format, is_formatted};
+
+    #[test]
+    fn normalizes_whitespace() {
+        assert_eq!(
+            format("\n\n=  Title=  \ntext   \n\n\n\n==Sub  ==\nmore"),
+            "= Title =\ntext\n\n== Sub ==\nmore\n"
+        );
+        assert_eq!(format("   = Centered =\n"), "   = Centered =\n");
+        assert_eq!(format(""), "");
+    }
+
+    #[test]
+    fn aligns_tables() {
+        assert_eq!(
+            format("|a|bbb|\n|-|-|\n|cc|[[x]]|\n"),
+            "| a  | bbb   |\n|----|-------|\n| cc | [[x]] |\n"
+        );
+        // columns are counted in chars
+        assert_eq!(format("| ä | b |\n| cc |d|\n"), "| ä  | b |\n| cc | d |\n");
+    }
+
+    #[test]
+    fn keeps_verbatim_blocks() {
+        let source = "{{{python\n  x = 1   \n\n\n}}}\n\n\n{{$\n a  \n}}$\n";
+        assert_eq!(
+            format(source),
+            "{{{python\n  x = 1   \n\n\n}}}\n\n{{$\n a  \n}}$\n"
+        );
+        // blocks with errors are left alone
+        assert_eq!(format("*unclosed  \n"), "*unclosed  \n");
+        for source in [source, "= A =\n\n|a|b|\n\n- item  \n"] {
+            assert!(is_formatted(&format(source)));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::graph::{Graph, PageId};
+    use vimwiki_syntax::workspace::{Page, WikiConfig, Workspace};
+
+    fn graph() -> Graph {
+        let workspace = Workspace {
+            wikis: vec![WikiConfig::new("/wiki"), WikiConfig::new("/work")],
+        };
+        let pages = vec![
+            vec![
+                Page::new(
+                    "index",
+                    "%title Home\n[[todo]] [[dir/notes|notes]] [[missing]]\n",
+                ),
+                Page::new("todo", "[[index]] [[todo#self]] [[https://example.com]]\n"),
+                Page::new("dir/notes", "[[../todo]] [[wiki1:plans]]\n"),
+                Page::new("lonely", "[[#top]]\n"),
+            ],
+            vec![
+                Page::new("plans", "%title The \"Plan\"\n"),
+                Page::new("other", "[[plans]]\n"),
+            ],
+        ];
+        Graph::build(&workspace, &pages)
+    }
+
+    fn ids(pages: Vec<&PageId>) -> Vec<String> {
+        pages.into_iter().map(PageId::id).collect()
+    }
+
+    #[test]
+    fn follows_links() {
+        let graph = graph();
+        assert_eq!(
+            ids(graph.pages().iter().collect()),
+            [
+                "dir/notes",
+                "index",
+                "lonely",
+                "todo",
+                "wiki1:other",
+                "wiki1:plans"
+            ]
+        );
+        assert_eq!(
+            ids(graph.outgoing(&PageId::new(0, "index"))),
+            ["dir/notes", "todo"]
+        );
+        assert_eq!(
+            ids(graph.backlinks(&PageId::new(0, "todo"))),
+            ["dir/notes", "index"]
+        );
+        assert_eq!(
+            ids(graph.backlinks(&PageId::new(1, "plans"))),
+            ["dir/notes", "wiki1:other"]
+        );
+        assert!(graph.backlinks(&PageId::new(0, "lonely")).is_empty());
+        assert!(graph.outgoing(&PageId::new(0, "missing")).is_empty());
+        assert_eq!(
+            graph.components().into_iter().map(ids).collect::<Vec<_>>(),
+            [
+                vec!["dir/notes", "index", "todo", "wiki1:other", "wiki1:plans"],
+                vec!["lonely"],
+            ]
+        );
+    }
+
+    #[test]
+    fn exports_dot() {
+        let graph = graph();
+        assert_eq!(
+            graph.to_dot(),
+            r#"digraph wiki {
+    "dir/notes" [label="dir/notes"];
+    "index" [label="Home"];
+    "lonely" [label="lonely"];
+    "todo" [label="todo"];
+    "wiki1:other" [label="wiki1:other"];
+    "wiki1:plans" [label="The \"Plan\""];
+    "dir/notes" -> "todo";
+    "dir/notes" -> "wiki1:plans";
+    "index" -> "dir/notes";
+    "index" -> "todo";
+    "todo" -> "index";
+    "wiki1:other" -> "wiki1:plans";
+}
+"#
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn exports_json() {
+        let json: serde_json::Value = serde_json::from_str(&graph().to_json()).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 6);
+        assert_eq!(
+            json["nodes"][1],
+            serde_json::json!({"id": "index", "wiki": 0, "page": "index", "title": "Home"})
+        );
+        assert_eq!(
+            json["edges"][1],
+            serde_json::json!({"source": "dir/notes", "target": "wiki1:plans"})
+        );
+        assert_eq!(json["edges"].as_array().unwrap().len(), 6);
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(test)]
+#[cfg(feature = "lsp")]
+mod test {
+    use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind};
+    use vimwiki_syntax::line_index::LineIndex;
+    use vimwiki_syntax::lsp::Page;
+    use vimwiki_syntax::lsp::folding::folding_ranges;
+    use vimwiki_syntax::lsp::hover::{first_paragraph, hover};
+    use vimwiki_syntax::parser::parse;
+
+    fn pages() -> Vec<Page> {
+        vec![
+            Page::new("index", "[[todo]] [[todo#Later]] [[gone]] :work:\n"),
+            Page::new(
+                "todo",
+                "%title Things to do\n= Now =\nfirst\nparagraph\n\n= Later =\nlater on :work:\n",
+            ),
+            Page::new("notes", "plain notes :home:\n"),
+        ]
+    }
+
+    #[test]
+    fn previews_linked_pages() {
+        let pages = pages();
+        let root = &pages[0].root;
+        assert_eq!(
+            hover(root, "index", 3, &pages).map(|(text, _)| text),
+            Some("**Things to do**\n\nfirst paragraph".to_owned())
+        );
+        assert_eq!(
+            hover(root, "index", 12, &pages).map(|(text, _)| text),
+            Some("**Things to do**\n\n`#Later`\n\nlater on :work:".to_owned())
+        );
+        assert_eq!(
+            hover(root, "index", 27, &pages).map(|(text, _)| text),
+            Some("page `gone` does not exist yet".to_owned())
+        );
+        assert_eq!(
+            first_paragraph(&pages[2].root, None),
+            Some("plain notes :home:".to_owned())
+        );
+    }
+
+    #[test]
+    fn counts_tag_usage() {
+        let pages = pages();
+        let root = &pages[0].root;
+        assert_eq!(
+            hover(root, "index", 35, &pages).map(|(text, _)| text),
+            Some("tag `:work:` is used on 2 pages".to_owned())
+        );
+        assert_eq!(hover(root, "index", 32, &pages), None);
+    }
+
+    fn fold(start_line: u32, end_line: u32, kind: FoldingRangeKind) -> FoldingRange {
+        FoldingRange {
+            start_line,
+            end_line,
+            kind: Some(kind),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn folds_sections_and_blocks() {
+        let source = "= A =\ntext\n- a\n  - b\n    - c\n\n== B ==\n{{{\ncode\n}}}\n%%+ c\nd +%%\n\n= C =\n| x |\n| y |\n";
+        let root = parse(source);
+        assert_eq!(
+            folding_ranges(&root, &LineIndex::new(source)),
+            [
+                fold(0, 11, FoldingRangeKind::Region),
+                fold(2, 4, FoldingRangeKind::Region),
+                fold(3, 4, FoldingRangeKind::Region),
+                fold(6, 11, FoldingRangeKind::Region),
+                fold(7, 9, FoldingRangeKind::Region),
+                fold(10, 11, FoldingRangeKind::Comment),
+                fold(13, 15, FoldingRangeKind::Region),
+                fold(14, 15, FoldingRangeKind::Region),
+            ]
+        );
+    }
+}
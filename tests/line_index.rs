@@ -0,0 +1,157 @@
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+    #[cfg(feature = "lsp")]
+    use tower_lsp::lsp_types::PositionEncodingKind;
+    use vimwiki_syntax::line_index::{Encoding, LineIndex, Position, Range};
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::span::Span;
+
+    const ENCODINGS: [Encoding; 3] = [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32];
+
+    /// the column of `offset` counted the slow way
+    fn column(text: &str, offset: usize, encoding: Encoding) -> u32 {
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let prefix = &text[line_start..offset];
+        (match encoding {
+            Encoding::Utf8 => prefix.len(),
+            Encoding::Utf16 => prefix.encode_utf16().count(),
+            Encoding::Utf32 => prefix.chars().count(),
+        }) as u32
+    }
+
+    #[test]
+    fn converts_malayalam_and_emoji() {
+        // every letter of മലയാളം is 3 bytes, one UTF-16 unit and one char,
+        // the emoji is 4 bytes, two UTF-16 units and one char
+        let text = "= മലയാളം =\n😀 *bold*\n";
+        let index = LineIndex::new(text);
+        let bold = text.find('*').unwrap();
+        assert_eq!(index.position(bold, Encoding::Utf8), Position::new(1, 5));
+        assert_eq!(index.position(bold, Encoding::Utf16), Position::new(1, 3));
+        assert_eq!(index.position(bold, Encoding::Utf32), Position::new(1, 2));
+        assert_eq!(
+            index.offset(Position::new(1, 3), Encoding::Utf16),
+            Some(bold)
+        );
+
+        let end = text.find(" =").unwrap();
+        assert_eq!(index.position(end, Encoding::Utf16), Position::new(0, 8));
+        assert_eq!(index.position(end, Encoding::Utf8), Position::new(0, 20));
+
+        // inside of a char counts as its start, past the end of a line as its end
+        assert_eq!(
+            index.position(bold - 3, Encoding::Utf16),
+            Position::new(1, 0)
+        );
+        assert_eq!(
+            index.offset(Position::new(1, 1), Encoding::Utf16),
+            Some(bold - 5)
+        );
+        assert_eq!(
+            index.offset(Position::new(0, 99), Encoding::Utf32),
+            Some(end + 2)
+        );
+        assert_eq!(
+            index.offset(Position::new(2, 0), Encoding::Utf16),
+            Some(text.len())
+        );
+        assert_eq!(index.offset(Position::new(3, 0), Encoding::Utf16), None);
+
+        assert_eq!(
+            Span::new(bold, bold + 6).into_lsp_range(text).unwrap(),
+            Range::new(Position::new(1, 3), Position::new(1, 9))
+        );
+        assert_eq!(
+            Span::new(bold, bold + 6).to_char_offset(text),
+            Span::new(13, 19)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "lsp")]
+    fn negotiates_encodings() {
+        assert_eq!(Encoding::negotiate(None), Encoding::Utf16);
+        assert_eq!(
+            Encoding::negotiate(Some(&[
+                PositionEncodingKind::UTF16,
+                PositionEncodingKind::UTF8
+            ])),
+            Encoding::Utf8
+        );
+        assert_eq!(
+            Encoding::negotiate(Some(&[PositionEncodingKind::UTF32])),
+            Encoding::Utf32
+        );
+    }
+
+    fn wiki_text() -> impl Strategy<Value = String> {
+        let pieces = prop::sample::select(vec![
+            "a",
+            " ",
+            "\n",
+            "*",
+            "_",
+            "=",
+            "- ",
+            "[[",
+            "]]",
+            "|",
+            ":",
+            "{{{\n",
+            "ക",
+            "മലയാളം",
+            "ൽ",
+            "😀",
+            "👩‍💻",
+            "é",
+            "日本",
+        ]);
+        prop::collection::vec(pieces, 0..64).prop_map(|pieces| pieces.concat())
+    }
+
+    proptest! {
+        #[test]
+        fn positions_round_trip(text in wiki_text()) {
+            let index = LineIndex::new(&text);
+            for offset in (0..=text.len()).filter(|&i| text.is_char_boundary(i)) {
+                for encoding in ENCODINGS {
+                    let position = index.position(offset, encoding);
+                    prop_assert_eq!(position.character, column(&text, offset, encoding));
+                    prop_assert_eq!(index.offset(position, encoding), Some(offset));
+                }
+            }
+        }
+
+        #[test]
+        fn edits_match_a_new_index(
+            text in wiki_text(),
+            insert in wiki_text(),
+            a in any::<prop::sample::Index>(),
+            b in any::<prop::sample::Index>(),
+        ) {
+            let boundaries: Vec<_> = (0..=text.len())
+                .filter(|&i| text.is_char_boundary(i))
+                .collect();
+            let (a, b) = (a.get(&boundaries), b.get(&boundaries));
+            let span = Span::new(*a.min(b), *a.max(b));
+            let mut index = LineIndex::new(&text);
+            index.edit(span, &insert);
+            let edited = format!("{}{insert}{}", &text[..span.start], &text[span.end..]);
+            prop_assert_eq!(index, LineIndex::new(&edited));
+        }
+
+        #[test]
+        fn spans_are_byte_offsets(text in wiki_text()) {
+            let root = parse(&text);
+            prop_assert_eq!(root.text(), text.clone());
+            fn check(node: &vimwiki_syntax::parser::Node, text: &str) -> bool {
+                let span = node.span();
+                (node.children().is_empty() || text.get(span.start..span.end).is_some())
+                    && (!node.is_leaf() || text.get(span.start..span.end) == Some(node.text().as_str()))
+                    && node.children().iter().all(|child| check(child, text))
+            }
+            prop_assert!(check(&root, &text));
+        }
+    }
+}
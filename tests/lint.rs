@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::lint::{LintConfig, Rule, Severity, lint};
+    use vimwiki_syntax::parser::parse;
+
+    const PAGE: &str = "= A = \n=== C ===\n== ==\n= A =\n\
+        - a\n* b\n\n1. x\n3. y\n\
+        | a | b |\n| c |\n[[page|page]] [[page|other]]\n";
+
+    fn rules(source: &str, config: &LintConfig) -> Vec<(Rule, usize)> {
+        lint(&parse(source), config)
+            .into_iter()
+            .map(|lint| (lint.rule, lint.span.start))
+            .collect()
+    }
+
+    #[test]
+    fn finds_problems() {
+        assert_eq!(
+            rules(PAGE, &LintConfig::default()),
+            [
+                (Rule::TrailingWhitespace, 5),
+                (Rule::HeadingSkip, 7),
+                (Rule::EmptyHeading, 17),
+                (Rule::DuplicateHeading, 23),
+                (Rule::MixedBullets, 33),
+                (Rule::ListNumbering, 43),
+                (Rule::RaggedTable, 58),
+                (Rule::RedundantDescription, 64),
+            ]
+        );
+        let lints = lint(&parse(PAGE), &LintConfig::default());
+        assert_eq!(lints[5].message, "item numbered 3, expected 2");
+        assert_eq!(lints[0].severity, Severity::Info);
+        assert!(rules("= A =\n== B ==\n- a\n- b\n\n* c\n", &LintConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn reads_config() {
+        let config = LintConfig::from_toml(
+            "[[wiki]]\nroot = \".\"\n\n[lint]\nheading-skip = \"error\"\ntrailing-whitespace = \"off\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.severity(Rule::HeadingSkip), Severity::Error);
+        assert_eq!(config.severity(Rule::MixedBullets), Severity::Warning);
+        let lints = lint(&parse(PAGE), &config);
+        assert_eq!(lints[0].rule, Rule::HeadingSkip);
+        assert_eq!(lints[0].severity, Severity::Error);
+        assert!(
+            lints
+                .iter()
+                .all(|lint| lint.rule != Rule::TrailingWhitespace)
+        );
+
+        let err = LintConfig::from_toml("[lint]\nno-such-rule = \"off\"\n").unwrap_err();
+        assert_eq!(err.to_string(), "unknown lint rule `no-such-rule`");
+        assert!(LintConfig::from_toml("[lint]\nheading-skip = \"loud\"\n").is_err());
+    }
+
+    #[test]
+    fn honours_disable_comments() {
+        let source = "a \n%% lint-disable trailing-whitespace\nb \n\
+            [[x|x]]\n%% lint-enable\nc \n%% lint-disable\n[[y|y]] \n";
+        assert_eq!(
+            rules(source, &LintConfig::default()),
+            [
+                (Rule::TrailingWhitespace, 1),
+                (Rule::RedundantDescription, 42),
+                (Rule::TrailingWhitespace, 66),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_rules_in_comments() {
+        let source = "%% lint-disable heading-skp\n= A =\n=== B ===\n\
+            %% lint-disable heading-skip nope\n===== C =====\n";
+        let root = parse(source);
+        let lints = lint(&root, &LintConfig::default());
+        let found: Vec<_> = lints
+            .iter()
+            .map(|lint| (lint.rule, &source[lint.span.start..lint.span.end]))
+            .collect();
+        // the typo does not turn off every rule
+        assert_eq!(
+            found,
+            [
+                (Rule::UnknownRule, "heading-skp"),
+                (Rule::HeadingSkip, "=== B ==="),
+                (Rule::UnknownRule, "nope"),
+            ]
+        );
+        assert_eq!(
+            lints[0].message,
+            "unknown lint rule `heading-skp`, it is ignored"
+        );
+    }
+}
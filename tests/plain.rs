@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::export::Plain;
+    use vimwiki_syntax::parser::parse;
+
+    const PAGE: &str = "= Intro =\n\
+        Some *bold* and [[other|a link]] in a line long enough to wrap\n\
+        - [X] done\n- [ ] open item which wraps too\n  1. sub\n\
+        | a | bb |\n|---|---|\n| ccc | d |\n";
+
+    #[test]
+    fn renders_plain_text() {
+        assert_eq!(
+            Plain::new().width(Some(24)).render(&parse(PAGE)),
+            "Intro\n=====\n\n\
+             Some bold and a link in\na line long enough to\nwrap\n\n\
+             - [x] done\n- [ ] open item which\n  wraps too\n  1. sub\n\n\
+             a    bb\n---  --\nccc  d\n"
+        );
+        assert_eq!(
+            Plain::new()
+                .width(None)
+                .render(&parse("a\nlong\nparagraph\n")),
+            "a long paragraph\n"
+        );
+    }
+
+    #[test]
+    fn renders_ansi() {
+        let text = Plain::new().ansi(true).render(&parse(PAGE));
+        assert!(text.starts_with("\x1b[1;35mIntro\x1b[0m\n"));
+        assert!(text.contains("Some \x1b[1mbold\x1b[22m and \x1b[4ma link\x1b[24m"));
+        assert!(text.contains("• ☑ done\n• ☐ open item which wraps too\n  1. sub\n"));
+        assert!(
+            text.ends_with(
+                "┌─────┬────┐\n│ a   │ bb │\n├─────┼────┤\n│ ccc │ d  │\n└─────┴────┘\n"
+            )
+        );
+    }
+
+    #[test]
+    fn turns_enclosing_styles_on_again() {
+        let text = Plain::new()
+            .ansi(true)
+            .render(&parse("*bold `code` more*\n\n= A *b* c =\n"));
+        assert!(text.contains("\x1b[1mbold \x1b[2mcode\x1b[22m\x1b[1m more\x1b[22m"));
+        assert!(text.contains("\x1b[1;35mA \x1b[1mb\x1b[22m\x1b[1;35m c\x1b[0m"));
+    }
+
+    #[test]
+    fn aligns_wide_characters() {
+        assert_eq!(
+            Plain::new().render(&parse("| 漢字 | b |\n| c | 😀 |\n")),
+            "漢字  b\nc     😀\n"
+        );
+    }
+}
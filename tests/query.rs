@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::query::Query;
+
+    const PAGE: &str = "= Projects =\n:project:\n| [[a]] | [[b|B]] |\n\
+        == Sub ==\n- [ ] open\n- [X] done [[c]]\n  1. [ ] nested\n\
+        = Other =\n| [[d]] |\n";
+
+    fn texts(query: &str) -> Vec<String> {
+        let root = parse(PAGE);
+        Query::parse(query)
+            .unwrap()
+            .select(&root)
+            .iter()
+            .map(|matched| matched.node.text().trim().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn selects_nodes() {
+        assert_eq!(
+            texts("heading[tag=project] table link"),
+            ["[[a]]", "[[b|B]]"]
+        );
+        assert_eq!(texts("table link[description]"), ["[[b|B]]"]);
+        assert_eq!(
+            texts("heading[level=2] > list checkbox[state=open]"),
+            ["[ ]", "[ ]"]
+        );
+        assert_eq!(
+            texts("heading[level=2] > list > list_item > checkbox").len(),
+            2
+        );
+        assert_eq!(
+            texts("list[ordered=true] list_item[state!=done]"),
+            ["1. [ ] nested"]
+        );
+        assert_eq!(
+            texts("heading[title^=Oth] link, list_item link"),
+            ["[[c]]", "[[d]]"]
+        );
+        assert_eq!(texts("heading[title*='roj'] > heading"), ["== Sub =="]);
+    }
+
+    #[test]
+    fn captures_nodes() {
+        let root = parse(PAGE);
+        let query: Query = "heading @section list_item[state=done] @item link"
+            .parse()
+            .unwrap();
+        let matches = query.select(&root);
+        assert_eq!(matches.len(), 1);
+        let matched = &matches[0];
+        assert_eq!(matched.node.kind(), SyntaxKind::Link);
+        assert_eq!(&PAGE[matched.span().start..matched.span().end], "[[c]]");
+        let names: Vec<_> = matched
+            .captures
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, ["section", "item"]);
+        assert_eq!(
+            matched.capture("section").map(|node| node.text()),
+            Some("== Sub ==\n".to_owned())
+        );
+    }
+
+    #[test]
+    fn reports_errors() {
+        let err = Query::parse("heading > nope").unwrap_err();
+        assert_eq!(err.offset, 10);
+        assert_eq!(err.to_string(), "unknown node kind `nope` at column 11");
+        assert!(Query::parse("link[target").is_err());
+        assert!(Query::parse("link[target='a]").is_err());
+        assert!(Query::parse("heading >").is_err());
+        assert!(Query::parse("heading @").is_err());
+    }
+}
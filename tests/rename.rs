@@ -0,0 +1,170 @@
+#[cfg(test)]
+#[cfg(feature = "lsp")]
+mod test {
+    use tower_lsp::lsp_types::{Position, Range, TextEdit};
+    use vimwiki_syntax::line_index::Encoding;
+    use vimwiki_syntax::lsp::rename::{Target, rename, target_at};
+    use vimwiki_syntax::lsp::{Page, relative_page, resolve_page};
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::span::Span;
+
+    fn edit(line: u32, start: u32, end: u32, text: &str) -> TextEdit {
+        TextEdit {
+            range: Range::new(Position::new(line, start), Position::new(line, end)),
+            new_text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn resolves_relative_pages() {
+        assert_eq!(resolve_page("index", "todo"), "todo");
+        assert_eq!(resolve_page("dir/index", "todo"), "dir/todo");
+        assert_eq!(resolve_page("dir/index", "../todo"), "todo");
+        assert_eq!(resolve_page("dir/index", "/todo"), "todo");
+        assert_eq!(resolve_page("dir/index", ""), "dir/index");
+        assert_eq!(relative_page("dir/index", "dir/todo"), "todo");
+        assert_eq!(relative_page("dir/index", "other/todo"), "../other/todo");
+        assert_eq!(relative_page("index", "dir/todo"), "dir/todo");
+    }
+
+    #[test]
+    fn finds_rename_targets() {
+        let root = parse("= Intro =\nsee [[todo#Next|the list]] and text\n");
+        assert_eq!(
+            target_at(&root, "index", 4),
+            Some(Target::Heading {
+                page: "index".to_owned(),
+                title: "Intro".to_owned(),
+                span: Span::new(2, 7)
+            })
+        );
+        assert_eq!(
+            target_at(&root, "index", 17),
+            Some(Target::Page {
+                page: "todo".to_owned(),
+                span: Span::new(16, 20)
+            })
+        );
+        assert_eq!(
+            target_at(&root, "index", 22),
+            Some(Target::Heading {
+                page: "todo".to_owned(),
+                title: "Next".to_owned(),
+                span: Span::new(21, 25)
+            })
+        );
+        // descriptions and plain text can not be renamed
+        assert_eq!(target_at(&root, "index", 28), None);
+        assert_eq!(target_at(&root, "index", 40), None);
+    }
+
+    #[test]
+    fn renames_pages_and_rewrites_links() {
+        let pages = vec![
+            Page::new("index", "[[todo]] [[todo#Next|the list]] [[other]]\n"),
+            Page::new("todo", "= Next =\n[[index]]\n"),
+            Page::new("dir/page", "[[../todo]] [[/todo]]\n"),
+        ];
+        let target = Target::Page {
+            page: "todo".to_owned(),
+            span: Span::new(2, 6),
+        };
+        let renamed = rename(&target, "index", "tasks/todo", &pages, Encoding::Utf16).unwrap();
+        assert_eq!(
+            renamed.moved,
+            Some(("todo".to_owned(), "tasks/todo".to_owned()))
+        );
+        assert_eq!(
+            renamed.edits["index"],
+            [
+                edit(0, 2, 6, "tasks/todo"),
+                edit(0, 11, 20, "tasks/todo#Next")
+            ]
+        );
+        assert_eq!(renamed.edits["todo"], [edit(1, 2, 7, "../index")]);
+        assert_eq!(
+            renamed.edits["dir/page"],
+            [
+                edit(0, 2, 9, "../tasks/todo"),
+                edit(0, 14, 19, "/tasks/todo")
+            ]
+        );
+
+        assert!(rename(&target, "index", "dir/page", &pages, Encoding::Utf16).is_err());
+        assert!(rename(&target, "index", "a|b", &pages, Encoding::Utf16).is_err());
+    }
+
+    #[test]
+    fn renames_headings_and_anchors() {
+        let pages = vec![
+            Page::new("index", "[[todo#Next]] [[todo#Next#Sub]]\n"),
+            Page::new("todo", "= Next =\n[[#Next|up]]\n"),
+        ];
+        let target = Target::Heading {
+            page: "todo".to_owned(),
+            title: "Next".to_owned(),
+            span: Span::new(2, 6),
+        };
+        let renamed = rename(&target, "todo", "Later", &pages, Encoding::Utf16).unwrap();
+        assert_eq!(renamed.moved, None);
+        assert_eq!(
+            renamed.edits["index"],
+            [
+                edit(0, 2, 11, "todo#Later"),
+                edit(0, 16, 29, "todo#Later#Sub")
+            ]
+        );
+        assert_eq!(
+            renamed.edits["todo"],
+            [edit(0, 2, 6, "Later"), edit(1, 2, 7, "#Later")]
+        );
+
+        // a heading with the same title under another one is left alone
+        let pages = vec![
+            Page::new("index", "[[todo#Next]] [[todo#Other#Next]]\n"),
+            Page::new("todo", "= Next =\n= Other =\n== Next ==\n"),
+        ];
+        let renamed = rename(&target, "todo", "Later", &pages, Encoding::Utf16).unwrap();
+        assert_eq!(renamed.edits["index"], [edit(0, 2, 11, "todo#Later")]);
+        assert_eq!(renamed.edits["todo"], [edit(0, 2, 6, "Later")]);
+
+        let target = target_at(&pages[0].root, "index", 30).unwrap();
+        let renamed = rename(&target, "index", "Later", &pages, Encoding::Utf16).unwrap();
+        assert_eq!(
+            renamed.edits["index"],
+            [edit(0, 16, 31, "todo#Other#Later")]
+        );
+        assert_eq!(renamed.edits["todo"], [edit(2, 3, 7, "Later")]);
+    }
+
+    #[test]
+    fn renames_anchors_written_as_unique_ids() {
+        let pages = vec![
+            Page::new("index", "[[todo#Setup-2]] [[todo#Setup]]\n"),
+            Page::new("todo", "= Setup =\n= Setup =\n"),
+        ];
+        let second = Target::Heading {
+            page: "todo".to_owned(),
+            title: "Setup".to_owned(),
+            span: Span::new(12, 17),
+        };
+        let renamed = rename(&second, "todo", "Install", &pages, Encoding::Utf16).unwrap();
+        assert_eq!(renamed.edits["index"], [edit(0, 2, 14, "todo#Install")]);
+        assert_eq!(renamed.edits["todo"], [edit(1, 2, 7, "Install")]);
+
+        // renaming the first one moves the id of the second
+        let first = Target::Heading {
+            page: "todo".to_owned(),
+            title: "Setup".to_owned(),
+            span: Span::new(2, 7),
+        };
+        let renamed = rename(&first, "todo", "Install", &pages, Encoding::Utf16).unwrap();
+        assert_eq!(
+            renamed.edits["index"],
+            [
+                edit(0, 2, 14, "todo#Setup"),
+                edit(0, 19, 29, "todo#Install")
+            ]
+        );
+    }
+}
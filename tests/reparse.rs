@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test {
+    use ropey::Rope;
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::reparser::{Edit, reparse};
+    use vimwiki_syntax::span::Span;
+
+    /// applies `edit` to `source` incrementally and checks the tree equals a fresh parse
+    fn check(source: &str, edit: Edit) -> String {
+        let mut rope = Rope::from(source);
+        edit.apply(&mut rope);
+        let text = rope.to_string();
+        let incremental = reparse(&parse(source), &rope, &edit);
+        assert_eq!(
+            incremental,
+            parse(&text),
+            "reparsing {source:?} after {edit:?} differs from a fresh parse of {text:?}"
+        );
+        text
+    }
+
+    fn replace(source: &str, pattern: &str, text: &str) -> Edit {
+        let start = source.find(pattern).unwrap();
+        Edit::new(Span::new(start, start + pattern.len()), text)
+    }
+
+    const PAGE: &str = "= Title =\nfirst paragraph\nwith *bold*\n\n- one\n- [ ] two\n  - nested\n\n| a | b |\n| c | d |\n\n{{{\ncode\n}}}\n%%+ hidden\ncomment +%%\n== Next ==\nlast [[link|desc]] :tag:\n";
+
+    #[test]
+    fn reparses_local_edits() {
+        check(PAGE, replace(PAGE, "first", "1st"));
+        check(PAGE, replace(PAGE, "bold*", "bold"));
+        check(PAGE, replace(PAGE, "two", "t\nwo"));
+        check(PAGE, replace(PAGE, "| c", "|| c"));
+        check(PAGE, Edit::new(Span::new(0, 0), "%title Page\n"));
+        check(PAGE, Edit::new(Span::new(PAGE.len(), PAGE.len()), "tail"));
+        check(PAGE, Edit::new(Span::new(0, PAGE.len()), ""));
+    }
+
+    #[test]
+    fn reparses_edits_changing_following_blocks() {
+        // merging two paragraphs
+        check(PAGE, replace(PAGE, "bold*\n\n", "bold* "));
+        // an unclosed code block swallows everything after it
+        check(PAGE, replace(PAGE, "}}}\n", ""));
+        check(PAGE, replace(PAGE, "first paragraph", "{{{"));
+        // and a comment opened early ends at the old one
+        check(PAGE, replace(PAGE, "- one", "%%+ one"));
+        // a line turning into a heading, a list item joining a list
+        check(PAGE, replace(PAGE, "first", "== first =="));
+        check(PAGE, replace(PAGE, "\n\n| a", "\n- three\n| a"));
+    }
+
+    /// a tiny deterministic generator, good enough to shuffle edits around
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self, bound: usize) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            ((self.0 >> 33) as usize) % bound.max(1)
+        }
+    }
+
+    #[test]
+    fn reparses_random_edit_sequences() {
+        const SNIPPETS: [&str; 18] = [
+            "", "\n", "\n\n", "x", "*", "_", "= ", " =", "- ", "  ", "| ", "{{{\n", "}}}\n", "%%+",
+            "+%%", "[[", "ക", "😀",
+        ];
+        let sources = [
+            PAGE,
+            include_str!("../examples/syntax/00.wiki"),
+            include_str!("../examples/tests/00.wiki"),
+        ];
+        for (seed, source) in sources.into_iter().enumerate() {
+            let mut rng = Lcg(seed as u64);
+            let mut rope = Rope::from(source);
+            let mut root = parse(source);
+            for _ in 0..300 {
+                let text = rope.to_string();
+                let boundaries: Vec<usize> = (0..=text.len())
+                    .filter(|&i| text.is_char_boundary(i))
+                    .collect();
+                let start = boundaries[rng.next(boundaries.len())];
+                let end = boundaries
+                    .iter()
+                    .copied()
+                    .filter(|&i| i >= start)
+                    .nth(rng.next(8))
+                    .unwrap_or(start);
+                let edit = Edit::new(Span::new(start, end), SNIPPETS[rng.next(SNIPPETS.len())]);
+
+                edit.apply(&mut rope);
+                root = reparse(&root, &rope, &edit);
+                let fresh = parse(&rope.to_string());
+                assert_eq!(root, fresh, "after {edit:?} on {text:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+#[cfg(feature = "serde")]
+mod test {
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::lexer::Lexer;
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::schema::{
+        SchemaError, VERSION, tokens_from_json, tokens_to_json, tree_from_json, tree_to_json,
+    };
+
+    const PAGE: &str = "= Intro =\nSome *bold* and [[link|desc]] :tag:\n\
+        | a | b |\n- [ ] todo\n{{{rust\nfn main() {}\n}}}\n*open [[x\n";
+
+    #[test]
+    fn round_trips_trees() {
+        let root = parse(PAGE);
+        assert!(!root.error_nodes().is_empty());
+        let json = tree_to_json(&root);
+        assert_eq!(tree_from_json(&json).unwrap(), root);
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["version"], VERSION);
+        assert_eq!(value["tree"]["kind"], "ROOT");
+        assert_eq!(value["tree"]["children"][0]["kind"], "HEADING");
+        assert_eq!(
+            value["diagnostics"].as_array().unwrap().len(),
+            root.error_nodes().len()
+        );
+    }
+
+    #[test]
+    fn round_trips_tokens() {
+        let tokens = Lexer::new(PAGE.into()).lex();
+        let json = tokens_to_json(&tokens);
+        assert_eq!(tokens_from_json(&json).unwrap(), tokens);
+        assert_eq!("HEADING".parse(), Ok(SyntaxKind::Heading));
+        for kind in SyntaxKind::ALL {
+            assert_eq!(kind.to_string().parse(), Ok(kind));
+        }
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let json = r#"{"version":2,"tokens":[]}"#;
+        assert!(matches!(
+            tokens_from_json(json),
+            Err(SchemaError::Version(2))
+        ));
+        let json = r#"{"version":1,"tree":{"kind":"NOPE","span":[0,0],"text":""}}"#;
+        assert!(matches!(
+            tree_from_json(json),
+            Err(SchemaError::UnknownKind(kind)) if kind == "NOPE"
+        ));
+        let json = r#"{"version":1,"tree":{"kind":"TEXT","span":[0,0]}}"#;
+        assert!(matches!(
+            tree_from_json(json),
+            Err(SchemaError::Malformed(..))
+        ));
+    }
+
+    #[test]
+    fn rejects_tampered_spans() {
+        let root = parse("= Intro =\nSome *bold*\n");
+        let json: serde_json::Value = serde_json::from_str(&tree_to_json(&root)).unwrap();
+        // the `Intro ` text of the heading, at 2..8
+        let tamper = |span: [usize; 2]| {
+            let mut json = json.clone();
+            json["tree"]["children"][0]["children"][2]["span"] = serde_json::json!(span);
+            tree_from_json(&json.to_string())
+        };
+        assert_eq!(tamper([2, 8]).unwrap(), root);
+        // ending before it starts, longer than its text, and leaving a gap
+        // after the node before it
+        for span in [[8, 2], [2, 9], [3, 9]] {
+            assert!(
+                matches!(tamper(span), Err(SchemaError::Malformed(..))),
+                "{span:?} is accepted"
+            );
+        }
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+#[cfg(feature = "lsp")]
+mod test {
+    use tower_lsp::lsp_types::SemanticToken;
+    use vimwiki_syntax::lsp::Document;
+    use vimwiki_syntax::lsp::semantic::{TOKEN_MODIFIERS, TOKEN_TYPES, highlights, tokens};
+    use vimwiki_syntax::span::Span;
+
+    fn type_of(name: &str) -> u32 {
+        TOKEN_TYPES.iter().position(|t| t.as_str() == name).unwrap() as u32
+    }
+
+    fn modifiers(names: &[&str]) -> u32 {
+        names
+            .iter()
+            .map(|name| {
+                1 << TOKEN_MODIFIERS
+                    .iter()
+                    .position(|m| m.as_str() == *name)
+                    .unwrap()
+            })
+            .sum()
+    }
+
+    fn token(
+        delta_line: u32,
+        delta_start: u32,
+        length: u32,
+        kind: &str,
+        mods: &[&str],
+    ) -> SemanticToken {
+        SemanticToken {
+            delta_line,
+            delta_start,
+            length,
+            token_type: type_of(kind),
+            token_modifiers_bitset: modifiers(mods),
+        }
+    }
+
+    #[test]
+    fn highlights_nested_markup() {
+        let source = "== Title ==\nplain *bold _both_* `code`\n";
+        assert_eq!(
+            tokens(&Document::new(source), None),
+            [
+                token(0, 0, 11, "heading", &["level2"]),
+                token(1, 6, 6, "markup", &["bold"]),
+                token(0, 6, 6, "markup", &["bold", "italic"]),
+                token(0, 6, 1, "markup", &["bold"]),
+                token(0, 2, 6, "string", &[]),
+            ]
+        );
+
+        // columns count utf-16 code units, two for a character outside the bmp
+        let source = "😀 é *bold*\n";
+        assert_eq!(
+            tokens(&Document::new(source), None),
+            [token(0, 5, 6, "markup", &["bold"])]
+        );
+    }
+
+    #[test]
+    fn highlights_lists_links_and_tags() {
+        let source = "- [X] see [[page]]\n  - [o] :todo:\n%% note\n";
+        let document = Document::new(source);
+        let kinds: Vec<_> = highlights(&document.root)
+            .iter()
+            .map(|h| (&source[h.span.start..h.span.end], h.kind, h.modifiers))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("[X]", type_of("checkbox"), modifiers(&["done"])),
+                ("[[page]]", type_of("link"), 0),
+                ("[o]", type_of("checkbox"), modifiers(&["half"])),
+                (":todo:", type_of("tag"), 0),
+                ("%% note", type_of("comment"), 0),
+            ]
+        );
+
+        // only the second line
+        let range = tokens(&document, Some(Span::new(19, 34)));
+        assert_eq!(
+            range,
+            [
+                token(1, 4, 3, "checkbox", &["half"]),
+                token(0, 4, 6, "tag", &[]),
+            ]
+        );
+    }
+}
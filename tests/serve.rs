@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::path::PathBuf;
+
+    use vimwiki_syntax::serve::{RELOAD_SCRIPT, Reloader, Watcher, inject, resolve, spawn};
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vimwiki-serve-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("sub dir")).unwrap();
+        std::fs::write(dir.join("index.html"), "<html><body>home</body></html>").unwrap();
+        std::fs::write(dir.join("sub dir/page.html"), "page").unwrap();
+        std::fs::write(dir.join("sub dir/index.html"), "sub").unwrap();
+        dir
+    }
+
+    #[test]
+    fn resolves_requests() {
+        let out = dir("resolve");
+        assert_eq!(resolve(&out, "/"), Some(out.join("index.html")));
+        assert_eq!(
+            resolve(&out, "/sub%20dir/"),
+            Some(out.join("sub dir/index.html"))
+        );
+        assert_eq!(
+            resolve(&out, "/sub%20dir/page?x=1#top"),
+            Some(out.join("sub dir/page.html"))
+        );
+        assert_eq!(resolve(&out, "/missing.html"), None);
+        assert_eq!(resolve(&out, "/../index.html"), None);
+        assert_eq!(resolve(&out, "/%2e%2e/index.html"), None);
+
+        assert_eq!(
+            inject("<html><body>x</body></html>"),
+            format!("<html><body>x{RELOAD_SCRIPT}\n</body></html>")
+        );
+        assert_eq!(inject("x"), format!("x{RELOAD_SCRIPT}\n"));
+    }
+
+    #[test]
+    fn watches_files() {
+        let root = dir("watch");
+        let out = root.join("out");
+        std::fs::create_dir_all(&out).unwrap();
+        let mut watcher = Watcher::new(&root, &[&out]);
+        assert!(watcher.changes().is_empty());
+
+        std::fs::write(root.join("new.wiki"), "= New =\n").unwrap();
+        std::fs::write(root.join("sub dir/page.html"), "changed page").unwrap();
+        std::fs::remove_file(root.join("index.html")).unwrap();
+        // neither the output nor hidden files are watched
+        std::fs::write(out.join("page.html"), "built").unwrap();
+        std::fs::create_dir_all(root.join(".vimwiki-syntax")).unwrap();
+        std::fs::write(root.join(".vimwiki-syntax/tags.json"), "{}").unwrap();
+        assert_eq!(
+            watcher.changes(),
+            [
+                root.canonicalize().unwrap().join("index.html"),
+                root.canonicalize().unwrap().join("new.wiki"),
+                root.canonicalize().unwrap().join("sub dir/page.html"),
+            ]
+        );
+        assert!(watcher.changes().is_empty());
+    }
+
+    #[test]
+    fn serves_and_reloads() {
+        let out = dir("http");
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let reloader = Reloader::default();
+        spawn(listener, out, reloader.clone());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: x\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/html; charset=utf-8\r\n"));
+        assert!(response.ends_with(&format!("home{RELOAD_SCRIPT}\n</body></html>")));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET /nope HTTP/1.1\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+        let mut events = TcpStream::connect(addr).unwrap();
+        events
+            .write_all(b"GET /__livereload HTTP/1.1\r\n\r\n")
+            .unwrap();
+        let mut events = BufReader::new(events);
+        let mut line = String::new();
+        while line != "\r\n" {
+            line.clear();
+            events.read_line(&mut line).unwrap();
+        }
+        while reloader.clients() == 0 {
+            std::thread::yield_now();
+        }
+        reloader.reload();
+        line.clear();
+        events.read_line(&mut line).unwrap();
+        assert_eq!(line, "data: reload\n");
+    }
+}
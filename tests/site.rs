@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    #[cfg(feature = "serde")]
+    use vimwiki_syntax::site::MANIFEST_FILE;
+    use vimwiki_syntax::site::Site;
+    use vimwiki_syntax::workspace::{Page, Workspace};
+
+    /// a wiki in a fresh temporary directory and the output directory next to it
+    fn wiki(name: &str, files: &[(&str, &str)]) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("vimwiki-site-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        for (path, text) in files {
+            let path = dir.join("wiki").join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+        (dir.join("wiki"), dir.join("out"))
+    }
+
+    #[cfg(feature = "serde")]
+    fn read(out: &std::path::Path, name: &str) -> String {
+        std::fs::read_to_string(out.join(name)).unwrap()
+    }
+
+    #[test]
+    fn rewrites_links() {
+        let (root, _) = wiki("links", &[]);
+        let workspace = Workspace::single(&root);
+        let site = Site::new(&workspace, 0, "out").css("style.css");
+        let pages = [
+            Page::new("index", "= Home =\n= Home =\n"),
+            Page::new(
+                "dir/page",
+                "[[/index#Home]] [[../index#Home-2]] [[#Sec]] [[diary:2026-10-19]] \
+                 [[local:files/a.pdf]] [[https://example.com]]\n= Sec =\n",
+            ),
+        ];
+        let html = site.render(&pages[1], &pages);
+        assert!(html.contains("<link rel=\"stylesheet\" href=\"../style.css\" />"));
+        let hrefs: Vec<_> = html
+            .split("<a href=\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .collect();
+        assert_eq!(
+            hrefs,
+            [
+                "../index.html#Home",
+                "../index.html#Home-2",
+                "#Sec",
+                "../diary/2026-10-19.html",
+                "files/a.pdf",
+                "https://example.com"
+            ]
+        );
+        assert_eq!(
+            site.files(&Page::new(
+                "dir/page",
+                "[[local:a.pdf]] {{../img/b.png|b}} {{http://x/y.png}}\n"
+            )),
+            ["dir/a.pdf".to_owned(), "img/b.png".to_owned()].into()
+        );
+    }
+
+    #[test]
+    fn generates_index_pages() {
+        let (root, _) = wiki("generated", &[]);
+        let workspace = Workspace::single(&root);
+        let site = Site::new(&workspace, 0, "out");
+        let pages = site.pages(&[vec![
+            Page::new("index", "= Home =\n:home:\n"),
+            Page::new("diary/2026-10-19", "= Monday =\n"),
+        ]]);
+        let names: Vec<_> = pages.iter().map(|page| page.name.as_str()).collect();
+        assert_eq!(names, ["diary/2026-10-19", "diary/diary", "index", "tags"]);
+        assert!(pages[1].root.text().contains("- [[2026-10-19|Monday]]"));
+        assert!(
+            pages[3]
+                .root
+                .text()
+                .contains("== home ==\n- [[index#Home]]")
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn builds_incrementally() {
+        let (root, out) = wiki(
+            "build",
+            &[
+                (
+                    "index.wiki",
+                    "= Home =\n[[dir/page#Sec|sub]] {{img/a.png}}\n",
+                ),
+                ("dir/page.wiki", "= Sec =\n"),
+                ("other.wiki", "= Other =\n"),
+                ("img/a.png", "png"),
+            ],
+        );
+        let workspace = Workspace::single(&root);
+        let site = Site::new(&workspace, 0, &out);
+
+        let report = site.build().unwrap();
+        assert_eq!(
+            report.written,
+            ["dir/page.html", "index.html", "other.html"]
+        );
+        assert_eq!(report.copied, ["img/a.png"]);
+        assert!(read(&out, "index.html").contains("<a href=\"dir/page.html#Sec\">sub</a>"));
+        assert!(out.join(MANIFEST_FILE).is_file());
+
+        let report = site.build().unwrap();
+        assert!(report.written.is_empty() && report.copied.is_empty());
+        assert_eq!(report.unchanged.len(), 4);
+
+        // a change rebuilds the page and the pages linking to it
+        std::fs::write(root.join("dir/page.wiki"), "= Sec =\nmore\n").unwrap();
+        std::fs::remove_file(root.join("other.wiki")).unwrap();
+        let report = site.build().unwrap();
+        assert_eq!(report.written, ["dir/page.html", "index.html"]);
+        assert_eq!(report.removed, ["other.html"]);
+        assert!(!out.join("other.html").exists());
+        assert!(read(&out, "dir/page.html").contains("more"));
+    }
+}
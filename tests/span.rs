@@ -1,20 +1,20 @@
 #[cfg(test)]
 mod test {
-    use neorg_syntax::span::Span;
-    use tower_lsp::lsp_types::Range;
+    use vimwiki_syntax::line_index::Range;
+    use vimwiki_syntax::span::Span;
 
     #[test]
     fn try_into_lsp_range_test_zero_based_01() {
         let input = "this is a string";
         let span = Span::new(0, input.len());
         assert_eq!(
-            span.into_zero_based_lsp_range(input).unwrap(),
+            span.into_lsp_range(input).unwrap(),
             Range {
-                start: tower_lsp::lsp_types::Position {
+                start: vimwiki_syntax::line_index::Position {
                     line: 0,
                     character: 0
                 },
-                end: tower_lsp::lsp_types::Position {
+                end: vimwiki_syntax::line_index::Position {
                     line: 0,
                     character: 16
                 }
@@ -27,13 +27,13 @@ mod test {
         let input = "this is a string \n and a newline";
         let span = Span::new(0, input.len());
         assert_eq!(
-            span.into_zero_based_lsp_range(input).unwrap(),
+            span.into_lsp_range(input).unwrap(),
             Range {
-                start: tower_lsp::lsp_types::Position {
+                start: vimwiki_syntax::line_index::Position {
                     line: 0,
                     character: 0
                 },
-                end: tower_lsp::lsp_types::Position {
+                end: vimwiki_syntax::line_index::Position {
                     line: 1,
                     character: 14
                 }
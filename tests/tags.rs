@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::ast::replace_section;
+    use vimwiki_syntax::graph::PageId;
+    use vimwiki_syntax::span::Span;
+    #[cfg(feature = "serde")]
+    use vimwiki_syntax::tags::{TagError, cache_path};
+    use vimwiki_syntax::tags::{TagIndex, TagQuery};
+    use vimwiki_syntax::workspace::Page;
+    #[cfg(feature = "serde")]
+    use vimwiki_syntax::workspace::{WikiConfig, Workspace};
+
+    fn index() -> TagIndex {
+        TagIndex::build(&[
+            vec![
+                Page::new("index", ":home:\n= Tags =\n"),
+                Page::new(
+                    "work/plan",
+                    ":work:\n= Q4 =\n:work:urgent:\n== Later ==\n:idea:\n",
+                ),
+                Page::new("ideas", "= Inbox =\n:idea:home:\n"),
+                Page::new("untagged", "text\n"),
+            ],
+            vec![Page::new("other", ":work:\n")],
+        ])
+    }
+
+    fn ids(pages: Vec<&PageId>) -> Vec<String> {
+        pages.into_iter().map(PageId::id).collect()
+    }
+
+    #[test]
+    fn records_scopes() {
+        let index = index();
+        let plan: Vec<_> = index
+            .entries()
+            .iter()
+            .filter(|e| e.page.page == "work/plan")
+            .map(|e| (e.tag.as_str(), e.heading.as_deref(), e.span))
+            .collect();
+        assert_eq!(
+            plan,
+            [
+                ("work", None, Span::new(1, 5)),
+                ("work", Some("Q4"), Span::new(15, 19)),
+                ("urgent", Some("Q4"), Span::new(20, 26)),
+                ("idea", Some("Later"), Span::new(41, 45)),
+            ]
+        );
+        assert_eq!(
+            index.tags().into_iter().collect::<Vec<_>>(),
+            ["home", "idea", "urgent", "work"]
+        );
+    }
+
+    #[test]
+    fn queries_tags() {
+        let index = index();
+        let query = |q| ids(index.query(&TagQuery::parse(q)));
+        assert_eq!(query("work"), ["work/plan", "wiki1:other"]);
+        assert_eq!(query("work & urgent"), ["work/plan"]);
+        assert_eq!(query(":idea: home"), ["ideas"]);
+        assert_eq!(query("urgent | home"), ["ideas", "index", "work/plan"]);
+        assert!(query("missing").is_empty());
+        assert!(query("").is_empty());
+    }
+
+    #[test]
+    fn generates_links() {
+        let index = index();
+        let links = "= Generated Tags =\n\n\
+            == home ==\n- [[../ideas#Inbox]]\n- [[../index]]\n\n\
+            == idea ==\n- [[../ideas#Inbox]]\n- [[#Later]]\n";
+        assert_eq!(index.links(0, "work/plan", Some(&["home", "idea"])), links);
+        assert_eq!(
+            index.links(0, "index", Some(&["work"])),
+            "= Generated Tags =\n\n== work ==\n- [[work/plan]]\n- [[work/plan#Q4]]\n"
+        );
+
+        let page = "= Plan =\n\n= Generated Tags =\n\n== old ==\n- [[gone]]\n\n= Notes =\n";
+        assert_eq!(
+            index.update_links(page, 0, "index", Some(&["urgent"])),
+            "= Plan =\n\n= Generated Tags =\n\n== urgent ==\n- [[work/plan#Q4]]\n\n= Notes =\n"
+        );
+        assert_eq!(
+            index.update_links("= Plan =\n", 0, "index", Some(&["urgent"])),
+            "= Plan =\n\n= Generated Tags =\n\n== urgent ==\n- [[work/plan#Q4]]\n"
+        );
+        assert_eq!(
+            replace_section("", "Contents", "= Contents =\n"),
+            "= Contents =\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn caches_index() {
+        let dir = std::env::temp_dir().join(format!("vimwiki-tags-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.wiki"), ":one:\n").unwrap();
+        std::fs::write(dir.join("b.wiki"), "= B =\n:two:\n").unwrap();
+        let workspace = Workspace::single(&dir);
+        let cache = cache_path(&dir);
+
+        let index = TagIndex::rebuild(&workspace, None);
+        index.save(&cache, 0).unwrap();
+        let loaded = TagIndex::load(&cache, 0).unwrap();
+        assert_eq!(loaded, index);
+
+        // a stale entry in the cache is kept as long as the page is unchanged
+        let source = std::fs::read_to_string(&cache).unwrap();
+        std::fs::write(&cache, source.replace("two", "old")).unwrap();
+        let stale = TagIndex::load(&cache, 0).unwrap();
+        let rebuilt = TagIndex::rebuild(&workspace, Some(&stale));
+        assert_eq!(
+            rebuilt.tags().into_iter().collect::<Vec<_>>(),
+            ["old", "one"]
+        );
+
+        // pages which are gone are dropped, new ones are read
+        std::fs::remove_file(dir.join("a.wiki")).unwrap();
+        std::fs::write(dir.join("c.wiki"), ":three:\n").unwrap();
+        let rebuilt = TagIndex::rebuild(&workspace, Some(&rebuilt));
+        assert_eq!(
+            rebuilt.tags().into_iter().collect::<Vec<_>>(),
+            ["old", "three"]
+        );
+
+        std::fs::write(&cache, r#"{"version":0,"pages":[]}"#).unwrap();
+        assert!(matches!(
+            TagIndex::load(&cache, 0),
+            Err(TagError::Version(0))
+        ));
+        std::fs::write(&cache, "[").unwrap();
+        assert!(matches!(TagIndex::load(&cache, 0), Err(TagError::Cache(_))));
+        let (_, errors) = TagIndex::load_caches(&workspace);
+        assert!(matches!(errors[..], [TagError::Cache(_)]));
+
+        // every wiki has a cache of its own, vimwiki's tag file is left alone
+        let second = dir.join("second");
+        std::fs::create_dir_all(&second).unwrap();
+        std::fs::write(second.join("d.wiki"), ":four:\n").unwrap();
+        std::fs::write(dir.join(".vimwiki_tags"), "vimwiki\n").unwrap();
+        let workspace = Workspace {
+            wikis: vec![WikiConfig::new(&second), WikiConfig::new(&dir)],
+        };
+        let index = TagIndex::rebuild(&workspace, None);
+        index.save_caches(&workspace).unwrap();
+        let (loaded, errors) = TagIndex::load_caches(&workspace);
+        assert!(errors.is_empty());
+        assert_eq!(loaded, index);
+        assert_eq!(
+            TagIndex::load(&cache_path(&second), 0).unwrap().tags(),
+            ["four"].into()
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.join(".vimwiki_tags")).unwrap(),
+            "vimwiki\n"
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::ast::CheckboxState;
+    use vimwiki_syntax::diary::Date;
+    use vimwiki_syntax::graph::PageId;
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::tags::TagQuery;
+    #[cfg(feature = "serde")]
+    use vimwiki_syntax::tasks::to_json;
+    use vimwiki_syntax::tasks::{Task, TaskFilter, collect, due_date, tasks};
+    use vimwiki_syntax::workspace::Page;
+
+    const PAGE: &str = "= Sprint =\n\
+        - [ ] ship release (due: 2026-10-20) :work:\n\
+        \x20 - [X] write notes\n\
+        \x20 - plain item\n\
+        \x20   - [.] nested task :work:urgent:\n\
+        - not a task\n\
+        \n\
+        == Home ==\n\
+        * [-] paint fence\n\
+        * [ ] call plumber (due: 2026-10-18)\n";
+
+    fn date(text: &str) -> Date {
+        Date::parse(text).unwrap()
+    }
+
+    #[test]
+    fn parses_due_dates() {
+        assert_eq!(
+            due_date("ship (due: 2026-10-20) today"),
+            (Some(date("2026-10-20")), "ship today".to_owned())
+        );
+        assert_eq!(
+            due_date("ship (due: soon)"),
+            (None, "ship (due: soon)".to_owned())
+        );
+        assert_eq!(due_date("ship (due:"), (None, "ship (due:".to_owned()));
+    }
+
+    #[test]
+    fn extracts_tasks() {
+        let found = tasks(&PageId::new(0, "sprint"), &parse(PAGE));
+        let summary: Vec<_> = found
+            .iter()
+            .map(|t| {
+                (
+                    t.state,
+                    t.text.as_str(),
+                    t.heading.as_deref(),
+                    t.path.join(" > "),
+                    t.line,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    CheckboxState::Open,
+                    "ship release :work:",
+                    Some("Sprint"),
+                    String::new(),
+                    2
+                ),
+                (
+                    CheckboxState::Done,
+                    "write notes",
+                    Some("Sprint"),
+                    "ship release :work:".to_owned(),
+                    3
+                ),
+                (
+                    CheckboxState::Started,
+                    "nested task :work:urgent:",
+                    Some("Sprint"),
+                    "ship release :work: > plain item".to_owned(),
+                    5
+                ),
+                (
+                    CheckboxState::Rejected,
+                    "paint fence",
+                    Some("Home"),
+                    String::new(),
+                    9
+                ),
+                (
+                    CheckboxState::Open,
+                    "call plumber",
+                    Some("Home"),
+                    String::new(),
+                    10
+                ),
+            ]
+        );
+        assert_eq!(found[0].tags, ["work"]);
+        assert_eq!(found[2].tags, ["work", "urgent"]);
+        assert_eq!(found[0].due, Some(date("2026-10-20")));
+        assert_eq!(found[1].due, None);
+        assert!(found[2].is_open());
+        assert!(!found[3].is_open());
+    }
+
+    #[test]
+    fn filters_tasks() {
+        let all = collect(&[
+            vec![Page::new("sprint", PAGE)],
+            vec![Page::new("other", "- [ ] elsewhere\n")],
+        ]);
+        assert_eq!(all.len(), 6);
+        assert_eq!(all[5].page, PageId::new(1, "other"));
+
+        let texts = |filter: TaskFilter| -> Vec<String> {
+            filter
+                .apply(&all)
+                .into_iter()
+                .map(|t: &Task| t.text.clone())
+                .collect()
+        };
+        assert_eq!(
+            texts(TaskFilter {
+                open: true,
+                ..Default::default()
+            }),
+            [
+                "ship release :work:",
+                "nested task :work:urgent:",
+                "call plumber",
+                "elsewhere"
+            ]
+        );
+        assert_eq!(
+            texts(TaskFilter {
+                tags: Some(TagQuery::parse("urgent")),
+                ..Default::default()
+            }),
+            ["nested task :work:urgent:"]
+        );
+        assert_eq!(
+            texts(TaskFilter {
+                due: Some(date("2026-10-19")),
+                ..Default::default()
+            }),
+            ["call plumber"]
+        );
+        assert_eq!(
+            texts(TaskFilter {
+                page: Some("oth".to_owned()),
+                ..Default::default()
+            }),
+            ["elsewhere"]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn writes_json() {
+        let all = collect(&[vec![Page::new("sprint", PAGE)]]);
+        let json: serde_json::Value =
+            serde_json::from_str(&to_json(&all.iter().take(3).collect::<Vec<_>>())).unwrap();
+        assert_eq!(
+            json[0],
+            serde_json::json!({
+                "wiki": 0,
+                "page": "sprint",
+                "line": 2,
+                "state": "open",
+                "text": "ship release :work:",
+                "heading": "Sprint",
+                "path": [],
+                "tags": ["work"],
+                "due": "2026-10-20",
+            })
+        );
+        assert_eq!(json[2]["state"], "started");
+        assert_eq!(json[2]["due"], serde_json::Value::Null);
+        assert_eq!(json.as_array().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::ast::{self, Link, ListItem};
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::parser::parse;
+    use vimwiki_syntax::span::Span;
+    use vimwiki_syntax::toc::{edit, entries, toc, update};
+
+    const PAGE: &str =
+        "%title Notes\n\n= Intro =\ntext\n== Setup ==\n=== Details ===\n== Usage ==\n= End =\n";
+
+    #[test]
+    fn lists_headings() {
+        let root = parse(PAGE);
+        let titles: Vec<_> = entries(&root, 2)
+            .into_iter()
+            .map(|e| (e.level, e.title))
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                (1, "Intro".to_owned()),
+                (2, "Setup".to_owned()),
+                (2, "Usage".to_owned()),
+                (1, "End".to_owned()),
+            ]
+        );
+        assert_eq!(entries(&root, 6)[0].span, Span::new(14, 24));
+        assert_eq!(toc(&parse("no headings\n"), 6), None);
+    }
+
+    #[test]
+    fn generates_nested_links() {
+        let contents = toc(&parse(PAGE), 6).unwrap();
+        assert_eq!(
+            contents,
+            "= Contents =\n\
+            - [[#Intro|Intro]]\n\
+            \x20 - [[#Setup|Setup]]\n\
+            \x20   - [[#Details|Details]]\n\
+            \x20 - [[#Usage|Usage]]\n\
+            - [[#End|End]]\n"
+        );
+
+        // the list nests like the headings
+        let root = parse(&contents);
+        let lists = ast::find_all(&root, SyntaxKind::List);
+        let top: Vec<_> = root
+            .children()
+            .iter()
+            .filter(|n| n.kind() == SyntaxKind::List)
+            .collect();
+        assert_eq!(top.len(), 1);
+        assert_eq!(lists.len(), 3);
+        let first = ListItem::cast(&top[0].children()[0]).unwrap();
+        assert_eq!(first.sublists().count(), 1);
+        let links: Vec<_> = ast::find_all(&root, SyntaxKind::Link)
+            .into_iter()
+            .filter_map(Link::cast)
+            .map(|l| l.anchor().unwrap_or_default())
+            .collect();
+        assert_eq!(links, ["Intro", "Setup", "Details", "Usage", "End"]);
+
+        assert_eq!(
+            toc(&parse("== A ==\n=== B ===\n"), 2).unwrap(),
+            "= Contents =\n- [[#A|A]]\n"
+        );
+    }
+
+    #[test]
+    fn updates_in_place() {
+        let inserted = update(PAGE, 1);
+        assert_eq!(
+            inserted,
+            "%title Notes\n\n= Contents =\n- [[#Intro|Intro]]\n- [[#End|End]]\n\n\
+            = Intro =\ntext\n== Setup ==\n=== Details ===\n== Usage ==\n= End =\n"
+        );
+        assert_eq!(edit(&parse(&inserted), 1), None);
+        assert_eq!(update(&inserted, 1), inserted);
+
+        let updated = update(&inserted, 2);
+        assert_eq!(
+            updated,
+            "%title Notes\n\n= Contents =\n- [[#Intro|Intro]]\n  - [[#Setup|Setup]]\n  \
+            - [[#Usage|Usage]]\n- [[#End|End]]\n\n\
+            = Intro =\ntext\n== Setup ==\n=== Details ===\n== Usage ==\n= End =\n"
+        );
+        assert_eq!(updated.matches("= Contents =").count(), 1);
+
+        assert_eq!(
+            update("= Contents =\n- [[#Old|Old]]\n\n== Only ==\n", 6),
+            "= Contents =\n- [[#Only|Only]]\n\n== Only ==\n"
+        );
+    }
+}
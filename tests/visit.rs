@@ -0,0 +1,118 @@
+#[cfg(test)]
+mod test {
+    use vimwiki_syntax::kind::SyntaxKind;
+    use vimwiki_syntax::parser::{Node, parse};
+    use vimwiki_syntax::span::Span;
+    use vimwiki_syntax::visit::{Rewrite, Rewriter, Visitor, Walk, rewrite, walk};
+
+    const PAGE: &str = "= One =\nsome *bold* [[link]]\n== Two ==\n- item [[other]]\n= Three =\n";
+
+    #[derive(Default)]
+    struct Outline {
+        events: Vec<String>,
+    }
+
+    impl Visitor for Outline {
+        fn enter_heading(&mut self, node: &Node) -> Walk {
+            if node.text().contains("Three") {
+                return Walk::Stop;
+            }
+            self.events.push(format!("enter {}", node.text().trim()));
+            Walk::Continue
+        }
+
+        fn leave_heading(&mut self, node: &Node) {
+            self.events.push(format!("leave {}", node.text().trim()));
+        }
+
+        fn enter_link(&mut self, node: &Node) -> Walk {
+            self.events.push(node.text());
+            Walk::Skip
+        }
+
+        fn enter_list(&mut self, _node: &Node) -> Walk {
+            Walk::Skip
+        }
+    }
+
+    #[test]
+    fn walks_in_document_order() {
+        let mut outline = Outline::default();
+        assert_eq!(walk(&parse(PAGE), &mut outline), Walk::Stop);
+        assert_eq!(
+            outline.events,
+            [
+                "enter = One =",
+                "leave = One =",
+                "[[link]]",
+                "enter == Two ==",
+                "leave == Two =="
+            ]
+        );
+    }
+
+    /// unwraps bold text and drops links
+    struct Plain;
+
+    impl Rewriter for Plain {
+        fn rewrite(&mut self, node: &Node) -> Rewrite {
+            match node.kind() {
+                SyntaxKind::Bold => Rewrite::Replace(vec![Node::leaf(
+                    SyntaxKind::Text,
+                    node.text().trim_matches('*').to_owned(),
+                    node.span(),
+                )]),
+                SyntaxKind::Link => Rewrite::Replace(Vec::new()),
+                _ => Rewrite::Keep,
+            }
+        }
+    }
+
+    #[test]
+    fn rewrites_trees() {
+        struct Nothing;
+        impl Rewriter for Nothing {}
+
+        let root = parse(PAGE);
+        assert_eq!(rewrite(&root, &mut Nothing), root);
+
+        let new = rewrite(&root, &mut Plain);
+        assert_eq!(
+            new.text(),
+            "= One =\nsome bold \n== Two ==\n- item \n= Three =\n"
+        );
+        assert_eq!(new.span(), Span::new(0, new.text().len()));
+        let paragraph = &new.children()[1];
+        assert_eq!(paragraph.kind(), SyntaxKind::Paragraph);
+        assert_eq!(
+            &new.text()[paragraph.span().start..paragraph.span().end],
+            paragraph.text()
+        );
+    }
+
+    /// empties links
+    struct Hollow;
+
+    impl Rewriter for Hollow {
+        fn fold(&mut self, node: Node) -> Node {
+            match node.kind() {
+                SyntaxKind::Link => Node::inner(SyntaxKind::Link, Vec::new()),
+                _ => node,
+            }
+        }
+    }
+
+    #[test]
+    fn places_empty_nodes_after_their_sibling() {
+        let new = rewrite(&parse(PAGE), &mut Hollow);
+        let paragraph = &new.children()[1];
+        let link = paragraph
+            .children()
+            .iter()
+            .find(|node| node.kind() == SyntaxKind::Link)
+            .unwrap();
+        assert_eq!(link.span(), Span::new(20, 20));
+        assert_eq!(paragraph.span(), Span::new(8, 21));
+        assert_eq!(link.shifted(5).span(), Span::new(25, 25));
+    }
+}
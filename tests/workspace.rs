@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use vimwiki_syntax::workspace::{Location, WikiConfig, Workspace, WorkspaceError};
+
+    const CONFIG: &str = r#"
+[[wiki]]
+name = "personal"
+root = "/home/user/vimwiki"
+
+[[wiki]]
+name = "work"
+path = "work"
+ext = ".md"
+index = "main"
+diary_rel_path = "journal"
+"#;
+
+    fn location(wiki: usize, page: &str, anchor: Option<&str>) -> Option<Location> {
+        Some(Location {
+            wiki,
+            page: page.to_owned(),
+            anchor: anchor.map(str::to_owned),
+        })
+    }
+
+    #[test]
+    fn loads_config() {
+        let workspace = Workspace::from_toml(CONFIG, Path::new("/etc/wiki")).unwrap();
+        assert_eq!(workspace.wikis.len(), 2);
+
+        let personal = &workspace.wikis[0];
+        assert_eq!(personal.root, PathBuf::from("/home/user/vimwiki"));
+        assert_eq!(personal.extension, "wiki");
+        assert_eq!(personal.index, "index");
+        assert_eq!(personal.diary, "diary");
+
+        let work = &workspace.wikis[1];
+        assert_eq!(work.name.as_deref(), Some("work"));
+        assert_eq!(work.root, PathBuf::from("/etc/wiki/work"));
+        assert_eq!(work.extension, "md");
+        assert_eq!(work.index, "main");
+        assert_eq!(work.diary, "journal");
+        assert_eq!(workspace.find("work"), Some(1));
+        assert_eq!(workspace.find("other"), None);
+
+        assert!(matches!(
+            Workspace::from_toml("", Path::new("/")),
+            Err(WorkspaceError::NoWiki)
+        ));
+        assert!(matches!(
+            Workspace::from_toml("[[wiki]]\nname = 1", Path::new("/")),
+            Err(WorkspaceError::Invalid(_))
+        ));
+        assert!(matches!(
+            Workspace::from_toml("[[wiki]\n", Path::new("/")),
+            Err(WorkspaceError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn maps_paths_to_pages() {
+        let wiki = WikiConfig::new("/wiki");
+        assert_eq!(wiki.path("dir/todo"), PathBuf::from("/wiki/dir/todo.wiki"));
+        assert_eq!(
+            wiki.page(Path::new("/wiki/dir/todo.wiki")).as_deref(),
+            Some("dir/todo")
+        );
+        assert_eq!(wiki.page(Path::new("/wiki/todo.md")), None);
+        assert_eq!(wiki.page(Path::new("/other/todo.wiki")), None);
+        assert!(wiki.is_diary("diary/2026-10-19"));
+        assert!(!wiki.is_diary("diaryish"));
+
+        let workspace = Workspace {
+            wikis: vec![WikiConfig::new("/wiki"), WikiConfig::new("/wiki/nested")],
+        };
+        assert_eq!(
+            workspace.locate(Path::new("/wiki/nested/page.wiki")),
+            Some((1, "page".to_owned()))
+        );
+        assert_eq!(
+            workspace.locate(Path::new("/wiki/page.wiki")),
+            Some((0, "page".to_owned()))
+        );
+        assert_eq!(
+            workspace.path(1, "page"),
+            Some(PathBuf::from("/wiki/nested/page.wiki"))
+        );
+        assert_eq!(workspace.path(2, "page"), None);
+    }
+
+    #[test]
+    fn resolves_links() {
+        let workspace = Workspace::from_toml(CONFIG, Path::new("/etc/wiki")).unwrap();
+        assert_eq!(
+            workspace.resolve(0, "dir/index", "todo"),
+            location(0, "dir/todo", None)
+        );
+        assert_eq!(
+            workspace.resolve(0, "dir/index", "/todo#Next Week"),
+            location(0, "todo", Some("Next Week"))
+        );
+        assert_eq!(
+            workspace.resolve(0, "dir/index", "#Tasks"),
+            location(0, "dir/index", Some("Tasks"))
+        );
+        assert_eq!(
+            workspace.resolve(0, "index", "projects/"),
+            location(0, "projects/index", None)
+        );
+        assert_eq!(
+            workspace.resolve(0, "dir/index", "wiki1:Page"),
+            location(1, "Page", None)
+        );
+        assert_eq!(
+            workspace.resolve(1, "index", "wiki1:"),
+            location(1, "main", None)
+        );
+        assert_eq!(
+            workspace.resolve(1, "index", "wn.personal:dir/Page#top"),
+            location(0, "dir/Page", Some("top"))
+        );
+        assert_eq!(
+            workspace.resolve(1, "index", "diary:2026-10-19"),
+            location(1, "journal/2026-10-19", None)
+        );
+        assert_eq!(workspace.resolve(0, "index", "wiki5:Page"), None);
+        assert_eq!(workspace.resolve(0, "index", "wn.none:Page"), None);
+        assert_eq!(workspace.resolve(0, "index", "https://example.com"), None);
+    }
+
+    #[test]
+    fn discovers_pages() {
+        let dir = std::env::temp_dir().join(format!("vimwiki-workspace-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("notes/deep")).unwrap();
+        std::fs::write(dir.join("index.wiki"), "= Index =\n").unwrap();
+        std::fs::write(dir.join("notes/deep/page.wiki"), "").unwrap();
+        std::fs::write(dir.join("notes/ignored.md"), "").unwrap();
+        // hidden directories and a symlink looping back to the root
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join(".git/hidden.wiki"), "").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&dir, dir.join("notes/loop")).unwrap();
+
+        let workspace = Workspace::discover(&dir).unwrap();
+        assert_eq!(workspace.wikis.len(), 1);
+        assert_eq!(workspace.wikis[0].pages(), ["index", "notes/deep/page"]);
+
+        std::fs::write(
+            dir.join("vimwiki.toml"),
+            "[[wiki]]\nroot = \"notes\"\n\n[[wiki]]\nroot = \".\"\next = \"md\"\n",
+        )
+        .unwrap();
+        let workspace = Workspace::discover(&dir).unwrap();
+        assert_eq!(workspace.wikis[0].pages(), ["deep/page"]);
+        assert_eq!(workspace.wikis[1].pages(), ["notes/ignored"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}